@@ -0,0 +1,296 @@
+//! # qbc
+//! Command-line companion to Question Bank Creator.  It reads a question
+//! bank (`.bnk` file), recalculates the variables in every question, and
+//! writes the rendered worksheet along with its answer key.  No windows are
+//! opened, so it can be run from scripts or on a machine without a display.
+//!
+//!     qbc --bank <file.bnk> [--versions N] [--seed N] [--only <version>]
+//!         [--format text|markdown|pdf|latex] [--output <file>] [--key <file>]
+//!         [--questions 1,3,5] [--work-lines N] [--paper letter|a4]
+//!         [--latex-class exam|article] [--answers yes|no]
+//!
+//! If `--output` is not given, the worksheets are written to stdout.  If `--key`
+//! is not given, the answer keys are written after the worksheets.  A PDF must
+//! be written to a file.  `--questions` picks which questions to use, and
+//! `--work-lines` & `--paper` set the layout of a PDF.
+//!
+//! A LaTeX document puts an `\answer{}` after each question unless `--answers no`
+//! is given;  the answers are hidden until `\printanswers` is turned on.  With
+//! `--key`, a copy with the answers printed is written there.
+//!
+//! Each version is generated from the seed and its version letter, and both are
+//! printed on the worksheet and answer key.  Running again with the same bank,
+//! `--seed`, and `--only B` reproduces Version B exactly.
+
+use question_bank_creator::banks::bnk_read_with_pathfname;
+use question_bank_creator::errors::err_join_messages;
+use question_bank_creator::export::*;
+use question_bank_creator::math_functions::math_new_seed;
+use question_bank_creator::pdf::{pdf_finish, Paper, PdfDoc};
+use std::process::exit;
+use std::fs;
+
+/// Holds the options passed on the command line.
+///
+struct CliArgs {
+    bank: String,
+    versions: usize,
+    seed: Option<u64>,
+    only: Option<usize>,
+    format: String,
+    output: Option<String>,
+    key: Option<String>,
+    questions: Option<Vec<usize>>,  // Zero-based indexes of the questions to use.
+    layout: PdfLayout,
+    latex: LatexOptions,
+}
+
+const USAGE: &str = "Usage:  qbc --bank <file.bnk> [--versions N] [--seed N] [--only <version>] \
+    [--format text|markdown|pdf|latex] [--output <file>] [--key <file>] [--questions 1,3,5] \
+    [--work-lines N] [--paper letter|a4] [--latex-class exam|article] [--answers yes|no]";
+
+fn main() {
+    let args = match cli_parse_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("qbc: {}\n{}", err, USAGE);
+            exit(2);
+        }
+    };
+
+    let usebank = match bnk_read_with_pathfname(&args.bank) {
+        Ok(usebank) => usebank,
+        Err(err) => {
+            eprintln!("qbc: {}", err);
+            exit(1);
+        }
+    };
+    let usebank = match &args.questions {
+        Some(picks) => export_select_questions(&usebank, picks),
+        None => usebank,
+    };
+
+    let seed = args.seed.unwrap_or_else(math_new_seed);
+    let versions: Vec<usize> = match args.only {
+        Some(version) => vec![version],
+        None => (0..args.versions).collect(),
+    };
+
+    // region Build each version of the worksheet and its answer key.
+    let mut worksheets = String::new();
+    let mut keys = String::new();
+    let mut sheetpdf = PdfDoc::new(args.layout.paper);
+    let mut keypdf = PdfDoc::new(args.layout.paper);
+    let mut verbanks = Vec::new();
+    for version in versions.iter().copied() {
+        let label = export_version_label(version);
+        let (verbank, failed) = export_make_version(&usebank, seed, version);
+        if !failed.is_empty() {
+            eprintln!("qbc: These variables could not be recalculated:\n{}", err_join_messages(&failed));
+            exit(1);
+        }
+        match args.format.as_str() {
+            "pdf" => export_worksheet_pdf(&mut sheetpdf, &verbank, &label, &args.layout),
+            "latex" => {}  // The whole document is written at once below.
+            _ => {
+                worksheets.push_str(&export_worksheet(&verbank, &label, &args.format));
+                keys.push_str(&export_answer_key(&verbank, &label, &args.format));
+            }
+        }
+        verbanks.push((verbank, label));
+    }
+    eprintln!("qbc: Generated {} version(s) using seed {}", versions.len(), seed);
+    // endregion
+
+    // region Write the results.
+    if args.format == "pdf" {
+        let keydoc = if args.key.is_some() { &mut keypdf } else { &mut sheetpdf };  // Else the keys follow the worksheets.
+        for (verbank, label) in verbanks.iter() {
+            export_answer_key_pdf(keydoc, verbank, label, &args.layout);
+        }
+        cli_write_bytes(args.output.as_deref().unwrap_or_default(), &pdf_finish(&sheetpdf));
+        if let Some(keypath) = &args.key {
+            cli_write_bytes(keypath, &pdf_finish(&keypdf));
+        }
+        return;
+    }
+    if args.format == "latex" {
+        cli_write(&args.output, &export_latex_document(&verbanks, &args.latex));
+        if let Some(keypath) = &args.key {
+            let keyoptions = LatexOptions { answers: true, show_answers: true, ..args.latex.clone() };
+            cli_write(&Some(keypath.clone()), &export_latex_document(&verbanks, &keyoptions));
+        }
+        return;
+    }
+    match &args.key {
+        Some(keypath) => {
+            cli_write(&args.output, &worksheets);
+            cli_write(&Some(keypath.clone()), &keys);
+        }
+        None => {
+            worksheets.push_str(&keys);
+            cli_write(&args.output, &worksheets);
+        }
+    }
+    // endregion
+}
+
+/// Reads the command-line arguments into a `CliArgs` struct.
+///
+fn cli_parse_args(argvec: Vec<String>) -> Result<CliArgs, String> {
+    let mut args = CliArgs {
+        bank: String::new(),
+        versions: 1,
+        seed: None,
+        only: None,
+        format: "text".to_string(),
+        output: None,
+        key: None,
+        questions: None,
+        layout: PdfLayout::new(),
+        latex: LatexOptions::new(),
+    };
+
+    let mut iter = argvec.into_iter();
+    while let Some(flag) = iter.next() {
+        if flag == "-h" || flag == "--help" {
+            println!("{}", USAGE);
+            exit(0);
+        }
+
+        let value = match iter.next() {
+            Some(value) => value,
+            None => return Err(format!("The option {} needs a value.", flag)),
+        };
+
+        match flag.as_str() {
+            "-b" | "--bank" => args.bank = value,
+            "-n" | "--versions" => {
+                args.versions = value.parse().map_err(|_| format!("'{}' is not a valid number of versions.", value))?;
+            }
+            "-s" | "--seed" => {
+                args.seed = Some(value.parse().map_err(|_| format!("'{}' is not a valid seed.", value))?);
+            }
+            "--only" => {
+                args.only = Some(export_version_index(&value)
+                    .ok_or_else(|| format!("'{}' is not a version letter.", value))?);
+            }
+            "-f" | "--format" => {
+                if !matches!(value.as_str(), "text" | "markdown" | "pdf" | "latex") {
+                    return Err(format!("'{}' is not a supported format.", value));
+                }
+                args.format = value;
+            }
+            "-o" | "--output" => args.output = Some(value),
+            "-k" | "--key" => args.key = Some(value),
+            "-q" | "--questions" => {
+                let picks: Result<Vec<usize>, _> = value.split(',').map(|num| num.trim().parse::<usize>()).collect();
+                match picks {
+                    Ok(picks) if !picks.is_empty() && !picks.contains(&0) => {
+                        args.questions = Some(picks.iter().map(|num| num - 1).collect());
+                    }
+                    _ => return Err(format!("'{}' is not a list of question numbers, e.g. 1,3,5.", value)),
+                }
+            }
+            "-w" | "--work-lines" => {
+                args.layout.work_lines = value.parse().map_err(|_| format!("'{}' is not a valid number of lines.", value))?;
+            }
+            "-p" | "--paper" => {
+                args.layout.paper = *Paper::ALL.iter()
+                    .find(|paper| paper.name().eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("'{}' is not a supported paper size.", value))?;
+            }
+            "--latex-class" => {
+                args.latex.class = *LatexClass::ALL.iter()
+                    .find(|class| class.name().eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("'{}' is not a supported LaTeX class.", value))?;
+            }
+            "--answers" => {
+                args.latex.answers = match value.to_lowercase().as_str() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(format!("'{}' is not yes or no.", value)),
+                };
+            }
+            _ => return Err(format!("Unknown option {}.", flag)),
+        }
+    }
+
+    if args.bank.is_empty() {
+        return Err("A bank file must be given with --bank.".to_string());
+    }
+    if args.versions == 0 {
+        return Err("At least one version must be generated.".to_string());
+    }
+    if args.format == "pdf" && args.output.is_none() {
+        return Err("A PDF must be written to a file given with --output.".to_string());
+    }
+    args.latex.work_lines = args.layout.work_lines;
+
+    Ok(args)
+}
+
+/// Writes text to the given file, or to stdout if no file is given.
+///
+fn cli_write(path: &Option<String>, text: &str) {
+    match path {
+        Some(path) => {
+            if let Err(err) = fs::write(path, text) {
+                eprintln!("qbc: Could not write to {}: {}", path, err);
+                exit(1);
+            }
+        }
+        None => print!("{}", text),
+    }
+}
+
+/// Writes the bytes of a binary file, such as a PDF.
+///
+fn cli_write_bytes(path: &str, bytes: &[u8]) {
+    if let Err(err) = fs::write(path, bytes) {
+        eprintln!("qbc: Could not write to {}: {}", path, err);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<CliArgs, String> {
+        cli_parse_args(line.split_whitespace().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn options_are_read() {
+        let args = parse("--bank math.bnk -n 3 --seed 42 --only c -f latex --questions 1,3,5 --paper A4 --answers NO").unwrap();
+        assert_eq!(args.bank, "math.bnk");
+        assert_eq!(args.versions, 3);
+        assert_eq!(args.seed, Some(42));
+        assert_eq!(args.only, Some(2));
+        assert_eq!(args.format, "latex");
+        assert_eq!(args.questions, Some(vec![0, 2, 4]));  // Numbered from 1 on the command line.
+        assert_eq!(args.layout.paper, Paper::A4);
+        assert!(!args.latex.answers);
+    }
+
+    #[test]
+    fn bad_options_are_errors() {
+        for line in ["--bank b.bnk --questions 0", "--bank b.bnk --questions 2,0", "--bank b.bnk --questions ,",
+                     "--bank b.bnk --questions x", "--bank b.bnk --questions -1"] {
+            assert!(parse(line).err().unwrap().contains("is not a list of question numbers"), "{}", line);
+        }
+        let blank = vec!["--bank".to_string(), "b.bnk".to_string(), "--questions".to_string(), " ".to_string()];
+        assert!(cli_parse_args(blank).err().unwrap().contains("is not a list of question numbers"));
+
+        assert_eq!(parse("--bank b.bnk --only 2").err(), Some("'2' is not a version letter.".to_string()));
+        assert_eq!(parse("--bank b.bnk --only B2").err(), Some("'B2' is not a version letter.".to_string()));
+        assert_eq!(parse("--bank b.bnk --format pdf").err(),
+                   Some("A PDF must be written to a file given with --output.".to_string()));
+        assert!(parse("--bank b.bnk --format pdf --output b.pdf").is_ok());
+        assert_eq!(parse("--bank b.bnk --seed").err(), Some("The option --seed needs a value.".to_string()));
+        assert_eq!(parse("--versions 2").err(), Some("A bank file must be given with --bank.".to_string()));
+        assert_eq!(parse("--bank b.bnk -n 0").err(), Some("At least one version must be generated.".to_string()));
+        assert_eq!(parse("--bank b.bnk --colour red").err(), Some("Unknown option --colour.".to_string()));
+    }
+}
@@ -31,11 +31,6 @@
        // TODO: Goal is for  WYSIWIG.  --  in the far, far future.
 
        // TODO: Add second line to the title containing the associated textbook text.
-       // TODO: Question display should show calculated values for the variables
-       //          rather than the variable ID.  Maybe highlight the values so
-       //          that the variable can be easily located.  May also want to set up
-       //          a hover over the value/variable to show the variable's value/variable name.
-
        // TODO: Refine the implementation of how you use LastDirUsed.  It is currently
        //          inconsistent in how it is applied.  Check every instance where a
        //          file is read or written -- primarily in the *_read() and *_save()
@@ -49,15 +44,21 @@
 */
 // TODO's
 
+// Note:  Everything that uses FLTK is only compiled when the `gui` feature is
+//          enabled (it is by default).  Build with  `--no-default-features`  to get
+//          just the core library -- the structs, generation, formulas, & file I/O.
+
+use crate::config::{cnfg_path, Config};
+#[cfg(feature = "gui")]
 use crate::banks::Bank;
-use fltk::app::App;
-use fltk::group::Scroll;
+#[cfg(feature = "gui")]
+use fltk::{app::App, group::Scroll, window, window::Window};
+#[cfg(feature = "gui")]
 use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+#[cfg(feature = "gui")]
 use fltk::text::{TextDisplay, TextEditor};
-use fltk::utils::oncelock::Lazy;
-use fltk::window;
-use fltk::window::Window;
-use std::sync::Mutex;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
 
 // region  Global Constants
 
@@ -71,21 +72,8 @@ pub const PROGRAM_TITLE: &str = "Question Bank Creator";
 ///
 pub const VERSION: &str = "0.29.8"; // Note:  Versioning, while semantic in format, is decimal in nature.
 
-// Note:  The constants below are here during development.  They will be moved to
-//          a config file once the program is more stable.
-
-/// The default folder where data is saved.
-///
-pub const DATA_GENERAL_FOLDER: &str = "/home/jtreagan/programming/mine/qbnk_rb7/src/qbnk_data";
-/// The default folder for saving Lists.
-///
-pub const LIST_DIR: &str = "/home/jtreagan/programming/mine/qbnk_rb7/src/qbnk_data/lists";
-/// The default folder for saving Variables.
-///
-pub const VARIABLE_DIR: &str = "/home/jtreagan/programming/mine/qbnk_rb7/src/qbnk_data/variables";
-/// The default folder for saving Banks.
-///
-pub const BANK_DIR: &str = "/home/jtreagan/programming/mine/qbnk_rb7/src/qbnk_data/banks";
+// Note:  The data folders, fonts, & window geometry are in the configuration
+//          file.  See the `config` module.
 
 /// Default height of the question display.
 ///
@@ -96,23 +84,36 @@ pub const SCROLLBAR_WIDTH: i32 = 15;
 // endregion
 
 //region Global Variables
-/// Contains the question Bank that is currently being edited.
+/// Holds the settings read from the configuration file.
 ///
-pub static CURRENT_BANK: Lazy<Mutex<Bank>> = Lazy::new(|| Mutex::new(Bank::new()));
-/// Contains the last directory path that was used.
+pub static CONFIG: LazyLock<Mutex<Config>> = LazyLock::new(|| Mutex::new(Config::new()));
+/// The path of the configuration file that is in use.
 ///
-pub static LAST_DIR_USED: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
+pub static CONFIG_PATH: LazyLock<Mutex<PathBuf>> = LazyLock::new(|| Mutex::new(cnfg_path(None)));
+
+// The rest are GUI only.
+/// Contains the question Bank that is currently being edited.
+///
+#[cfg(feature = "gui")]
+pub static CURRENT_BANK: LazyLock<Mutex<Bank>> = LazyLock::new(|| Mutex::new(Bank::new()));
 /// Holds the currently running FLTK App.
 ///
-pub static APP_FLTK: Lazy<Mutex<App>> = Lazy::new(|| Mutex::new(App::default()));
+#[cfg(feature = "gui")]
+pub static APP_FLTK: LazyLock<Mutex<App>> = LazyLock::new(|| Mutex::new(App::default()));
 /// Holds the FLTK widgets currently being used.
 ///
-pub static WIDGETS: Lazy<Mutex<Wdgts>> = Lazy::new(|| Mutex::new(Wdgts::new()));
+#[cfg(feature = "gui")]
+pub static WIDGETS: LazyLock<Mutex<Wdgts>> = LazyLock::new(|| Mutex::new(Wdgts::new()));
+/// When `true`, the question display shows the calculated values of the
+/// variables rather than their flagged names.
+#[cfg(feature = "gui")]
+pub static SHOW_VALUES: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(true));
 //endregion
 
-// region Global Structs & Enums
+// region Global Structs & Enums  (GUI only)
 /// Struct that holds the primary window's widgets.
 ///
+#[cfg(feature = "gui")]
 pub struct Wdgts {
     pub prim_win: Window,
     pub title_editbox: TextEditor,
@@ -120,11 +121,13 @@ pub struct Wdgts {
     pub qstn_boxes: Vec<TextDisplay>,
 }
 
+#[cfg(feature = "gui")]
 impl Default for Wdgts {
     fn default() -> Self {
-        let prim_win = window::Window::new(1100, 200, 825, 900, PROGRAM_TITLE)
-            .with_size(825, 900)
-            .with_pos(1100, 200);
+        let config = CONFIG.lock().unwrap().clone();
+        let prim_win = window::Window::new(config.win_x, config.win_y, config.win_w, config.win_h, PROGRAM_TITLE)
+            .with_size(config.win_w, config.win_h)
+            .with_pos(config.win_x, config.win_y);
         prim_win.end();
 
         Self {
@@ -136,12 +139,14 @@ impl Default for Wdgts {
     }
 }
 
+#[cfg(feature = "gui")]
 impl Wdgts {
     pub fn new() -> Self {
         Self::default()
     }
 }
 
+#[cfg(feature = "gui")]
 impl Clone for Wdgts {
     fn clone(&self) -> Self {
         Self {
@@ -159,93 +164,532 @@ impl Clone for Wdgts {
 ///
 pub mod global {
 
-    use crate::{DATA_GENERAL_FOLDER, LAST_DIR_USED};
-    use lib_file::dir_mngmnt::dir_normalize_path;
+    use crate::math_functions::Ratio;
     use serde::{Deserialize, Serialize};
+    #[cfg(feature = "gui")]
+    pub use gui::*;
 
-    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
     pub enum TypeWrapper {
         Alphanum(String),
         Letter(char),
         Integer(i64),
         Floating(f64),
+        Rational(Ratio),
     }
 
-    /// This function retrieves the last directory used and ensures it is
-    ///     normalized as a proper directory path.
-    ///
-    /// If the `LAST_DIR_USED` global variable is empty, it sets it to
-    /// the default directory specified by `BANK_DIR`. It then normalizes
-    /// the path of the directory to ensure it ends in a valid directory
-    /// format (and not a file).
-    ///
-    /// # Returns
-    ///
-    /// * A `String` representing the normalized directory path.
-    ///
-    /// # Behavior
-    ///
-    /// - If `LAST_DIR_USED` is empty, it initializes it to the `BANK_DIR` value, a
-    ///     global constant that specifies the default directory for question banks.
-    /// - Ensures the directory path is properly normalized using the helper
-    ///     function `dir_normalize_path`.
-    /// - Makes use of a global variable `LAST_DIR_USED` that is protected by
-    ///     a `Mutex` for thread-safe operations.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if the `Mutex` lock on `LAST_DIR_USED` cannot be obtained,
-    /// or if there is a logic failure when attempting to normalize the directory path.
-    ///
-    /// # Dependencies
-    ///
-    /// This function uses the directory management functions in the `lib_file` crate.
-    ///
-    /// # Example
-    ///
-    ///     use std::sync::Mutex;
-    ///     use fltk::utils::oncelock::Lazy;
-    ///     use lib_file::dir_mngmnt::dir_normalize_path;
-    ///
-    ///     pub const BANK_DIR: &str = "/home/jtreagan/programming/mine/qbnk_rb7/src/qbnk_data/banks";
-    ///     pub static LAST_DIR_USED: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
-    ///
-    ///     fn main() {
-    ///         *LAST_DIR_USED.lock().unwrap() = "/home/jtreagan/programming/mine/deleteme3/somefile.fde".to_string().clone();
-    ///         let path = glob_check_lastdirused();
-    ///         println!("\n {:?} \n", path);
-    ///     }
+    /// Global helpers that only the GUI uses.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use crate::config::cnfg_store;
+        use crate::CONFIG;
+        use lib_file::dir_mngmnt::dir_normalize_path;
+
+        /// This function retrieves the last directory used for a kind of file and
+        ///     ensures it is normalized as a proper directory path.
+        ///
+        /// The `kind` is "Banks", "Lists", or "Variables".  Any other kind uses
+        /// the general data folder.  If no directory has been used yet for that
+        /// kind, the kind's default folder from the configuration file is used.
+        ///
+        /// # Returns
+        ///
+        /// * A `String` representing the normalized directory path.
+        ///
+        /// # Example
+        ///
+        ///     use question_bank_creator::global::glob_check_lastdirused;
+        ///
+        ///     let path = glob_check_lastdirused("Banks");
+        ///     println!("\n {:?} \n", path);
+        ///
+        pub fn glob_check_lastdirused(kind: &str) -> String {
+            let usedir = CONFIG.lock().unwrap().last_dir(kind).to_string();
+
+            dir_normalize_path(&usedir)  // Makes sure the path ends on a folder, not a file.
+        }
+
+        /// Records the directory of `path` as the last one used for a kind of
+        /// file and saves it in the configuration file for the next session.
+        pub fn glob_update_lastdirused(kind: &str, path: &str) {
+            let purepath = dir_normalize_path(path);  // Truncate any file name.
+            {
+                CONFIG.lock().unwrap().set_last_dir(kind, &purepath);
+            }
+            cnfg_store();
+        }
+
+    } // End   gui   submodule
+
+} // End   global   module
+
+/// Holds the QbcError enum returned by the functions that load, save,
+/// and regenerate Banks, Variables, and Lists.
+///
+pub mod errors {
+    use std::{error, fmt, io};
+
+    /// Everything that can go wrong while reading, writing, or
+    /// recalculating QBC data.  The `Display` text is meant to be
+    /// shown to the user as is.
+    #[derive(Debug)]
+    pub enum QbcError {
+        Io { path: String, source: io::Error },
+        Json { path: String, source: serde_json::Error },
+        MissingVariable { name: String, path: String },
+        ListTypeMismatch { list: String, expected: String, found: String },
+        InvalidParameters { name: String, reason: String },
+        InvalidListEntry { entry: String, typechoice: String },
+        UnmetConstraint { constraint: String, reason: String },
+        UnitMismatch { formula: String, reason: String },
+        RepeatedChoice { option: String, value: String },
+    }
+
+    impl QbcError {
+        /// Wraps an I/O error together with the path that caused it.
+        pub fn io(path: &str, source: io::Error) -> QbcError {
+            QbcError::Io { path: path.to_string(), source }
+        }
+
+        /// Wraps a json error together with the path of the file being read or written.
+        pub fn json(path: &str, source: serde_json::Error) -> QbcError {
+            QbcError::Json { path: path.to_string(), source }
+        }
+
+        /// Builds an InvalidParameters error for the named Variable.
+        pub fn invalid(name: &str, reason: &str) -> QbcError {
+            QbcError::InvalidParameters { name: name.to_string(), reason: reason.to_string() }
+        }
+    } // End   QbcError   impl
+
+    impl fmt::Display for QbcError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                QbcError::Io { path, source } => {
+                    write!(f, "The file {} could not be read or written:  {}", path, source)
+                }
+                QbcError::Json { path, source } => {
+                    write!(f, "The file {} is not a valid QBC file:  {}", path, source)
+                }
+                QbcError::MissingVariable { name, path } => {
+                    write!(f, "The Variable file {} was not found in {}.", name, path)
+                }
+                QbcError::ListTypeMismatch { list, expected, found } => {
+                    write!(f, "The list {} holds {}, but {} are needed.", list, found, expected)
+                }
+                QbcError::InvalidParameters { name, reason } if name.is_empty() => {
+                    write!(f, "The Variable's parameters are not valid:  {}", reason)
+                }
+                QbcError::InvalidParameters { name, reason } => {
+                    write!(f, "The Variable {} has invalid parameters:  {}", name, reason)
+                }
+                QbcError::InvalidListEntry { entry, typechoice } => {
+                    write!(f, "'{}' can't be used in a list of {}.", entry, typechoice)
+                }
+                QbcError::UnmetConstraint { constraint, reason } => {
+                    write!(f, "The condition  {}  could not be met:  {}", constraint, reason)
+                }
+                QbcError::UnitMismatch { formula, reason } => {
+                    write!(f, "The formula  {}  has mismatched units:  {}", formula, reason)
+                }
+                QbcError::RepeatedChoice { option, value } => {
+                    write!(f, "The option  {}  came out as  {},  the same as another option.", option, value)
+                }
+            }
+        }
+    }
+
+    /// Joins the messages of several errors into one block of text,
+    /// one message per line, for showing in a dialog or on stderr.
+    pub fn err_join_messages(errs: &[QbcError]) -> String {
+        errs.iter().map(|err| err.to_string()).collect::<Vec<String>>().join("\n")
+    }
+
+    impl error::Error for QbcError {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            match self {
+                QbcError::Io { source, .. } => Some(source),
+                QbcError::Json { source, .. } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+} // End   errors   module
+
+/// Holds the Config struct and the functions that find, read, and write
+/// the configuration file.
+///
+pub mod config {
+    use crate::errors::QbcError;
+    use serde::{Deserialize, Serialize};
+    use std::path::PathBuf;
+    use std::{env, fs};
+    #[cfg(feature = "gui")]
+    pub use gui::*;
+
+    /// Environment variable that gives the path of the configuration file.
+    /// It is overridden by the `--config` command-line flag.
+    pub const CONFIG_ENV_VAR: &str = "QBC_CONFIG";
+
+    //region Struct Section
+
+    /// The last directory used for each kind of file.  An empty
+    /// string means the kind's default folder should be used.
+    #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+    #[serde(default)]
+    pub struct LastDirs {
+        pub banks: String,
+        pub lists: String,
+        pub variables: String,
+        pub general: String,
+    }
+
+    /// User settings that are saved between sessions.  Any field missing
+    /// from the file is given its default value.
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    #[serde(default)]
+    pub struct Config {
+        pub data_dir: String,
+        pub bank_dir: String,
+        pub list_dir: String,
+        pub variable_dir: String,
+        pub font_name: String,  // One of FLTK's font names, e.g. "Helvetica".
+        pub font_size: i32,     // Used for menus & dialogs.
+        pub text_size: i32,     // Used for question text.
+        pub win_x: i32,
+        pub win_y: i32,
+        pub win_w: i32,
+        pub win_h: i32,
+        pub last_dirs: LastDirs,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Config {
+        /// Initialize a Config with the default settings.  The data folders
+        /// are placed in the user's XDG data directory.
+        pub fn new() -> Config {
+            let data_dir = cnfg_default_data_dir();
+            Self {
+                bank_dir: format!("{}/banks", data_dir),
+                list_dir: format!("{}/lists", data_dir),
+                variable_dir: format!("{}/variables", data_dir),
+                data_dir,
+                font_name: "Helvetica".to_string(),
+                font_size: 20,
+                text_size: 22,
+                win_x: 1100,
+                win_y: 200,
+                win_w: 825,
+                win_h: 900,
+                last_dirs: LastDirs::default(),
+            }
+        }
+
+        /// Returns the default folder for a kind of file:
+        /// "Banks", "Lists", or "Variables".  Any other kind gets `data_dir`.
+        pub fn kind_dir(&self, kind: &str) -> &str {
+            match kind {
+                "Banks" => &self.bank_dir,
+                "Lists" => &self.list_dir,
+                "Variables" => &self.variable_dir,
+                _ => &self.data_dir,
+            }
+        }
+
+        /// Returns the last directory used for a kind of file, falling back
+        /// to the kind's default folder if none has been used yet.
+        pub fn last_dir(&self, kind: &str) -> &str {
+            let lastdir = match kind {
+                "Banks" => &self.last_dirs.banks,
+                "Lists" => &self.last_dirs.lists,
+                "Variables" => &self.last_dirs.variables,
+                _ => &self.last_dirs.general,
+            };
+            if lastdir.is_empty() { self.kind_dir(kind) } else { lastdir }
+        }
+
+        /// Records the last directory used for a kind of file.
+        ///
+        pub fn set_last_dir(&mut self, kind: &str, dir: &str) {
+            let lastdir = match kind {
+                "Banks" => &mut self.last_dirs.banks,
+                "Lists" => &mut self.last_dirs.lists,
+                "Variables" => &mut self.last_dirs.variables,
+                _ => &mut self.last_dirs.general,
+            };
+            *lastdir = dir.to_string();
+        }
+    } // End   Config   impl
+
+    //endregion
+
+    /// Finds the configuration file.  In order of preference:  the path given
+    /// on the command line, the `QBC_CONFIG` environment variable, then
+    /// `question_bank_creator/config.json` in the XDG config directory.
+    pub fn cnfg_path(cli_path: Option<&str>) -> PathBuf {
+        if let Some(path) = cli_path {
+            return PathBuf::from(path);
+        }
+        if let Ok(path) = env::var(CONFIG_ENV_VAR) && !path.is_empty() {
+            return PathBuf::from(path);
+        }
+        cnfg_xdg_dir("XDG_CONFIG_HOME", ".config").join("question_bank_creator").join("config.json")
+    }
+
+    /// The default folder for QBC data:  `question_bank_creator` in the XDG data directory.
     ///
+    pub fn cnfg_default_data_dir() -> String {
+        cnfg_xdg_dir("XDG_DATA_HOME", ".local/share")
+            .join("question_bank_creator")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Returns the directory named by an XDG environment variable, or the
+    /// given fallback inside the user's home directory.
+    fn cnfg_xdg_dir(envvar: &str, fallback: &str) -> PathBuf {
+        match env::var(envvar) {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var("HOME").unwrap_or_default()).join(fallback),
+        }
+    }
+
+    /// Reads the configuration file.  If the file doesn't exist yet,
+    /// the default settings are returned.
+    pub fn cnfg_load(path: &PathBuf) -> Result<Config, QbcError> {
+        let pathtxt = path.to_string_lossy();
+        if !path.exists() {
+            return Ok(Config::new());
+        }
+        let contents = fs::read_to_string(path).map_err(|err| QbcError::io(&pathtxt, err))?;
+        serde_json::from_str(&contents).map_err(|err| QbcError::json(&pathtxt, err))
+    }
+
+    /// Saves the configuration file in json format, creating its folder if needed.
     ///
-    pub fn glob_check_lastdirused() -> String {
-        if LAST_DIR_USED.lock().unwrap().clone() == "" {  // Set to default directory.
-            *LAST_DIR_USED.lock().unwrap() = DATA_GENERAL_FOLDER.to_string().clone();
+    pub fn cnfg_save(config: &Config, path: &PathBuf) -> Result<(), QbcError> {
+        let pathtxt = path.to_string_lossy();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| QbcError::io(&pathtxt, err))?;
         }
-        let usedir = LAST_DIR_USED.lock().unwrap().clone();
+        let cnfg_as_json = serde_json::to_string_pretty(config).map_err(|err| QbcError::json(&pathtxt, err))?;
+        fs::write(path, cnfg_as_json).map_err(|err| QbcError::io(&pathtxt, err))
+    }
 
-        dir_normalize_path(&usedir)  // Makes sure the path ends on a folder, not a file.
+    /// Creates the data folders named in a Config if they don't exist.
+    ///
+    pub fn cnfg_make_data_dirs(config: &Config) -> Result<(), QbcError> {
+        for dir in [&config.data_dir, &config.bank_dir, &config.list_dir, &config.variable_dir] {
+            fs::create_dir_all(dir).map_err(|err| QbcError::io(dir, err))?;
+        }
+        Ok(())
     }
 
+    /// FLTK startup & Settings dialog for the configuration.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::{CONFIG, CONFIG_PATH, WIDGETS};
+        use fltk::app;
+        use fltk::button::Button;
+        use fltk::enums::Font;
+        use fltk::input::{Input, IntInput};
+        use fltk::menu::Choice;
+        use fltk::prelude::{GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::{frame::Frame, window::Window};
+        use lib_file::file_fltk::file_pathonly;
+        use lib_myfltk::fltkutils::fltk_custom_message;
+
+        /// Loads the configuration file into the CONFIG global and applies the
+        /// font settings.  Call once at startup, before any windows are made.
+        pub fn cnfg_startup(cli_path: Option<&str>) {
+            let path = cnfg_path(cli_path);
+            let config = match cnfg_load(&path) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("\n Error reading the configuration file: {} \n", err);
+                    eprintln!(" The default settings will be used. \n");
+                    Config::new()
+                }
+            };
+            if let Err(err) = cnfg_make_data_dirs(&config) {
+                eprintln!("\n Error creating the data folders: {} \n", err);
+            }
 
+            cnfg_apply_fonts(&config);
+            {
+                *CONFIG.lock().unwrap() = config;
+                *CONFIG_PATH.lock().unwrap() = path;
+            }
+        }
 
-} // End   global   module
+        /// Sets the app's font & font size from a Config.
+        ///
+        pub fn cnfg_apply_fonts(config: &Config) {
+            app::set_font(Font::by_name(&config.font_name));
+            app::set_font_size(config.font_size);
+        }
+
+        /// Writes the CONFIG global to the configuration file.
+        ///
+        pub fn cnfg_store() {
+            let config;
+            let path;
+            {
+                config = CONFIG.lock().unwrap().clone();
+                path = CONFIG_PATH.lock().unwrap().clone();
+            }
+            if let Err(err) = cnfg_save(&config, &path) {
+                eprintln!("\n Error saving the configuration file: {} \n", err);
+            }
+        }
+
+        /// Records the primary window's position & size so it opens in
+        /// the same place next time, then saves the configuration.
+        pub fn cnfg_store_geometry() {
+            let win = WIDGETS.lock().unwrap().prim_win.clone();
+            {
+                let mut config = CONFIG.lock().unwrap();
+                config.win_x = win.x();
+                config.win_y = win.y();
+                config.win_w = win.w();
+                config.win_h = win.h();
+            }
+            cnfg_store();
+        }
+
+        /// Opens the Settings dialog, which lets the user change the data
+        /// folders, fonts, and window geometry.  The changes are saved to
+        /// the configuration file when `Save` is pressed.
+        pub fn cnfg_settings_dialog() {
+            let config = CONFIG.lock().unwrap().clone();
+
+            let mut win = Window::default().with_size(700, 560).with_label("Settings");
+            win.make_modal(true);
+
+            // region Folder inputs, each with a Browse button.
+            let labels = ["Data folder:", "Banks folder:", "Lists folder:", "Variables folder:"];
+            let values = [&config.data_dir, &config.bank_dir, &config.list_dir, &config.variable_dir];
+            let mut dir_inputs: Vec<Input> = Vec::new();
+            for (idx, (label, value)) in labels.iter().zip(values.iter()).enumerate() {
+                let yyy = 20 + idx as i32 * 50;
+                let mut dirinput = Input::new(180, yyy, 390, 35, *label);
+                dirinput.set_value(value);
+                let mut browsebtn = Button::new(580, yyy, 100, 35, "Browse");
+                let mut dirinput_clone = dirinput.clone();
+                browsebtn.set_callback(move |_| {
+                    let path = file_pathonly(&dirinput_clone.value(), "Choose a folder.");
+                    if !path.is_empty() {
+                        dirinput_clone.set_value(&path);
+                    }
+                });
+                dir_inputs.push(dirinput);
+            }
+            // endregion
+
+            // region Font inputs.
+            let mut fontchoice = Choice::new(180, 230, 250, 35, "Font:");
+            let fontnames = ["Helvetica", "Times", "Courier", "Screen"];
+            for name in fontnames.iter() {
+                fontchoice.add_choice(name);
+            }
+            fontchoice.set_value(fontnames.iter().position(|name| *name == config.font_name).unwrap_or(0) as i32);
+
+            let mut fontsize = IntInput::new(180, 280, 80, 35, "Menu font size:");
+            fontsize.set_value(&config.font_size.to_string());
+            let mut textsize = IntInput::new(450, 280, 80, 35, "Question text size:");
+            textsize.set_value(&config.text_size.to_string());
+            // endregion
+
+            // region Window geometry inputs.
+            Frame::new(20, 335, 660, 30, "Main window position & size:");
+            let geometry = [("X:", config.win_x), ("Y:", config.win_y), ("Width:", config.win_w), ("Height:", config.win_h)];
+            let mut geom_inputs: Vec<IntInput> = Vec::new();
+            for (idx, (label, value)) in geometry.iter().enumerate() {
+                let mut geominput = IntInput::new(100 + idx as i32 * 150, 375, 80, 35, *label);
+                geominput.set_value(&value.to_string());
+                geom_inputs.push(geominput);
+            }
+            // endregion
+
+            let mut savebtn = Button::new(220, 480, 120, 40, "Save");
+            let mut cancelbtn = Button::new(360, 480, 120, 40, "Cancel");
+
+            win.end();
+            win.show();
+
+            // region Callbacks
+            let mut win_clone = win.clone();
+            cancelbtn.set_callback(move |_| win_clone.hide());
+
+            let mut win_clone = win.clone();
+            savebtn.set_callback(move |_| {
+                let sizes: Vec<Option<i32>> = [&fontsize, &textsize]
+                    .iter()
+                    .chain(geom_inputs.iter().collect::<Vec<&IntInput>>().iter())
+                    .map(|input| input.value().trim().parse::<i32>().ok().filter(|num| *num >= 0))
+                    .collect();
+                if sizes.iter().any(|size| size.is_none()) {
+                    fltk_custom_message("The sizes and positions must be whole numbers.", "Return to Settings.");
+                    return;  // Leave the dialog open so the values can be fixed.
+                }
+                let sizes: Vec<i32> = sizes.into_iter().flatten().collect();
+
+                {
+                    let mut config = CONFIG.lock().unwrap();
+                    config.data_dir = dir_inputs[0].value();
+                    config.bank_dir = dir_inputs[1].value();
+                    config.list_dir = dir_inputs[2].value();
+                    config.variable_dir = dir_inputs[3].value();
+                    config.font_name = fontchoice.choice().unwrap_or("Helvetica".to_string());
+                    config.font_size = sizes[0];
+                    config.text_size = sizes[1];
+                    config.win_x = sizes[2];
+                    config.win_y = sizes[3];
+                    config.win_w = sizes[4];
+                    config.win_h = sizes[5];
+
+                    cnfg_apply_fonts(&config);
+                    if let Err(err) = cnfg_make_data_dirs(&config) {
+                        fltk_custom_message(&err.to_string(), "Return.");
+                    }
+                }
+                {
+                    let mut primwin = WIDGETS.lock().unwrap().prim_win.clone();
+                    primwin.resize(sizes[2], sizes[3], sizes[4], sizes[5]);
+                }
+                cnfg_store();
+                win_clone.hide();
+            });
+            // endregion
+
+            while win.shown() {
+                app::wait();
+            }
+        }
+
+    } // End   gui   submodule
+
+} // End   config   module
 
 /// Functions that deal with the Bank struct.
 ///
 pub mod banks {
-    use std::fs::File;
-    use std::io::Write;
-    use crate::misc::{dir_is_empty, make_question_boxes, make_scrollgroup, make_title_txtedtr};
-    use crate::{questions::*, Wdgts, APP_FLTK, CURRENT_BANK, LAST_DIR_USED, WIDGETS};
-    use fltk::dialog::{choice2_default, message_title};
-    use fltk::prelude::{DisplayExt, GroupExt, WidgetExt};
-    use fltk::text::TextBuffer;
-    use lib_file::{dir_mngmnt::dir_normalize_path, file_fltk::*};
-    use lib_file::file_mngmnt::file_read_to_string;
-    use lib_myfltk::fltkutils::fltk_custom_message;
-    use lib_myfltk::input_fltk::*;
+    use crate::errors::QbcError;
+    use crate::math_functions::{math_seeded_rng, math_version_seed, NumLocale};
+    use crate::questions::*;
+    use crate::variable::{vrbl_detach, vrbl_embed_list, vrbl_is_from_file, vrbl_sync_from_library};
+    use rand::Rng;
     use serde::{Deserialize, Serialize};
-    use crate::global::glob_check_lastdirused;
+    use std::fs;
+    #[cfg(feature = "gui")]
+    pub use gui::*;
 
     //region Struct Section
 
@@ -258,6 +702,14 @@ pub mod banks {
         pub bank_title: String,          // Also used for file name.
         pub associated_textbook: String, // Use  ""  if no text being used.
         pub question_vec: Vec<Question>,
+        #[serde(default)]
+        pub seed: Option<u64>,  // Seed & version the current values came from.  See `bnk_recalc_seeded()`.
+        #[serde(default)]
+        pub seed_version: usize,
+        #[serde(default)]
+        pub locale: NumLocale,  // How numbers are written in this Bank's questions & answers.
+        #[serde(skip)]
+        pub dirty: bool,  // `true` when there are changes that haven't been saved.
     }
 
     impl Default for Bank {
@@ -273,6 +725,10 @@ pub mod banks {
                 bank_title: "No Bank Loaded".to_string(),
                 associated_textbook: "Untitled Textbook".to_string(),
                 question_vec: Vec::new(),
+                seed: None,
+                seed_version: 0,
+                locale: NumLocale::new(),
+                dirty: false,
             }
         }
     }
@@ -284,6 +740,10 @@ pub mod banks {
                 bank_title: self.bank_title.clone(),
                 associated_textbook: self.associated_textbook.clone(),
                 question_vec: self.question_vec.clone(), // Vec itself does implement Clone.
+                seed: self.seed,
+                seed_version: self.seed_version,
+                locale: self.locale.clone(),
+                dirty: self.dirty,
             }
         }
     }
@@ -291,249 +751,783 @@ pub mod banks {
     //endregion
 
 
-    /// Checks if a bank is currently loaded.
-    ///
-    /// This function determines whether a bank is loaded in memory by checking
-    /// the `CURRENT_BANK` global variable. If the `bank_title` of the current bank
-    /// is anything other than "No Bank Loaded", the function returns `true`, indicating
-    /// that a bank is loaded. Otherwise, it returns `false`.
-    ///
-    /// # Returns
-    ///
-    /// * `true` - if a bank is loaded.
-    /// * `false` - if no bank is currently loaded.
-    ///
-    /// # Example
-    /// main() {
-    ///     if bnk_check_loaded() {
-    ///         println!("A bank is loaded in memory.");
-    ///     } else {
-    ///         println!("No bank is loaded.");
-    ///     }
-    /// }
-    /// # Note
+    /// Reads a question bank from the file at the given path without
+    /// showing any dialogs.  Used by the command-line tools.
+    pub fn bnk_read_with_pathfname(readpath: &str) -> Result<Bank, QbcError> {
+        let contents = fs::read_to_string(readpath).map_err(|err| QbcError::io(readpath, err))?;
+        serde_json::from_str(&contents).map_err(|err| QbcError::json(readpath, err))
+    }
+
+    /// Saves a Bank struct to a file in json format.
     ///
-    /// This function involves locking the `CURRENT_BANK` mutex for thread-safe access.
-    /// Ensure that this does not lead to any potential deadlocks in your program.
-    pub fn bnk_loaded() -> bool {
-        let mut inmem = false;
-        {
-            if CURRENT_BANK.lock().unwrap().clone().bank_title != "No Bank Loaded" {
-                inmem = true;
-            }
-        }
-        inmem
+    pub fn bnk_save_as_json(usebank: &Bank, usepath: &str) -> Result<(), QbcError> {
+        let bnk_as_json = serde_json::to_string(usebank)  // Convert bank to json string.
+            .map_err(|err| QbcError::json(usepath, err))?;
+        fs::write(usepath, bnk_as_json).map_err(|err| QbcError::io(usepath, err))
     }
 
-    /// Refreshes the contents of the widgets that are currently being displayed
-    /// and edited in the primary window.
-    pub fn bnk_refresh_widgets() {
-        let mut wdgts: Wdgts;
-        {
-            wdgts = WIDGETS.lock().unwrap().clone();
-        }
+    /// Steps through every question in the given Bank and recalculates
+    /// each of its variables.  Returns the errors for any variables that
+    /// could not be recalculated, with repeats removed.
+    pub fn bnk_recalc_questions(usebank: &mut Bank, rng: &mut impl Rng) -> Vec<QbcError> {
+        let mut failed: Vec<QbcError> = Vec::new();
 
-        wdgts.scroll.clear();
-        wdgts.scroll.redraw();
+        for quest in usebank.question_vec.iter_mut() {
+            for err in qst_recalc(quest, &usebank.locale, rng) {
+                if !failed.iter().any(|item| item.to_string() == err.to_string()) {
+                    failed.push(err);
+                }
+            }
+        }
 
-        // Create/refresh widgets based on data in CURRENT_BANK.
-        make_title_txtedtr();
-        make_scrollgroup();
-        make_question_boxes();
+        failed
     }
 
-    /// Creates a brand new question bank.
+    /// Adds a new question to the end of the Bank.
     ///
-    pub fn bnk_create() {
-        // todo: Check for bank in memory before proceeding.
-
-        let app;
-        {
-            app = *APP_FLTK.lock().unwrap();
-        } // Access the main app.
-
-        // Input values into the struct fields.
-        let mut newbank = Bank::new();
-        newbank.bank_title = input_string(&app, "Please enter the bank's title.", 300, 90);
-        newbank.associated_textbook = input_string(
-            &app,
-            "If you are using an associated textbook \
-         please enter its info. \n Press  Enter  if no textbook is being used.",
-            800,
-            200,
-        );
+    pub fn bnk_add_question(usebank: &mut Bank, quest: Question) {
+        usebank.question_vec.push(quest);
+        usebank.dirty = true;
+    }
 
-        // Pass the new bank into CURRENT_BANK
-        {
-            *CURRENT_BANK.lock().unwrap() = newbank.clone();
+    /// Replaces the question at `idx` with an edited copy, keeping its place
+    /// in the Bank.  Returns `false` if there is no question at `idx`.
+    pub fn bnk_replace_question(usebank: &mut Bank, idx: usize, quest: Question) -> bool {
+        match usebank.question_vec.get_mut(idx) {
+            Some(item) => {
+                *item = quest;
+                usebank.dirty = true;
+                true
+            }
+            None => false,
         }
-
-        // Save and display the bank.
-        bnk_save();
-
-        // Create widgets based on the data in CURRENT_BANK.
-        //make_title_txtedtr();
-        //make_scrollgroup();
-        //make_question_boxes();
     }
 
-    /// Reads a question bank's data from a file.
+    /// Removes the question at `idx` from the Bank and returns it.
     ///
-    pub fn bnk_read() {
+    pub fn bnk_delete_question(usebank: &mut Bank, idx: usize) -> Option<Question> {
+        if idx >= usebank.question_vec.len() {
+            return None;
+        }
+        usebank.dirty = true;
+        Some(usebank.question_vec.remove(idx))
+    }
 
-        // region Check if a bank is already loaded.  If so, ask the user if they want to replace it.
-        if bnk_loaded() {
-            message_title("A bank is already in memory.");  // todo: If bank is loaded section still needs help.
-            let choice = choice2_default("Save and replace the existing bank in memory?", "Yes", "No", "");
-            match choice {
-                Some(0) => {  // User chose "Yes".
-                    bnk_save();
-                }
-                Some(1) => {  // User chose "No".  Do nothing and return.
-                    message_title("No bank loaded");
-                    return;
-                }
-                _ => {  // Some unexpected value was chosen.  Do nothing and return.
-                    message_title("No bank loaded");
-                    return;
-                }
+    /// Inserts a copy of the question at `idx` right after it.
+    /// Returns `false` if there is no question at `idx`.
+    pub fn bnk_duplicate_question(usebank: &mut Bank, idx: usize) -> bool {
+        match usebank.question_vec.get(idx).cloned() {
+            Some(quest) => {
+                usebank.question_vec.insert(idx + 1, quest);
+                usebank.dirty = true;
+                true
             }
+            None => false,
         }
-        // endregion
+    }
 
-        // region Set up directories.
+    /// Moves the question at `from` so that it ends up at `to`, shifting the
+    /// questions in between.  Returns `false` if either index is out of range.
+    pub fn bnk_move_question(usebank: &mut Bank, from: usize, to: usize) -> bool {
+        let len = usebank.question_vec.len();
+        if from >= len || to >= len {
+            return false;
+        }
+        if from != to {
+            let quest = usebank.question_vec.remove(from);
+            usebank.question_vec.insert(to, quest);
+            usebank.dirty = true;
+        }
+        true
+    }
 
-        let usedir = glob_check_lastdirused();  // Handles the case where LAST_DIR_USED is empty.
-        let readpath: String;  // This will be the path to the file that is to be read.
+    /// Recalculates every question in the Bank from a seed and a zero-based
+    /// version number, and records both in the Bank.  The same seed, version,
+    /// and bank always give the same values.
+    pub fn bnk_recalc_seeded(usebank: &mut Bank, seed: u64, version: usize) -> Vec<QbcError> {
+        let mut rng = math_seeded_rng(math_version_seed(seed, version));
+        let failed = bnk_recalc_questions(usebank, &mut rng);
+        usebank.seed = Some(seed);
+        usebank.seed_version = version;
+        usebank.dirty = true;
+        failed
+    }
 
-        if !dir_is_empty(&usedir) {
-            readpath = file_fullpath(&usedir, "Choose the Bank file you want to read.");
-        } else {
-            fltk_custom_message("The directory you chose is empty.","Return to the main menu.");
-            return;
+    /// Changes how a Bank writes its numbers.  The options of its questions are
+    /// rewritten to match without being reshuffled, so the values & answer keys
+    /// still match the Bank's seed.
+    pub fn bnk_set_locale(usebank: &mut Bank, locale: NumLocale) {
+        for quest in usebank.question_vec.iter_mut() {
+            qst_relocalize_choices(quest, &usebank.locale, &locale);
         }
+        usebank.locale = locale;
+        usebank.dirty = true;
+    }
 
-        // Normalize the path, truncating any file name before updating LAST_DIR_USED.
-        let purepath = dir_normalize_path(readpath.as_str());
-        *LAST_DIR_USED.lock().unwrap() = purepath.clone(); // Update LAST_DIR_USED
+    /// Embeds a copy of the List used by every list-based Variable in the
+    /// Bank that doesn't already have one, so the bank file is self-contained.
+    /// Returns the errors for any lists that could not be read.
+    pub fn bnk_embed_definitions(usebank: &mut Bank) -> Vec<QbcError> {
+        let mut failed: Vec<QbcError> = Vec::new();
 
-        //endregion
+        for quest in usebank.question_vec.iter_mut() {
+            for item in quest.var_vec.iter_mut() {
+                if item.list_data.is_none() && let Err(err) = vrbl_embed_list(item) {
+                    failed.push(err);
+                }
+            }
+        }
 
-        // region Read the chosen file.
+        failed
+    }
 
-        let usebank: Bank;
-        match file_read_to_string(&readpath) {
-            Ok(contents) => {
-                usebank = serde_json::from_str(&contents).unwrap();
-                *CURRENT_BANK.lock().unwrap() = usebank.clone();
-            }
-            // TODO: Fix error handling.  This is terrible.  See thread in forum at
-            // https://users.rust-lang.org/t/help-understanding-never-used-warning/125562/2
-            Err(err) => {
-                eprintln!("\n Error reading the file: {} \n", err);
-                fltk_custom_message("Error reading the file.","Return to the main menu.");
+    /// Refreshes every Variable in the Bank that is still linked to the shared
+    /// library with the library's current definition and List.  Returns the
+    /// number of Variables synced along with any errors.
+    pub fn bnk_sync_from_library(usebank: &mut Bank) -> (usize, Vec<QbcError>) {
+        let mut synced = 0;
+        let mut failed: Vec<QbcError> = Vec::new();
+
+        for quest in usebank.question_vec.iter_mut() {
+            for item in quest.var_vec.iter_mut() {
+                match vrbl_sync_from_library(item) {
+                    Ok(true) => {
+                        synced += 1;
+                        usebank.dirty = true;
+                    }
+                    Ok(false) => {}
+                    Err(err) => failed.push(err),
+                }
             }
         }
-        // endregion
-    }
 
-    /// Refreshes the contents of the title box of a bank's display.
-    ///
-    pub fn bnk_refresh_title() {
-        let usebank: Bank;
-        let mut wdgts: Wdgts;
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
-            wdgts = WIDGETS.lock().unwrap().clone();
-        } // Access global structs.
+        (synced, failed)
+    }
 
-        let mut buf = TextBuffer::default();
-        buf.set_text(usebank.bank_title.as_str()); // Uses the title from the current bank.
-        wdgts.title_editbox.set_buffer(buf);
+    /// Searches every Bank file in `bankdir` for Questions that use the library
+    /// Variable saved at `libpath`.  Returns each Bank's path along with the
+    /// numbers (counting from 1) of the Questions that use the Variable, and
+    /// any Bank files that could not be read.
+    pub fn bnk_find_variable_uses(bankdir: &str, libpath: &str) -> (Vec<(String, Vec<usize>)>, Vec<QbcError>) {
+        let mut uses: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut failed: Vec<QbcError> = Vec::new();
+
+        let entries = match fs::read_dir(bankdir) {
+            Ok(entries) => entries,
+            Err(err) => return (uses, vec![QbcError::io(bankdir, err)]),
+        };
+        let mut bankpaths: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bnk"))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        bankpaths.sort();
+
+        for bankpath in bankpaths {
+            match bnk_read_with_pathfname(&bankpath) {
+                Ok(usebank) => {
+                    let qnums: Vec<usize> = usebank.question_vec.iter().enumerate()
+                        .filter(|(_, quest)| quest.var_vec.iter().any(|item| vrbl_is_from_file(item, libpath)))
+                        .map(|(idx, _)| idx + 1)
+                        .collect();
+                    if !qnums.is_empty() {
+                        uses.push((bankpath, qnums));
+                    }
+                }
+                Err(err) => failed.push(err),
+            }
+        }
 
-        //  let title_text =   // There is likely to be a use for  title_text   in the future.
-        wdgts.title_editbox.buffer().unwrap().text();
+        (uses, failed)
     }
 
-    /// Prepares a Bank struct for saving.
-    ///
-    pub fn bnk_save() {
-
-        let lastdir = glob_check_lastdirused();  // Handles the case where LAST_DIR_USED is empty.
+    /// Detaches every Variable in the Bank from the shared library.
+    /// The copies embedded in the Bank are used from then on.
+    pub fn bnk_detach_from_library(usebank: &mut Bank) {
+        for quest in usebank.question_vec.iter_mut() {
+            for item in quest.var_vec.iter_mut() {
+                vrbl_detach(item);
+            }
+        }
+        usebank.dirty = true;
+    }
 
-        let usebank: Bank;   // Pull the data from CURRENT_BANK.
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::variable::test_int_var;
+
+        /// Makes a Bank with one question of each kind that has options,
+        /// each titled by its number.
+        fn sample_bank() -> Bank {
+            let mut usebank = Bank::new();
+            usebank.bank_title = "Sample".to_string();
+
+            let mut quest = Question::new();
+            quest.qtext = "Question 1:  what is §x§ + §y§?".to_string();
+            quest.var_vec = vec![test_int_var("x", 1, 50), test_int_var("y", 1, 50)];
+            quest.answer = "§x§ + §y§".to_string();
+            quest.kind = QuestionKind::MultipleChoice { distractors: vec!["§x§ - §y§".to_string(), "§x§ * §y§".to_string()] };
+            quest.constraints = vec!["§x§ > §y§".to_string()];
+            usebank.question_vec.push(quest);
+
+            let mut quest = Question::new();
+            quest.qtext = "Question 2:  is §z§ even?".to_string();
+            quest.var_vec = vec![test_int_var("z", 1, 99)];
+            quest.answer = "§z§ % 2".to_string();
+            quest.kind = QuestionKind::Numeric { tolerance: 0.5 };
+            usebank.question_vec.push(quest);
+
+            let mut quest = Question::new();
+            quest.qtext = "Question 3:  match each sum.".to_string();
+            quest.var_vec = vec![test_int_var("a", 1, 9)];
+            quest.kind = QuestionKind::Matching { pairs: vec![
+                ("§a§ + 1".to_string(), "one more".to_string()),
+                ("§a§ + 2".to_string(), "two more".to_string()),
+            ] };
+            usebank.question_vec.push(quest);
+
+            usebank
         }
 
-        let usename = usebank.bank_title.clone();  // Pull the bank title to be used as the file name.
+        #[test]
+        fn bank_survives_save_and_read() {
+            let mut usebank = sample_bank();
+            assert!(bnk_recalc_questions(&mut usebank, &mut math_seeded_rng(1)).is_empty());
 
-        let filters = vec!["Banks", "*.bnk", "Lists", "*.lst", "Variables", "*.vrbl", "Text", "*.txt", "All Files", "*.*"];
-        let usepath = file_browse_tosave(&lastdir, usename.as_str(), &filters);  // Browse to choose directory and set file name.
+            let usepath = std::env::temp_dir().join(format!("qbc_roundtrip_{}.bnk", std::process::id()));
+            let usepath = usepath.to_string_lossy().to_string();
+            bnk_save_as_json(&usebank, &usepath).unwrap();
+            let readback = bnk_read_with_pathfname(&usepath);
+            let _ = fs::remove_file(&usepath);
 
-        {  // Since the new path has been chosen, update LAST_DIR_USED.
-            let purepath = dir_normalize_path(usepath.as_str());  // Normalize the path and truncate any file name.
-            *LAST_DIR_USED.lock().unwrap() = purepath.clone();
+            let readback = readback.unwrap();
+            assert_eq!(serde_json::to_string(&readback).unwrap(), serde_json::to_string(&usebank).unwrap());
+            assert!(!readback.dirty);
         }
 
-        bnk_save_as_json(&usepath);
-        // endregion
-    }
-
-    /// Saves a Bank struct to a file in json format.
-    ///
-    pub fn bnk_save_as_json(usepath: &String) {
-        let usebank;
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
+        /// Returns the values, options, and answers of every question, for
+        /// comparing two versions of a Bank.
+        fn snapshot(usebank: &Bank) -> String {
+            usebank.question_vec.iter()
+                .map(|quest| format!("{:?} {:?} {:?} {:?}", quest.var_vec.iter().map(|item| &item.content).collect::<Vec<_>>(),
+                                     quest.choices, quest.choice_key, qst_answer_text(quest, &usebank.locale)))
+                .collect::<Vec<String>>()
+                .join("\n")
         }
 
-        let bnk_as_json = serde_json::to_string(&usebank).unwrap(); // Convert bank to json string.
+        #[test]
+        fn same_seed_and_version_give_the_same_values() {
+            let mut first = sample_bank();
+            let mut again = sample_bank();
+            assert!(bnk_recalc_seeded(&mut first, 20240917, 1).is_empty());
+            assert!(bnk_recalc_seeded(&mut again, 20240917, 1).is_empty());
+            assert_eq!(snapshot(&first), snapshot(&again));
+            assert_eq!((first.seed, first.seed_version), (Some(20240917), 1));
+
+            let mut other = sample_bank();
+            assert!(bnk_recalc_seeded(&mut other, 20240917, 2).is_empty());
+            assert_ne!(snapshot(&first), snapshot(&other));
+
+            // Going back to version 1 later doesn't depend on what was generated in between.
+            assert!(bnk_recalc_seeded(&mut other, 20240917, 1).is_empty());
+            assert_eq!(snapshot(&first), snapshot(&other));
+        }
 
-        let mut file = File::create(usepath).expect("Could not create file!");
+        fn titles(usebank: &Bank) -> Vec<&str> {
+            usebank.question_vec.iter().map(|quest| &quest.qtext[..10]).collect()
+        }
 
-        file.write_all(bnk_as_json.as_bytes())
-            .expect("Cannot write to the file!");
-    }
+        #[test]
+        fn questions_move_within_the_bank() {
+            let mut usebank = sample_bank();
+            assert!(bnk_move_question(&mut usebank, 0, 2));
+            assert_eq!(titles(&usebank), ["Question 2", "Question 3", "Question 1"]);
+            assert!(usebank.dirty);
+            assert!(bnk_move_question(&mut usebank, 2, 0));
+            assert_eq!(titles(&usebank), ["Question 1", "Question 2", "Question 3"]);
+
+            usebank.dirty = false;
+            assert!(bnk_move_question(&mut usebank, 1, 1));
+            assert!(!usebank.dirty);  // Staying put isn't a change.
+            assert!(!bnk_move_question(&mut usebank, 3, 0));
+            assert!(!bnk_move_question(&mut usebank, 0, 3));
+            assert_eq!(titles(&usebank), ["Question 1", "Question 2", "Question 3"]);
+            assert!(!usebank.dirty);
+        }
 
-    /// Recalculates the variables in the questions of a Bank.
-    ///
-    pub fn bnk_recalc() {
-        let usebank;
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
+        #[test]
+        fn questions_are_added_replaced_duplicated_and_deleted() {
+            let mut usebank = sample_bank();
+            let mut quest = usebank.question_vec[0].clone();
+            quest.qtext = "Question 4".to_string();
+            bnk_add_question(&mut usebank, quest.clone());
+            assert_eq!(titles(&usebank), ["Question 1", "Question 2", "Question 3", "Question 4"]);
+            assert!(usebank.dirty);
+
+            quest.qtext = "Question 9".to_string();
+            assert!(bnk_replace_question(&mut usebank, 1, quest.clone()));
+            assert!(!bnk_replace_question(&mut usebank, 4, quest));
+            assert_eq!(titles(&usebank), ["Question 1", "Question 9", "Question 3", "Question 4"]);
+
+            assert!(bnk_duplicate_question(&mut usebank, 3));
+            assert!(!bnk_duplicate_question(&mut usebank, 5));
+            assert_eq!(titles(&usebank), ["Question 1", "Question 9", "Question 3", "Question 4", "Question 4"]);
+
+            assert_eq!(bnk_delete_question(&mut usebank, 0).map(|quest| quest.qtext[..10].to_string()), Some("Question 1".to_string()));
+            assert!(bnk_delete_question(&mut usebank, 4).is_none());
+            assert_eq!(titles(&usebank), ["Question 9", "Question 3", "Question 4", "Question 4"]);
         }
 
-        println!("\n Recalculate variables in a bank.  Not yet implemented. \n");
-        println!("\n The current bank is: \n {:?} \n", usebank);
+        #[test]
+        fn unreadable_banks_are_errors() {
+            assert!(matches!(bnk_read_with_pathfname("/no/such/bank.bnk"), Err(QbcError::Io { .. })));
 
-        //  Read the passed bank
-        //  for q in usebank.question_vec.iter() {
-        //      step thru and recalc each variable
-        //      in each question.
-        // }
-    }
-    
-} // End    bank    module
+            let usepath = std::env::temp_dir().join(format!("qbc_notabank_{}.bnk", std::process::id()));
+            let usepath = usepath.to_string_lossy().to_string();
+            fs::write(&usepath, "{ \"bank_title\": 3 }").unwrap();
+            let readback = bnk_read_with_pathfname(&usepath);
+            let _ = fs::remove_file(&usepath);
+            assert!(matches!(readback, Err(QbcError::Json { .. })));
+        }
 
-/// Functions that deal with the Question struct.
+        #[test]
+        fn sample_bank_files_still_read() {
+            let bankdir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/qbnk_data/banks");
+            for entry in fs::read_dir(bankdir).unwrap().filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "bnk") {
+                    assert!(bnk_read_with_pathfname(&path.to_string_lossy()).is_ok(), "{} did not read", path.display());
+                }
+            }
+        }
+    }
+
+    /// FLTK dialogs & display functions for working with Banks.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::errors::err_join_messages;
+        use crate::export::export_version_index;
+        use crate::global::{glob_check_lastdirused, glob_update_lastdirused};
+        use crate::math_functions::{math_format_currency, math_format_number, math_new_seed, NumStyle};
+        use crate::misc::{dir_is_empty, make_question_boxes, make_scrollgroup, make_title_txtedtr, primwin_update_title};
+        use crate::{Wdgts, APP_FLTK, CURRENT_BANK, WIDGETS};
+        use fltk::app;
+        use fltk::button::{Button, CheckButton};
+        use fltk::dialog::choice2_default;
+        use fltk::enums::{CallbackTrigger, Color};
+        use fltk::frame::Frame;
+        use fltk::input::Input;
+        use fltk::menu::Choice;
+        use fltk::prelude::{DisplayExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::window::Window;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use fltk::text::TextBuffer;
+        use lib_file::file_fltk::*;
+        use lib_myfltk::fltkutils::fltk_custom_message;
+        use lib_myfltk::input_fltk::*;
+
+        /// Checks if a bank is currently loaded.
+        ///
+        /// This function determines whether a bank is loaded in memory by checking
+        /// the `CURRENT_BANK` global variable. If the `bank_title` of the current bank
+        /// is anything other than "No Bank Loaded", the function returns `true`, indicating
+        /// that a bank is loaded. Otherwise, it returns `false`.
+        ///
+        /// # Returns
+        ///
+        /// * `true` - if a bank is loaded.
+        /// * `false` - if no bank is currently loaded.
+        ///
+        /// # Example
+        /// main() {
+        ///     if bnk_check_loaded() {
+        ///         println!("A bank is loaded in memory.");
+        ///     } else {
+        ///         println!("No bank is loaded.");
+        ///     }
+        /// }
+        /// # Note
+        ///
+        /// This function involves locking the `CURRENT_BANK` mutex for thread-safe access.
+        /// Ensure that this does not lead to any potential deadlocks in your program.
+        pub fn bnk_loaded() -> bool {
+            let mut inmem = false;
+            {
+                if CURRENT_BANK.lock().unwrap().clone().bank_title != "No Bank Loaded" {
+                    inmem = true;
+                }
+            }
+            inmem
+        }
+
+        /// Refreshes the contents of the widgets that are currently being displayed
+        /// and edited in the primary window.
+        pub fn bnk_refresh_widgets() {
+            let mut wdgts: Wdgts;
+            {
+                wdgts = WIDGETS.lock().unwrap().clone();
+            }
+
+            wdgts.scroll.clear();
+            wdgts.scroll.redraw();
+
+            // Create/refresh widgets based on data in CURRENT_BANK.
+            make_title_txtedtr();
+            make_scrollgroup();
+            make_question_boxes();
+            primwin_update_title();
+        }
+
+        /// Creates a brand new question bank.
+        ///
+        pub fn bnk_create() {
+            if !bnk_confirm_discard() {
+                return;
+            }
+
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            } // Access the main app.
+
+            // Input values into the struct fields.
+            let mut newbank = Bank::new();
+            newbank.bank_title = input_string(&app, "Please enter the bank's title.", 300, 90);
+            newbank.associated_textbook = input_string(
+                &app,
+                "If you are using an associated textbook \
+             please enter its info. \n Press  Enter  if no textbook is being used.",
+                800,
+                200,
+            );
+
+            // Pass the new bank into CURRENT_BANK
+            {
+                *CURRENT_BANK.lock().unwrap() = newbank.clone();
+            }
+
+            // Save and display the bank.
+            bnk_save();
+
+            // Create widgets based on the data in CURRENT_BANK.
+            //make_title_txtedtr();
+            //make_scrollgroup();
+            //make_question_boxes();
+        }
+
+        /// Reads a question bank's data from a file.
+        ///
+        pub fn bnk_read() {
+
+            // region If the bank in memory has unsaved changes, ask the user what to do with them.
+            if bnk_loaded() && !bnk_confirm_discard() {
+                return;
+            }
+            // endregion
+
+            // region Set up directories.
+
+            let usedir = glob_check_lastdirused("Banks");  // Falls back to the configured Banks folder.
+            if dir_is_empty(&usedir) {
+                fltk_custom_message("The directory you chose is empty.","Return to the main menu.");
+                return;
+            }
+            let readpath = file_fullpath(&usedir, "Choose the Bank file you want to read.");  // The path to the file that is to be read.
+
+            glob_update_lastdirused("Banks", &readpath);
+
+            //endregion
+
+            // region Read the chosen file.
+
+            match bnk_read_with_pathfname(&readpath) {
+                Ok(usebank) => {
+                    *CURRENT_BANK.lock().unwrap() = usebank;
+                }
+                Err(err) => {
+                    eprintln!("\n Error reading the bank: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return to the main menu.");
+                }
+            }
+            // endregion
+        }
+
+        /// Refreshes the contents of the title box of a bank's display.
+        ///
+        pub fn bnk_refresh_title() {
+            let usebank: Bank;
+            let mut wdgts: Wdgts;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+                wdgts = WIDGETS.lock().unwrap().clone();
+            } // Access global structs.
+
+            let mut buf = TextBuffer::default();
+            buf.set_text(usebank.bank_title.as_str()); // Uses the title from the current bank.
+            wdgts.title_editbox.set_buffer(buf);
+
+            //  let title_text =   // There is likely to be a use for  title_text   in the future.
+            wdgts.title_editbox.buffer().unwrap().text();
+        }
+
+        /// Prepares a Bank struct for saving.
+        ///
+        pub fn bnk_save() {
+
+            let lastdir = glob_check_lastdirused("Banks");  // Falls back to the configured Banks folder.
+
+            let mut usebank: Bank;   // Pull the data from CURRENT_BANK.
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+
+            // Make sure the bank carries its own copy of every list it uses.
+            let failed = bnk_embed_definitions(&mut usebank);
+            if !failed.is_empty() {
+                let msg = format!("These lists could not be embedded in the bank:\n {}", err_join_messages(&failed));
+                fltk_custom_message(&msg, "Continue saving.");
+            }
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+
+            let usename = usebank.bank_title.clone();  // Pull the bank title to be used as the file name.
+
+            let filters = vec!["Banks", "*.bnk", "Lists", "*.lst", "Variables", "*.vrbl", "Text", "*.txt", "All Files", "*.*"];
+            let usepath = file_browse_tosave(&lastdir, usename.as_str(), &filters);  // Browse to choose directory and set file name.
+
+            glob_update_lastdirused("Banks", &usepath);  // Since the new path has been chosen, update it.
+
+            match bnk_save_as_json(&usebank, &usepath) {
+                Ok(()) => {
+                    CURRENT_BANK.lock().unwrap().dirty = false;
+                }
+                Err(err) => {
+                    eprintln!("\n Error saving the bank: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return to the main menu.");
+                }
+            }
+            // endregion
+        }
+
+        /// If the current bank has unsaved changes, asks the user whether to save
+        /// them.  Returns `false` if the user cancels, so whatever was about to
+        /// replace or close the bank should not go ahead.
+        pub fn bnk_confirm_discard() -> bool {
+            if !CURRENT_BANK.lock().unwrap().dirty {
+                return true;
+            }
+
+            match choice2_default("The bank has changes that have not been saved.", "Save", "Discard changes", "Cancel") {
+                Some(0) => {
+                    bnk_save();
+                    !CURRENT_BANK.lock().unwrap().dirty  // Saving may have failed.
+                }
+                Some(1) => true,
+                _ => false,
+            }
+        }
+
+        /// Refreshes the linked Variables in the current bank from the shared library.
+        ///
+        pub fn bnk_sync() {
+            let mut usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+
+            let (synced, failed) = bnk_sync_from_library(&mut usebank);
+
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+
+            let mut msg = format!("{} variable(s) were synced from the library.", synced);
+            if !failed.is_empty() {
+                msg = format!("{}\n These could not be synced:\n {}", msg, err_join_messages(&failed));
+            }
+            fltk_custom_message(&msg, "Return to the main menu.");
+        }
+
+        /// Detaches the current bank from the shared library after asking the user.
+        ///
+        pub fn bnk_detach() {
+            let choice = choice2_default(
+                "Detach this bank from the variable library?  It will keep its own copies \
+                of its variables and lists, and syncing will no longer change them.",
+                "Detach", "Cancel", "");
+            if choice != Some(0) {
+                return;
+            }
+
+            let mut usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            bnk_detach_from_library(&mut usebank);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+        }
+
+        /// Recalculates the variables in the questions of a Bank.
+        ///
+        pub fn bnk_recalc() {
+            bnk_recalc_with(math_new_seed(), 0);
+        }
+
+        /// Asks the user for a seed & version letter and recalculates the bank
+        /// from them, reproducing the values of an earlier worksheet.
+        pub fn bnk_recalc_from_seed() {
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            let txt = input_string(&app, "Please enter the seed and the version letter, e.g.  1234567 B  :  ", 790, 300);
+            let mut parts = txt.split_whitespace();
+            let seed = parts.next().and_then(|part| part.parse::<u64>().ok());
+            let version = export_version_index(parts.next().unwrap_or("A"));
+            match (seed, version) {
+                (Some(seed), Some(version)) => bnk_recalc_with(seed, version),
+                _ => fltk_custom_message("The seed must be a whole number and the version a letter.", "Return to the main menu."),
+            }
+        }
+
+        /// Asks how the current bank should write its numbers -- the separators
+        /// and the currency symbol -- and applies the answer to the bank.
+        pub fn bnk_number_format() {
+            let oldlocale;
+            {
+                oldlocale = CURRENT_BANK.lock().unwrap().locale.clone();
+            }
+
+            let mut win = Window::new(900, 100, 420, 250, "Number Format");
+            win.set_color(Color::Cyan);
+
+            let mut style = Choice::new(160, 20, 200, 25, "Separators:");
+            for item in NumStyle::ALL.iter() {
+                style.add_choice(item.name());
+            }
+            style.set_value(NumStyle::ALL.iter().position(|item| *item == oldlocale.style).unwrap_or(0) as i32);
+
+            let mut currency = Input::new(160, 60, 80, 25, "Currency symbol:");
+            currency.set_value(&oldlocale.currency);
+            let after = CheckButton::new(160, 95, 220, 25, "Symbol after the number");
+            after.set_checked(oldlocale.currency_after);
+
+            let mut preview = Frame::new(20, 130, 380, 25, "");
+            let mut ok_btn = Button::new(110, 180, 90, 40, "OK");
+            let mut cancel_btn = Button::new(220, 180, 90, 40, "Cancel");
+            win.end();
+            win.show();
+
+            // Reads the dialog's fields into a locale.
+            let read_fields = {
+                let (style, currency, after) = (style.clone(), currency.clone(), after.clone());
+                move || NumLocale {
+                    style: NumStyle::ALL[style.value().max(0) as usize],
+                    currency: currency.value().trim().to_string(),
+                    currency_after: after.is_checked(),
+                }
+            };
+            let show_preview = {
+                let read_fields = read_fields.clone();
+                move |preview: &mut Frame| {
+                    let locale = read_fields();
+                    let sample = math_format_number("1234567.89", true, &locale);
+                    preview.set_label(&format!("e.g.  {}      {}", sample, math_format_currency(&sample, &locale)));
+                }
+            };
+            show_preview(&mut preview);
+
+            // Refresh the example whenever a setting changes.
+            let mut preview_clone = preview.clone();
+            let show_clone = show_preview.clone();
+            style.set_callback(move |_| show_clone(&mut preview_clone));
+            let mut preview_clone = preview.clone();
+            let show_clone = show_preview.clone();
+            currency.set_trigger(CallbackTrigger::Changed);
+            currency.set_callback(move |_| show_clone(&mut preview_clone));
+            let mut preview_clone = preview.clone();
+            let mut after_clone = after.clone();
+            after_clone.set_callback(move |_| show_preview(&mut preview_clone));
+
+            let chosen: Rc<RefCell<Option<NumLocale>>> = Rc::new(RefCell::new(None));
+            let chosen_clone = chosen.clone();
+            let mut win_clone = win.clone();
+            ok_btn.set_callback(move |_| {
+                *chosen_clone.borrow_mut() = Some(read_fields());
+                win_clone.hide();
+            });
+            let mut win_clone = win.clone();
+            cancel_btn.set_callback(move |_| win_clone.hide());
+
+            while win.shown() {
+                app::wait();
+            }
+
+            let Some(locale) = chosen.borrow_mut().take() else {
+                return;  // Closed or cancelled.
+            };
+            if locale == oldlocale {
+                return;
+            }
+            let mut usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            bnk_set_locale(&mut usebank, locale);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+        }
+
+        /// Recalculates the current bank from a seed & version and reports any failures.
+        ///
+        fn bnk_recalc_with(seed: u64, version: usize) {
+            let mut usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+
+            let failed = bnk_recalc_seeded(&mut usebank, seed, version);
+
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+
+            if !failed.is_empty() {
+                let msg = format!("These variables could not be recalculated:\n {}", err_join_messages(&failed));
+                fltk_custom_message(&msg, "Return to the main menu.");
+            }
+        }
+
+    } // End   gui   submodule
+
+} // End    bank    module
+
+/// Functions that deal with the Question struct.
 ///
 pub mod questions {
-    use crate::banks::{bnk_refresh_widgets, bnk_save, Bank};
+    use crate::errors::QbcError;
+    use crate::formulas::{frml_check_condition, frml_check_condition_units, frml_check_units, frml_eval, frml_evaluate_in_unit,
+                          frml_evaluate_text, frml_format_answer, frml_parse, frml_parse_condition};
+    use crate::global::TypeWrapper;
+    use crate::lists::list_entry_form;
+    use crate::math_functions::{math_format_number, NumLocale};
     use crate::variable::*;
-    use crate::{APP_FLTK, CURRENT_BANK, LAST_DIR_USED, VARIABLE_DIR};
-    use fltk::app::set_font_size;
-    use fltk::enums::{Color, Shortcut};
-    use fltk::prelude::{DisplayExt, GroupExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
-    use fltk::text::{TextBuffer, TextEditor};
-    use fltk::{app, menu, text, window};
-    use lib_file::file_fltk::*;
-    use lib_file::file_mngmnt::file_get_dir_list;
-    use lib_myfltk::fltkutils::*;
-    use lib_myfltk::input_fltk::{input_string, input_strvec};
-    use lib_utils::utilities::*;
+    use crate::CONFIG;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
     use serde::{Deserialize, Serialize};
-    use crate::global::glob_check_lastdirused;
+    #[cfg(feature = "gui")]
+    pub use gui::*;
+
     //region Struct Section
 
     /// The second layer of the three structs QBC is built around.
@@ -546,270 +1540,1228 @@ pub mod questions {
         pub var_dirpath: String,
         pub var_vec: Vec<Variable>,
         pub answer: String,
+        #[serde(default)]
+        pub answer_value: Option<f64>,  // Calculated from the `answer` formula.
         pub objectives: Vec<String>,
         pub prereqs: Vec<String>,
+        #[serde(default)]
+        pub kind: QuestionKind,
+        #[serde(default)]
+        pub choices: Vec<String>,  // Options in the order shown for the current version.
+        #[serde(default)]
+        pub choice_key: Vec<usize>,  // Index into `choices` of each correct option.
+        #[serde(default)]
+        pub constraints: Vec<String>,  // Conditions the Variables must meet together, e.g.  §a§ > §b§
+        #[serde(default)]
+        pub answer_unit: String,  // Unit the answer is given in, e.g.  m/s.  Blank picks one from the Variables.
+        #[serde(default)]
+        pub distinct_lists: bool,  // Variables drawn from the same List get different entries.
+    }
+
+    /// The kinds of question QBC can hold.  For every kind, the `answer`
+    /// formula of the Question gives the correct answer, except for
+    /// `TrueFalse` & `Matching`, which carry their answers here.
+    #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+    pub enum QuestionKind {
+        #[default]
+        FreeResponse,
+        MultipleChoice { distractors: Vec<String> },  // Formulas for wrong answers.
+        TrueFalse { correct: bool },
+        Numeric { tolerance: f64 },
+        Matching { pairs: Vec<(String, String)> },  // (prompt, match)
+    }
+
+    impl QuestionKind {
+        /// The names of the kinds, in the order they are offered to the user.
+        pub const NAMES: [&'static str; 5] = ["Free response", "Multiple choice", "True/False", "Numeric", "Matching"];
+
+        /// Returns the name shown to the user for this kind.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                QuestionKind::FreeResponse => Self::NAMES[0],
+                QuestionKind::MultipleChoice { .. } => Self::NAMES[1],
+                QuestionKind::TrueFalse { .. } => Self::NAMES[2],
+                QuestionKind::Numeric { .. } => Self::NAMES[3],
+                QuestionKind::Matching { .. } => Self::NAMES[4],
+            }
+        }
+    } // End   QuestionKind   impl
+
+    /// How many times the variables of a question are regenerated when
+    /// looking for values that give distinct choices.
+    pub const QST_MAX_CHOICE_TRIES: usize = 25;
+
+    /// How many sets of values are drawn when looking for one that meets
+    /// all of a question's constraints.
+    pub const QST_MAX_CONSTRAINT_TRIES: usize = 1000;
+
+    impl Default for Question {
+        fn default() -> Self {
+            Self::new()
+        }
     }
 
     impl Question {
         /// Initialize a new Question.
-        fn new() -> Question {
+        pub fn new() -> Question {
             Self {
                 qtext: "Please enter the text of your question.  Use real values. You will replace those values with variables later.  Be sure to delete these instructions before entering your question text.".to_string(),
-                var_dirpath: VARIABLE_DIR.to_string(),
+                var_dirpath: CONFIG.lock().unwrap().variable_dir.clone(),
                 var_vec: Vec::new(),
                 answer: "Answer".to_string(),
+                answer_value: None,
                 objectives: Vec::new(),
                 prereqs: Vec::new(),
+                kind: QuestionKind::FreeResponse,
+                choices: Vec::new(),
+                choice_key: Vec::new(),
+                constraints: Vec::new(),
+                answer_unit: String::new(),
+                distinct_lists: false,
             }
         }
     } // End   Question   impl
       //endregion
 
-    /// Create a new question.
-    ///
-    pub fn qst_create() {
-        // todo: The answer will need to parse inserted variables.
+    /// Replaces each flagged variable in the question text with the formatted
+    /// value of the matching Variable in `var_vec`.  Flags that don't match
+    /// a Variable are left in place.
+    pub fn qst_render_text(quest: &Question, locale: &NumLocale) -> String {
+        qst_render_with_spans(quest, locale).0
+    }
+
+    /// Renders the question text the same way as `qst_render_text()`, but also
+    /// returns a vector of (start, end, variable name) tuples giving the byte
+    /// range of each substituted value in the rendered text.  The ranges are
+    /// used to highlight the values in the question display.
+    pub fn qst_render_with_spans(quest: &Question, locale: &NumLocale) -> (String, Vec<(usize, usize, String)>) {
+        qst_substitute_with_spans(&quest.qtext, &quest.var_vec, locale)
+    }
+
+    /// Replaces each flagged variable in any text -- question text, a matching
+    /// item, etc. -- with the formatted value of the matching Variable.
+    pub fn qst_substitute(text: &str, var_vec: &[Variable], locale: &NumLocale) -> String {
+        qst_substitute_with_spans(text, var_vec, locale).0
+    }
 
-        let mut newquest = Question::new();
+    /// Does the work for `qst_render_with_spans()` & `qst_substitute()`.
+    ///
+    fn qst_substitute_with_spans(text: &str, var_vec: &[Variable], locale: &NumLocale) -> (String, Vec<(usize, usize, String)>) {
+        let mut rendered = String::new();
+        let mut spans: Vec<(usize, usize, String)> = Vec::new();
+
+        // Every odd-numbered piece of the split text lies between a pair of flags.
+        let pieces: Vec<&str> = text.split('§').collect();
+        for (idx, piece) in pieces.iter().enumerate() {
+            let is_flagged = idx % 2 == 1 && idx < pieces.len() - 1; // An unpaired flag is just text.
+            if !is_flagged {
+                if idx > 0 && idx % 2 == 1 {
+                    rendered.push('§');
+                }
+                rendered.push_str(piece);
+                continue;
+            }
 
-        // region Question data entry
+            match qst_flag_value(piece, var_vec, locale) {
+                Some(value) => {
+                    let start = rendered.len();
+                    rendered.push_str(&value);
+                    spans.push((start, rendered.len(), qst_split_flag(piece).0.to_string()));
+                }
+                None => {
+                    rendered.push_str(&format!("§{}§", piece));
+                }
+            }
+        }
 
-        let nowtext = qst_editor(newquest.qtext.as_str(), "Question Editor");
-        newquest.qtext = nowtext.clone();
+        (rendered, spans)
+    }
 
-        // Pull the flagged variables from the text and push them to the variable vector.
-        qst_fill_varvec_parsetext(&mut newquest);
+    /// Returns the text that replaces a flag, e.g. the value of  §a§  or the
+    /// pronoun asked for by  §student:they§.  The `piece` is the text between
+    /// the flags.  Returns `None` if there is no such Variable or form.
+    pub fn qst_flag_value(piece: &str, var_vec: &[Variable], locale: &NumLocale) -> Option<String> {
+        let (name, form) = qst_split_flag(piece);
+        let usevar = vrbl_find_by_name(var_vec, name)?;
+        match form {
+            Some(form) => qst_render_form(usevar, form, var_vec, locale),
+            None => Some(vrbl_format_content(usevar, locale)),
+        }
+    }
 
-        // Answer will eventually need to be calculated.
+    /// Splits a flagged piece of text into the Variable's name and the
+    /// grammatical form asked for, if any:  `student:they`  gives
+    /// `("student", Some("they"))`.
+    pub fn qst_split_flag(piece: &str) -> (&str, Option<&str>) {
+        match piece.split_once(':') {
+            Some((name, form)) => (name.trim(), Some(form.trim())),
+            None => (piece, None),
+        }
+    }
 
-        let app;
-        {
-            app = *APP_FLTK.lock().unwrap();
+    /// Writes a Variable's value in a grammatical form, e.g. the pronoun that
+    /// goes with a name.  See `list_entry_form()` for the forms.  The form
+    /// `plural(count)`  gives the plural unless the Variable `count` is 1.
+    /// Returns `None` if the form isn't known.
+    fn qst_render_form(usevar: &Variable, form: &str, var_vec: &[Variable], locale: &NumLocale) -> Option<String> {
+        let entry = vrbl_format_content(usevar, locale);
+        let attrs = vrbl_entry_attrs(usevar);
+        if form.to_lowercase().starts_with("plural(") && let Some(countname) = form[7..].strip_suffix(')') {
+            let countvar = vrbl_find_by_name(var_vec, countname.trim())?;
+            let is_one = match countvar.content {
+                TypeWrapper::Integer(num) => num.abs() == 1,
+                TypeWrapper::Floating(num) => num.abs() == 1.0,
+                _ => return None,
+            };
+            return if is_one { Some(entry) } else { list_entry_form(&entry, &attrs, "plural") };
         }
+        list_entry_form(&entry, &attrs, form)
+    }
 
-        newquest.answer = input_string(&app, "Please input the question's answer:  ", 790, 300);
-        newquest.objectives =
-            input_strvec(&app, "Please enter the question objectives:  ", 790, 300);
-        newquest.prereqs =
-            input_strvec(&app, "Please enter the question prerequisites:  ", 790, 300);
-        // endregion
+    /// Returns the answer to a question as text, in the form used by
+    /// answer keys -- e.g. `B.  12.5` for a multiple choice question.
+    pub fn qst_answer_text(quest: &Question, locale: &NumLocale) -> String {
+        match &quest.kind {
+            QuestionKind::FreeResponse => qst_correct_text(quest, locale),
+            QuestionKind::MultipleChoice { .. } => match quest.choice_key.first() {
+                Some(idx) => format!("{}.  {}", qst_choice_label(*idx), qst_correct_text(quest, locale)),
+                None => qst_correct_text(quest, locale),
+            },
+            QuestionKind::TrueFalse { correct } => {
+                if *correct { "True".to_string() } else { "False".to_string() }
+            }
+            QuestionKind::Numeric { tolerance } if *tolerance > 0.0 => {
+                let tolerance = math_format_number(&frml_format_answer(*tolerance), false, locale);
+                format!("{}  (± {})", qst_correct_text(quest, locale), tolerance)
+            }
+            QuestionKind::Numeric { .. } => qst_correct_text(quest, locale),
+            QuestionKind::Matching { .. } => quest.choice_key.iter()
+                .enumerate()
+                .map(|(idx, key)| format!("{}-{}", idx + 1, qst_choice_label(*key)))
+                .collect::<Vec<String>>()
+                .join(", "),
+        }
+    }
 
-        // region Save and store the data
-        let mut usebank: Bank;
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
-        } // Access the global Bank variable
-        usebank.question_vec.push(newquest); // Store the new question in the bank
-        {
-            // Pass the modified bank into the global variable.
-            *CURRENT_BANK.lock().unwrap() = usebank.clone();
+    /// Returns the value of the `answer` formula as text.  A calculated answer
+    /// is used when there is one, otherwise the answer is shown as it was typed.
+    fn qst_correct_text(quest: &Question, locale: &NumLocale) -> String {
+        match quest.answer_value {
+            Some(value) => frml_evaluate_text(&quest.answer, &quest.var_vec, &quest.answer_unit, locale)
+                .unwrap_or_else(|_| math_format_number(&frml_format_answer(value), false, locale)),
+            None => qst_substitute(&quest.answer, &quest.var_vec, locale),
         }
-        bnk_save();
-        // endregion
     }
 
-    /// Edit a question.
+    /// Converts a zero-based choice number into its label:  A, B, C, ...
     ///
-    pub fn qst_edit(qst_idx: usize) {
-        let app;
-        let mut usebank;
-        {
-            app = *APP_FLTK.lock().unwrap();
-            usebank = CURRENT_BANK.lock().unwrap().clone();
-        } // Access global variables.
-
-        let mut editqst = usebank.question_vec.get(qst_idx).unwrap().clone();
+    pub fn qst_choice_label(idx: usize) -> char {
+        (b'A' + (idx % 26) as u8) as char
+    }
 
-        let nowtext = qst_editor(editqst.qtext.as_str(), "Question Editor");
-        editqst.qtext = nowtext.clone();
+    /// Returns the lines that show a question's options below its text, e.g.
+    /// `A.  12.5` for multiple choice.  Kinds without options give no lines.
+    pub fn qst_render_choices(quest: &Question, locale: &NumLocale) -> Vec<String> {
+        match &quest.kind {
+            QuestionKind::MultipleChoice { .. } => quest.choices.iter()
+                .enumerate()
+                .map(|(idx, item)| format!("{}.  {}", qst_choice_label(idx), item))
+                .collect(),
+            QuestionKind::TrueFalse { .. } => vec!["True      False".to_string()],
+            QuestionKind::Matching { pairs } => pairs.iter()
+                .enumerate()
+                .map(|(idx, (prompt, _))| {
+                    let rgt = quest.choices.get(idx).cloned().unwrap_or_default();
+                    format!("{}.  {:<30}  {}.  {}", idx + 1, qst_substitute(prompt, &quest.var_vec, locale),
+                            qst_choice_label(idx), rgt)
+                })
+                .collect(),
+            QuestionKind::FreeResponse | QuestionKind::Numeric { .. } => Vec::new(),
+        }
+    }
 
-        // Pull the flagged variables from the text and push them to the variable vector.
-        qst_fill_varvec_parsetext(&mut editqst); // Need to clear the vector first.
+    /// Builds and shuffles the options of a multiple choice or matching
+    /// question from the current variable values.  Options that come out the
+    /// same as an earlier one are dropped, and the first of them is returned
+    /// as the error.  The options are written with the Bank's `locale`.
+    pub fn qst_make_choices(quest: &mut Question, locale: &NumLocale, rng: &mut impl Rng) -> Result<(), QbcError> {
+        let (options, repeated) = qst_choice_options(quest, locale);
+
+        match &quest.kind {
+            QuestionKind::MultipleChoice { .. } => {
+                let mut order: Vec<usize> = (0..options.len()).collect();
+                order.shuffle(rng);
+                quest.choices = order.iter().map(|idx| options[*idx].clone()).collect();
+                quest.choice_key = order.iter().position(|idx| *idx == 0).into_iter().collect();
+            }
+            QuestionKind::Matching { .. } => {
+                let matches = options;
+                let mut order: Vec<usize> = (0..matches.len()).collect();
+                order.shuffle(rng);
+                quest.choices = order.iter().map(|idx| matches[*idx].clone()).collect();
+                quest.choice_key = (0..matches.len())
+                    .map(|idx| order.iter().position(|item| *item == idx).unwrap_or(idx))
+                    .collect();
+            }
+            _ => {
+                quest.choices.clear();
+                quest.choice_key.clear();
+            }
+        }
 
-        // Answer will eventually need to be calculated.
+        match repeated {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
 
-        editqst.answer = input_string(&app, "Please input the question's answer:  ", 790, 300);
-        editqst.objectives =
-            input_strvec(&app, "Please enter the question objectives:  ", 790, 300);
-        editqst.prereqs =
-            input_strvec(&app, "Please enter the question prerequisites:  ", 790, 300);
+    /// Returns the options of a multiple choice or matching question in the
+    /// order they were written, before shuffling.  Multiple choice options that
+    /// repeat an earlier one are left out.  Also returns an error naming the
+    /// first option that repeats, if any do.
+    fn qst_choice_options(quest: &Question, locale: &NumLocale) -> (Vec<String>, Option<QbcError>) {
+        let mut repeated: Option<QbcError> = None;
+
+        let options = match &quest.kind {
+            QuestionKind::MultipleChoice { distractors } => {
+                let mut options = vec![qst_correct_text(quest, locale)];  // The correct answer is first until shuffled.
+                for formula in distractors.iter() {
+                    // A distractor that isn't a formula, e.g. "None of these", is used as text.
+                    let option = frml_evaluate_text(formula, &quest.var_vec, &quest.answer_unit, locale)
+                        .unwrap_or_else(|_| qst_substitute(formula, &quest.var_vec, locale));
+                    if options.contains(&option) {
+                        repeated.get_or_insert(QbcError::RepeatedChoice { option: formula.clone(), value: option });
+                    } else {
+                        options.push(option);
+                    }
+                }
+                options
+            }
+            QuestionKind::Matching { pairs } => {
+                let matches: Vec<String> = pairs.iter()
+                    .map(|(_, rgt)| qst_substitute(rgt, &quest.var_vec, locale))
+                    .collect();
+                for (idx, item) in matches.iter().enumerate() {
+                    if matches[..idx].contains(item) && repeated.is_none() {
+                        repeated = Some(QbcError::RepeatedChoice { option: pairs[idx].1.clone(), value: item.clone() });
+                    }
+                }
+                matches
+            }
+            _ => Vec::new(),
+        };
 
-        // Push the question to the vector in the bank and save the bank.
-        //let mut usebank = CURRENT_BANK.lock().unwrap();
+        (options, repeated)
+    }
 
-        // todo: This won't work.  push()  appends to the end of the vector. Fix it.
-        usebank.question_vec.push(editqst.clone());
-        bnk_save();
-        bnk_refresh_widgets();
+    /// Rewrites the options of a question that were made with the locale `old`
+    /// so they use `new`, keeping their order and the answer key.
+    pub fn qst_relocalize_choices(quest: &mut Question, old: &NumLocale, new: &NumLocale) {
+        let (before, _) = qst_choice_options(quest, old);
+        let (after, _) = qst_choice_options(quest, new);
+        for item in quest.choices.iter_mut() {
+            if let Some(option) = before.iter().position(|option| option == item).and_then(|idx| after.get(idx)) {
+                *item = option.clone();
+            }
+        }
     }
 
-    /// // Is this necessary now?
-    ///
-    pub fn qst_chooseqst() -> Question {
-        // TODO: Instead of trying to put the whole text of the question
-        //          body in the radio button, number each question in the
-        //          bank display and choose by the question number.
+    /// Calculates the answer of a question from its answer formula and the
+    /// current values of its variables, in the question's `answer_unit`.  If
+    /// the answer is not a formula, `answer_value` is set to `None`.
+    pub fn qst_calc_answer(quest: &mut Question) {
+        quest.answer_value = frml_evaluate_in_unit(&quest.answer, &quest.var_vec, &quest.answer_unit)
+            .map(|(value, _)| value)
+            .ok();
+    }
 
-        // Note:  This function may not be necessary.
+    /// Checks that the units agree in the answer formula, in formula
+    /// distractors, and in the constraints of a question.  Text that isn't
+    /// a formula is skipped.
+    pub fn qst_check_units(quest: &Question) -> Vec<QbcError> {
+        let mut formulas = vec![&quest.answer];
+        if let QuestionKind::MultipleChoice { distractors } = &quest.kind {
+            formulas.extend(distractors.iter());
+        }
 
-        let mut usevec: Vec<String> = Vec::new();
+        let mut failed: Vec<QbcError> = formulas.into_iter()
+            .filter(|formula| frml_parse(formula).is_ok())
+            .filter_map(|formula| frml_check_units(formula, &quest.var_vec, &quest.answer_unit)
+                .err()
+                .map(|reason| QbcError::UnitMismatch { formula: formula.clone(), reason }))
+            .collect();
+        failed.extend(quest.constraints.iter()
+            .filter_map(|constraint| frml_check_condition_units(constraint, &quest.var_vec)
+                .err()
+                .map(|reason| QbcError::UnitMismatch { formula: constraint.clone(), reason })));
+        failed
+    }
 
-        let usebank;
-        {
-            usebank = CURRENT_BANK.lock().unwrap().clone();
+    /// Recalculates the content of every Variable in a Question.
+    /// Returns the errors for any Variables that could not be recalculated,
+    /// or for options that still repeat after QST_MAX_CHOICE_TRIES tries.
+    pub fn qst_recalc(quest: &mut Question, locale: &NumLocale, rng: &mut impl Rng) -> Vec<QbcError> {
+        let mut failed: Vec<QbcError> = Vec::new();
+        let mut repeated: Option<QbcError> = None;
+
+        // Regenerate until the choices are all different, up to QST_MAX_CHOICE_TRIES times.
+        for _ in 0..QST_MAX_CHOICE_TRIES {
+            failed = qst_regen_constrained(quest, rng);
+            qst_calc_answer(quest);
+
+            repeated = qst_make_choices(quest, locale, rng).err();
+            if repeated.is_none() || !failed.is_empty() {
+                break;
+            }
         }
 
-        for item in usebank.question_vec.iter() {
-            usevec.push(item.qtext.clone());
+        if failed.is_empty() {
+            failed.extend(repeated);
+            failed.extend(qst_check_units(quest));
         }
+        failed
+    }
+
+    /// Returns `true` if two or more Variables in a Question draw from the same List.
+    ///
+    pub fn qst_shares_list(quest: &Question) -> bool {
+        let listpaths: Vec<&String> = quest.var_vec.iter()
+            .filter(|item| item.params.is_from_list && !item.params.is_derived)
+            .map(|item| &item.uselist)
+            .collect();
+        listpaths.iter().enumerate().any(|(idx, listpath)| listpaths[..idx].contains(listpath))
+    }
 
-        let usequest = fltk_radio_lightbtn_menu(&usevec, "");
-        let mut editquest = Question::new();
+    /// Draws a new value for each Variable in a Question that isn't derived.
+    /// With `distinct_lists` set, Variables that share a List get different entries.
+    fn qst_regen_values(quest: &mut Question, rng: &mut impl Rng) -> Vec<QbcError> {
+        if !quest.distinct_lists {
+            return quest.var_vec.iter_mut()
+                .filter_map(|item| vrbl_regen_content(item, rng).err())
+                .collect();
+        }
 
-        for item in usebank.question_vec.iter() {
-            if item.qtext == usequest {
-                editquest = item.clone();
+        let mut drawn: Vec<(String, TypeWrapper)> = Vec::new();  // (List path, entry)
+        let mut failed: Vec<QbcError> = Vec::new();
+        for item in quest.var_vec.iter_mut() {
+            let taken: Vec<TypeWrapper> = drawn.iter()
+                .filter(|(listpath, _)| item.params.is_from_list && *listpath == item.uselist)
+                .map(|(_, entry)| entry.clone())
+                .collect();
+            match vrbl_regen_distinct(item, &taken, rng) {
+                Ok(()) if item.params.is_from_list => drawn.push((item.uselist.clone(), item.content.clone())),
+                Ok(()) => {}
+                Err(err) => failed.push(err),
             }
         }
-
-        editquest
+        failed
     }
 
-    /// Parses the text of a question looking for flags that mark
-    /// variables.  Separates out the flagged variables, reading the
-    /// data into Variable structs and saving them in the
-    /// `quest.var_vec` vector field of the current question.
-    pub fn qst_fill_varvec_parsetext(quest: &mut Question) {
-        // Note:  All variable files must be in the same directory.
+    /// Regenerates the Variables of a Question until they meet all of its
+    /// constraints, up to QST_MAX_CONSTRAINT_TRIES times.  If they never do,
+    /// the error names the constraint that failed most often.
+    pub fn qst_regen_constrained(quest: &mut Question, rng: &mut impl Rng) -> Vec<QbcError> {
+        // region Check the derived Variables & constraints before drawing any values.
+        if let Err(err) = vrbl_derived_order(&quest.var_vec) {
+            return vec![err];
+        }
+        let has_derived = quest.var_vec.iter().any(|item| item.params.is_derived);
 
-        // region Create a vector of the variable names that have been flagged in the text.
-        let mut usevec = util_flaggedtxt_2vec(&quest.qtext, '§');
-        usevec.sort();
-        usevec.dedup(); // Remove repeats of the flagged variable names.
+        let mut conditions = Vec::new();
+        for constraint in quest.constraints.iter() {
+            match frml_check_condition(constraint, &quest.var_vec).and_then(|_| frml_parse_condition(constraint)) {
+                Ok(expr) => conditions.push(expr),
+                Err(reason) => return vec![QbcError::UnmetConstraint { constraint: constraint.clone(), reason }],
+            }
+        }
         // endregion
 
-        // region Read the variable files from disk and insert them into the variable vector.
+        let tries = if conditions.is_empty() && !has_derived { 1 } else { QST_MAX_CONSTRAINT_TRIES };
+        let mut misses = vec![0usize; conditions.len()];
+        let mut last_err: Vec<Option<String>> = vec![None; conditions.len()];
+        let mut derived_err: Option<QbcError> = None;
+
+        for _ in 0..tries {
+            let failed = qst_regen_values(quest, rng);
+            if !failed.is_empty() {
+                return failed;
+            }
 
-        let lastdir = glob_check_lastdirused();
-        let usepath = file_pathonly(&lastdir, "Choose the folder that contains your variable files.");
+            // Derived Variables come after the random ones.  Values that make a
+            // formula fail, e.g. by dividing by zero, are redrawn.
+            if let Err(err) = vrbl_calc_derived(&mut quest.var_vec) {
+                derived_err = Some(err);
+                continue;
+            }
 
-        quest.var_vec.clear();
-        for item in usevec.iter() {
-            match vrbl_read_with_pathfname(&usepath, item) {
-                Some(newvar) => {
-                    quest.var_vec.push(newvar);
-                }
-                None => {
-                    eprintln!("\n Error reading the Variable file {}. \n", item);
-                    fltk_custom_message("Error reading the Variable file.","Return to the question editor.");
+            // A condition that can't be evaluated for these values, e.g. because
+            // it divides by zero, counts as not met.
+            let mut all_met = true;
+            for (idx, expr) in conditions.iter().enumerate() {
+                match frml_eval(expr, &quest.var_vec) {
+                    Ok(value) if value != 0.0 => {}
+                    Ok(_) => {
+                        misses[idx] += 1;
+                        all_met = false;
+                    }
+                    Err(reason) => {
+                        misses[idx] += 1;
+                        last_err[idx] = Some(reason);
+                        all_met = false;
+                    }
                 }
             }
+            if all_met {
+                return Vec::new();
+            }
         }
 
-            // todo:  Add a check to make sure all needed variable files are in the same directory.
-            // todo:  Add a check to make sure those variable files exist and are readable.
-            // todo:  For later.  Allow for the variable files to be in different directories.
-
+        // region Explain which constraint kept the values from being used.
+        if misses.iter().all(|count| *count == 0) && let Some(err) = derived_err {
+            return vec![err];  // Every set of values failed in a derived Variable's formula.
+        }
+        let (worst, worst_misses) = misses.iter().copied().enumerate()
+            .max_by_key(|(_, count)| *count)
+            .unwrap_or_default();
+        let mut reason = if worst_misses == tries {
+            format!("it was false for all {} sets of values tried.", tries)
+        } else {
+            format!("it failed for {} of the {} sets of values tried, and the conditions were never all true at once.",
+                worst_misses, tries)
+        };
+        if let Some(err) = &last_err[worst] {
+            reason.push_str(&format!("  {}", err));
+        }
+        vec![QbcError::UnmetConstraint { constraint: quest.constraints[worst].clone(), reason }]
         // endregion
     }
 
-    /// Calls up an FLTK TextEditor for entering/editing question text
-    /// and variables.
-    pub fn qst_editor(startertxt: &str, winlabel: &str) -> String {
-        let mut buf = TextBuffer::default();
-        let mut edtrwin = window::Window::default().with_size(800, 300);
-        set_font_size(20);
-        edtrwin.set_color(Color::Blue);
-        edtrwin.set_label(winlabel);
-        edtrwin.make_resizable(true);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::lists::List;
+        use crate::math_functions::{math_seeded_rng, NumStyle};
+
+        fn choice_question(distractors: &[&str]) -> Question {
+            let mut quest = Question::new();
+            quest.qtext = "What is §x§?".to_string();
+            quest.var_vec = vec![test_int_var("x", 1, 9)];
+            quest.answer = "§x§".to_string();
+            quest.kind = QuestionKind::MultipleChoice { distractors: distractors.iter().map(|item| item.to_string()).collect() };
+            quest
+        }
+
+        #[test]
+        fn repeated_choices_are_reported() {
+            let mut rng = math_seeded_rng(3);
+            let mut quest = choice_question(&["§x§ + 1", "§x§ * 1"]);
+            let failed = qst_recalc(&mut quest, &NumLocale::new(), &mut rng);
+            assert_eq!(failed.len(), 1);
+            assert!(matches!(&failed[0], QbcError::RepeatedChoice { option, .. } if option == "§x§ * 1"));
+
+            let mut quest = choice_question(&["§x§ + 1", "§x§ + 2"]);
+            assert!(qst_recalc(&mut quest, &NumLocale::new(), &mut rng).is_empty());
+            assert_eq!(quest.choices.len(), 3);
+        }
 
-        buf.set_text(startertxt);
-        let mut edtr = TextEditor::default().with_size(770, 222).center_of_parent();
+        #[test]
+        fn spans_cover_the_substituted_values() {
+            let locale = NumLocale { style: NumStyle::French, currency: "€".to_string(), currency_after: true };
+            let mut price = Variable::new();
+            price.fname = "price".to_string();
+            price.var_type = "Decimals".to_string();
+            price.content = TypeWrapper::Floating(1234.5);
+            price.params.num_dcml_places = 2;
+            price.params.num_comma_frmttd = true;
+            price.params.num_currency = true;
+            let mut big = test_int_var("big", 1_234_567, 1_234_567);
+            big.params.num_comma_frmttd = true;
+            let var_vec = vec![price, big];
+
+            let text = "Café §price§ for §nope§, §big§ items § left";
+            let (rendered, spans) = qst_substitute_with_spans(text, &var_vec, &locale);
+            let values: Vec<String> = var_vec.iter().map(|var1| vrbl_format_content(var1, &locale)).collect();
+            assert!(values.iter().all(|value| value.contains(['\u{2009}', '\u{202F}'])));
+            assert_eq!(rendered, format!("Café {} for §nope§, {} items § left", values[0], values[1]));
+            assert_eq!(spans.len(), 2);
+            for ((start, end, name), (var1, value)) in spans.iter().zip(var_vec.iter().zip(values.iter())) {
+                assert_eq!(name, &var1.fname);
+                assert_eq!(&rendered[*start..*end], value.as_str());
+            }
+        }
 
-        qst_editor_menubar(&edtr, &mut edtrwin, &mut buf);
+        fn list_var(name: &str, words: &[&str]) -> Variable {
+            let mut uselist = List::new();
+            uselist.typechoice = "Strings".to_string();
+            uselist.words = words.iter().map(|word| word.to_string()).collect();
+            let mut var1 = Variable::new();
+            var1.fname = name.to_string();
+            var1.var_type = "Strings".to_string();
+            var1.uselist = "names.lst".to_string();
+            var1.params.is_from_list = true;
+            var1.list_data = Some(uselist);
+            var1
+        }
 
-        edtr.set_buffer(buf.clone()); // Clone is used here to avoid an ownership error.
-        edtr.wrap_mode(text::WrapMode::AtBounds, 0);
-        edtr.set_color(Color::White);
-        edtr.set_text_size(22);
-        edtr.set_text_color(Color::Black);
+        #[test]
+        fn shared_lists_draw_distinct_entries() {
+            let mut quest = Question::new();
+            quest.distinct_lists = true;
+            quest.var_vec = vec![list_var("first", &["Ann", "Bo"]), list_var("second", &["Ann", "Bo"])];
+            for seed in 0..30 {
+                assert!(qst_regen_values(&mut quest, &mut math_seeded_rng(seed)).is_empty());
+                assert_ne!(quest.var_vec[0].content, quest.var_vec[1].content, "seed {}", seed);
+            }
 
-        edtrwin.end();
-        edtrwin.show();
+            quest.var_vec = vec![list_var("first", &["Ann"]), list_var("second", &["Ann"])];
+            let failed = qst_regen_values(&mut quest, &mut math_seeded_rng(1));
+            assert_eq!(failed.len(), 1);
+            assert!(failed[0].to_string().contains("doesn't have enough different entries"), "{}", failed[0]);
 
-        while edtrwin.shown() {
-            app::wait();
+            quest.distinct_lists = false;  // Repeats are fine.
+            assert!(qst_regen_values(&mut quest, &mut math_seeded_rng(1)).is_empty());
         }
 
-        println!(
-            "\n W5:  End of qst_editor().  The quesion text is:  {} \n",
-            buf.text()
-        );
+        #[test]
+        fn constraints_are_met_by_drawing_again() {
+            let mut quest = Question::new();
+            quest.var_vec = vec![test_int_var("x", 1, 60), test_int_var("y", 2, 60)];
+            quest.constraints = vec!["§x§ > §y§".to_string(), "divides(§y§, §x§)".to_string()];
+            for seed in 0..20 {
+                assert!(qst_regen_constrained(&mut quest, &mut math_seeded_rng(seed)).is_empty());
+                let (TypeWrapper::Integer(xxx), TypeWrapper::Integer(yyy)) = (&quest.var_vec[0].content, &quest.var_vec[1].content) else {
+                    panic!("the values are not integers");
+                };
+                assert!(xxx > yyy && xxx % yyy == 0, "{} and {} were kept", xxx, yyy);
+            }
+        }
 
-        buf.text()
-    }
+        #[test]
+        fn unmet_constraints_are_explained() {
+            let mut quest = Question::new();
+            quest.var_vec = vec![test_int_var("x", 1, 9), test_int_var("y", 1, 9)];
+            quest.constraints = vec!["§x§ > 0".to_string(), "§x§ + §y§ > 100".to_string()];
+            let failed = qst_regen_constrained(&mut quest, &mut math_seeded_rng(5));
+            assert_eq!(failed.len(), 1);
+            let QbcError::UnmetConstraint { constraint, reason } = &failed[0] else {
+                panic!("expected an unmet constraint, got {}", failed[0]);
+            };
+            assert_eq!(constraint, "§x§ + §y§ > 100");
+            assert!(reason.contains(&format!("false for all {} sets", QST_MAX_CONSTRAINT_TRIES)), "{}", reason);
 
-    /// Menu bar for the `qst_editor`.
-    ///
-    pub fn qst_editor_menubar(edtr: &TextEditor, edtrwin: &mut window::Window, buf: &mut TextBuffer) -> menu::MenuBar {
-        let mut menubar = menu::MenuBar::new(0, 0, edtrwin.width(), 40, "");
+            quest.constraints = vec!["§z§ > 1".to_string()];  // No such Variable.
+            assert!(matches!(qst_regen_constrained(&mut quest, &mut math_seeded_rng(5)).as_slice(),
+                             [QbcError::UnmetConstraint { .. }]));
+        }
 
-        // region  "Finished" menu item
-        let mut edtrwin_clone = edtrwin.clone();
-        let quit_idx = menubar.add(
-            "Finished\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                edtrwin_clone.hide();
-            },
-        );
-        menubar.at(quit_idx).unwrap().set_label_color(Color::Red);
-        // endregion
-
-        // region "Insert Variable" menu item
-        let edtr_clone = edtr.clone();
-        let mut buf_clone = buf.clone();
-        menubar.add(
-            "Insert_Variable\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                let newtext = qst_make_var_replace_text();
-                fltk_replace_highlighted_text(&edtr_clone, &mut buf_clone, &newtext);
-            },
-        );
-        // endregion
+        #[test]
+        fn question_survives_json() {
+            let mut quest = choice_question(&["§x§ + 1", "None of these"]);
+            quest.constraints = vec!["§x§ > 2".to_string()];
+            quest.objectives = vec!["Read a value".to_string()];
+            assert!(qst_recalc(&mut quest, &NumLocale::new(), &mut math_seeded_rng(8)).is_empty());
+
+            let json = serde_json::to_string(&quest).unwrap();
+            let readback: Question = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&readback).unwrap(), json);
+            assert_eq!(readback.kind, quest.kind);
+            assert_eq!(readback.choices, quest.choices);
+            assert_eq!(readback.choice_key, quest.choice_key);
+        }
 
-        menubar
+        #[test]
+        fn older_questions_get_defaults() {
+            let json = r#"{"qtext":"Hi","var_dirpath":"","var_vec":[],"answer":"4","objectives":[],"prereqs":[]}"#;
+            let quest: Question = serde_json::from_str(json).unwrap();
+            assert_eq!(quest.kind, QuestionKind::FreeResponse);
+            assert!(quest.choices.is_empty() && quest.constraints.is_empty() && quest.answer_unit.is_empty());
+        }
     }
 
-    /// This function is called when the user highlights text that is
-    /// to be replaced by a variable. This function uses the variable name
-    /// to create text -- between flags -- that then replaces the highlighted text.
-    /// Returns the replacement text.
-    pub fn qst_make_var_replace_text() -> String {
-        // todo: Allow for user to input a more readable variable name
-        //          than the name of the variable file name on disk.
-        // todo: Change the display of the variable name to be more readable without using
-        //          flags.
+    /// FLTK dialogs & editors for creating and editing Questions.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::banks::*;
+        use crate::errors::err_join_messages;
+        use crate::formulas::{frml_check, frml_variables};
+        use crate::global::{glob_check_lastdirused, glob_update_lastdirused};
+        use crate::{APP_FLTK, CURRENT_BANK};
+        use fltk::app::set_font_size;
+        use fltk::dialog::choice2_default;
+        use fltk::enums::{Color, Shortcut};
+        use fltk::prelude::{DisplayExt, GroupExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::text::{TextBuffer, TextEditor};
+        use fltk::{app, menu, text, window};
+        use lib_file::file_fltk::*;
+        use lib_file::file_mngmnt::file_get_dir_list;
+        use lib_myfltk::fltkutils::*;
+        use lib_myfltk::input_fltk::{input_string, input_strvec};
+        use lib_utils::utilities::*;
+        use rand::thread_rng;
+
+        /// Create a new question.
+        ///
+        pub fn qst_create() {
+            let mut newquest = Question::new();
+
+            // region Question data entry
 
-        let lastdir = glob_check_lastdirused();
+            let nowtext = qst_editor(newquest.qtext.as_str(), "Question Editor");
+            newquest.qtext = nowtext.clone();
 
-        let path = file_pathonly(&lastdir, "Choose the folder that contains your variable files.");
-        {
-            LAST_DIR_USED.lock().unwrap().clone_from(&path); // Refresh LAST_DIR_USED
+            // Pull the flagged variables from the text and push them to the variable vector.
+            qst_fill_varvec_parsetext(&mut newquest);
+            qst_input_distinct(&mut newquest);
+            qst_input_constraints(&mut newquest);
+
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            qst_input_kind(&mut newquest);
+            newquest.objectives =
+                input_strvec(&app, "Please enter the question objectives:  ", 790, 300);
+            newquest.prereqs =
+                input_strvec(&app, "Please enter the question prerequisites:  ", 790, 300);
+            // endregion
+
+            // region Store the data.  The bank is saved when the user chooses, the same as after an edit.
+            let mut usebank: Bank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            } // Access the global Bank variable
+            bnk_add_question(&mut usebank, newquest);
+            {
+                // Pass the modified bank into the global variable.
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+            // endregion
+        }
+
+        /// Edit a question.
+        ///
+        pub fn qst_edit(qst_idx: usize) {
+            let app;
+            let mut usebank;
+            {
+                app = *APP_FLTK.lock().unwrap();
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            } // Access global variables.
+
+            let mut editqst = usebank.question_vec.get(qst_idx).unwrap().clone();
+
+            let nowtext = qst_editor(editqst.qtext.as_str(), "Question Editor");
+            editqst.qtext = nowtext.clone();
+
+            // Pull the flagged variables from the text and push them to the variable vector.
+            qst_fill_varvec_parsetext(&mut editqst); // Need to clear the vector first.
+            qst_input_distinct(&mut editqst);
+            qst_input_constraints(&mut editqst);
+
+            qst_input_kind(&mut editqst);
+            editqst.objectives =
+                input_strvec(&app, "Please enter the question objectives:  ", 790, 300);
+            editqst.prereqs =
+                input_strvec(&app, "Please enter the question prerequisites:  ", 790, 300);
+
+            // Put the edited question back in its place.  The bank is saved when the user chooses.
+            bnk_replace_question(&mut usebank, qst_idx, editqst);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+            bnk_refresh_widgets();
         }
 
-        let flist = file_get_dir_list(&path);
-        let varname = fltk_radio_lightbtn_menu(&flist, "");
-        let rpltxt = format!("§{}§", varname);
+        /// Deletes a question from the current bank after asking the user.
+        ///
+        pub fn qst_delete(qst_idx: usize) {
+            let msg = format!("Delete Question {}?  This can't be undone.", qst_idx + 1);
+            if choice2_default(&msg, "Delete", "Cancel", "") != Some(0) {
+                return;
+            }
 
-        rpltxt
-    }
+            let mut usebank: Bank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            bnk_delete_question(&mut usebank, qst_idx);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+        }
+
+        /// Inserts a copy of a question right after it in the current bank.
+        ///
+        pub fn qst_duplicate(qst_idx: usize) {
+            let mut usebank: Bank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            bnk_duplicate_question(&mut usebank, qst_idx);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+        }
+
+        /// Moves a question one place up (toward Question 1) or down in the current bank.
+        ///
+        pub fn qst_move(qst_idx: usize, up: bool) {
+            let newidx = if up { qst_idx.checked_sub(1) } else { Some(qst_idx + 1) };
+            let Some(newidx) = newidx else {
+                return;  // Already the first question.
+            };
+
+            let mut usebank: Bank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            bnk_move_question(&mut usebank, qst_idx, newidx);
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+        }
+
+        /// // Is this necessary now?
+        ///
+        pub fn qst_chooseqst() -> Question {
+            // TODO: Instead of trying to put the whole text of the question
+            //          body in the radio button, number each question in the
+            //          bank display and choose by the question number.
+
+            // Note:  This function may not be necessary.
+
+            let mut usevec: Vec<String> = Vec::new();
+
+            let usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+
+            for item in usebank.question_vec.iter() {
+                usevec.push(item.qtext.clone());
+            }
+
+            let usequest = fltk_radio_lightbtn_menu(&usevec, "");
+            let mut editquest = Question::new();
+
+            for item in usebank.question_vec.iter() {
+                if item.qtext == usequest {
+                    editquest = item.clone();
+                }
+            }
+
+            editquest
+        }
+
+        /// Asks the user for the question's answer formula, written in terms of
+        /// the question's variables, e.g.  `(§const2§ - §const1§) / §coeff1§`.
+        /// The formula is checked and its value calculated from the current
+        /// variable values.
+        pub fn qst_input_answer(quest: &mut Question) {
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            loop {
+                let formula = input_string(&app, "Please input the question's answer formula.  \
+                    Place each variable name between § flags:  ", 790, 300);
+
+                match frml_check(&formula, &quest.var_vec) {
+                    Ok(()) => {
+                        let Some(unit) = qst_input_answer_unit(&app, &formula, quest) else {
+                            continue;  // The units in the formula don't agree, so re-enter it.
+                        };
+                        quest.answer = formula;
+                        quest.answer_unit = unit;
+                        qst_calc_answer(quest);
+                        return;
+                    }
+                    Err(err) => {
+                        let msg = format!("The answer formula could not be used:\n {}", err);
+                        match choice2_default(&msg, "Re-enter", "Keep as typed", "") {
+                            Some(1) => {  // Store the answer as plain text.  It won't be calculated.
+                                quest.answer = formula;
+                                quest.answer_value = None;
+                                return;
+                            }
+                            _ => continue,
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Asks for the unit the answer is given in, if the answer formula uses
+        /// Variables with units.  Blank lets QBC pick one.  Returns `None` if
+        /// the units within the formula don't agree.
+        fn qst_input_answer_unit(app: &app::App, formula: &str, quest: &Question) -> Option<String> {
+            if let Err(err) = frml_check_units(formula, &quest.var_vec, "") {
+                fltk_custom_message(&format!("The answer formula has mismatched units:\n {}", err), "Re-enter the formula.");
+                return None;
+            }
+            let has_units = frml_parse(formula)
+                .map(|expr| frml_variables(&expr).iter()
+                    .filter_map(|name| vrbl_find_by_name(&quest.var_vec, name))
+                    .any(|usevar| !usevar.params.unit.trim().is_empty()))
+                .unwrap_or(false);
+            if !has_units {
+                return Some(String::new());
+            }
+
+            loop {
+                let unit = input_string(app, "Please enter the unit to give the answer in, e.g.  m/s  \
+                    (leave blank to pick one from the variables):  ", 790, 300);
+                match frml_check_units(formula, &quest.var_vec, &unit) {
+                    Ok(()) => return Some(unit.trim().to_string()),
+                    Err(err) => fltk_custom_message(&format!("The unit could not be used:\n {}", err), "Re-enter the unit."),
+                }
+            }
+        }
+
+        /// Asks whether Variables that draw from the same List must get different
+        /// entries.  Only asked when at least two Variables share a List.
+        fn qst_input_distinct(quest: &mut Question) {
+            if !qst_shares_list(quest) {
+                quest.distinct_lists = false;
+                return;
+            }
+            let msg = "Some variables in this question use the same list.\n\
+                Should each of them get a different entry?";
+            quest.distinct_lists = choice2_default(msg, "Different entries", "Repeats are fine", "") == Some(0);
+        }
+
+        /// Asks for the conditions the question's variables must meet together,
+        /// e.g.  `§a§ > §b§`  or  `divides(§divisor§, §dividend§)`.  The variables
+        /// are then regenerated until the conditions hold, so the answer is
+        /// calculated from values that meet them.
+        pub fn qst_input_constraints(quest: &mut Question) {
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            if !quest.constraints.is_empty() {
+                let msg = format!("Keep the conditions on the variables?\n\n{}", quest.constraints.join("\n"));
+                if choice2_default(&msg, "Keep", "Re-enter", "") == Some(0) {
+                    return;
+                }
+            }
+
+            loop {
+                let mut constraints: Vec<String> = Vec::new();
+                let entries = input_strvec(&app, "Please enter any conditions the variables must meet, \
+                    e.g.  §a§ > §b§  (leave blank for none):  ", 790, 300);
+                for condition in entries.into_iter().filter(|entry| !entry.trim().is_empty()) {
+                    match frml_check_condition(&condition, &quest.var_vec) {
+                        Ok(()) => constraints.push(condition),
+                        Err(err) => {
+                            let msg = format!("The condition  {}  could not be used:\n {}", condition, err);
+                            fltk_custom_message(&msg, "Leave it out.");
+                        }
+                    }
+                }
+                quest.constraints = constraints;
+
+                let failed = qst_regen_constrained(quest, &mut thread_rng());
+                if failed.is_empty() {
+                    return;
+                }
+                let msg = format!("{}\n\nChange the conditions or the variables' ranges.", err_join_messages(&failed));
+                if choice2_default(&msg, "Re-enter", "Keep anyway", "") == Some(1) {
+                    return;
+                }
+            }
+        }
+
+        /// Asks the user what kind of question this is, then for the answer
+        /// and whatever else that kind needs -- distractors, a tolerance, etc.
+        pub fn qst_input_kind(quest: &mut Question) {
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            let usevec: Vec<String> = QuestionKind::NAMES.iter().map(|name| name.to_string()).collect();
+            let choice = fltk_radio_lightbtn_menu(&usevec, "What kind of question is this?");
+
+            quest.kind = match choice.as_str() {
+                "Multiple choice" => {
+                    qst_input_answer(quest);
+                    QuestionKind::MultipleChoice { distractors: qst_input_distractors(quest) }
+                }
+                "True/False" => {
+                    let correct = choice2_default("Is the statement true or false?", "True", "False", "") == Some(0);
+                    quest.answer = if correct { "True".to_string() } else { "False".to_string() };
+                    quest.answer_value = None;
+                    QuestionKind::TrueFalse { correct }
+                }
+                "Numeric" => {
+                    qst_input_answer(quest);
+                    let tolerance = loop {
+                        let txt = input_string(&app, "How far from the answer may a response be and still be correct?  \
+                            (0 for an exact answer):  ", 790, 300);
+                        match txt.trim().parse::<f64>() {
+                            Ok(num) if num >= 0.0 => break num,
+                            _ => fltk_custom_message("The tolerance must be a number that is 0 or more.", "Re-enter."),
+                        }
+                    };
+                    QuestionKind::Numeric { tolerance }
+                }
+                "Matching" => {
+                    let entries = input_strvec(&app, "Please enter each pair to be matched as  \
+                        prompt | match  :  ", 790, 300);
+                    let pairs = entries.iter()
+                        .filter_map(|entry| entry.split_once('|'))
+                        .map(|(prompt, rgt)| (prompt.trim().to_string(), rgt.trim().to_string()))
+                        .collect();
+                    quest.answer = String::new();
+                    quest.answer_value = None;
+                    QuestionKind::Matching { pairs }
+                }
+                _ => {
+                    qst_input_answer(quest);
+                    QuestionKind::FreeResponse
+                }
+            };
+
+            let locale;
+            {
+                locale = CURRENT_BANK.lock().unwrap().locale.clone();
+            } // The options are written the way the bank writes its numbers.
+            if let Err(err) = qst_make_choices(quest, &locale, &mut thread_rng()) {
+                let msg = format!("{}\nIt was left out for these values.  It may reappear when the \
+                    question is recalculated.", err);
+                fltk_custom_message(&msg, "Continue.");
+            }
+        }
+
+        /// Asks for the formulas of the wrong answers to a multiple choice question,
+        /// e.g. the answer with a sign error.  Each formula is checked the same
+        /// way as the answer formula.  Anything that isn't a formula is kept as text.
+        pub fn qst_input_distractors(quest: &Question) -> Vec<String> {
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            let mut distractors: Vec<String> = Vec::new();
+            let entries = input_strvec(&app, "Please enter a formula for each wrong answer.  \
+                Place each variable name between § flags:  ", 790, 300);
+            for formula in entries.into_iter() {
+                if let Err(err) = frml_check(&formula, &quest.var_vec) {
+                    let msg = format!("The wrong answer  {}  is not a formula:\n {}", formula, err);
+                    if choice2_default(&msg, "Keep as text", "Leave it out", "") != Some(0) {
+                        continue;
+                    }
+                }
+                distractors.push(formula);
+            }
+
+            distractors
+        }
+
+        /// Recalculates the Variables of a single question in the current bank
+        /// and stores the result back in CURRENT_BANK.
+        pub fn qst_recalc_in_bank(qst_idx: usize) {
+            let mut usebank: Bank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            } // Access the global Bank variable
+
+            let failed = match usebank.question_vec.get_mut(qst_idx) {
+                Some(quest) => qst_recalc(quest, &usebank.locale, &mut thread_rng()),
+                None => return,
+            };
+            usebank.seed = None;  // The bank's values no longer all come from its seed.
+
+            {
+                *CURRENT_BANK.lock().unwrap() = usebank.clone();
+            }
+
+            if !failed.is_empty() {
+                let msg = format!("These variables could not be recalculated:\n {}", err_join_messages(&failed));
+                fltk_custom_message(&msg, "Return to the main menu.");
+            }
+        }
+
+        /// Lets the user choose a question in the current bank by its number.
+        /// Returns the index of the chosen question in `question_vec`.
+        pub fn qst_choose_number() -> Option<usize> {
+            let usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+
+            let usevec: Vec<String> = (1..=usebank.question_vec.len())
+                .map(|qnum| format!("Question {}", qnum))
+                .collect();
+            if usevec.is_empty() {
+                fltk_custom_message("There are no questions in this bank.", "Return to the main menu.");
+                return None;
+            }
+
+            let choice = fltk_radio_lightbtn_menu(&usevec, "Choose a question.");
+            usevec.iter().position(|item| *item == choice)
+        }
+
+        /// Parses the text of a question looking for flags that mark
+        /// variables.  Separates out the flagged variables, reading the
+        /// data into Variable structs and saving them in the
+        /// `quest.var_vec` vector field of the current question.
+        pub fn qst_fill_varvec_parsetext(quest: &mut Question) {
+            // Note:  Variables already embedded in the question are kept.  Only the
+            //          ones that are new to the question are read from the library.
+
+            // region Create a vector of the variable names that have been flagged in the text.
+            let mut usevec: Vec<String> = util_flaggedtxt_2vec(&quest.qtext, '§').iter()
+                .map(|item| qst_split_flag(item).0.to_string())  // Keep the name of  §student:they§
+                .collect();
+            usevec.sort();
+            usevec.dedup(); // Remove repeats of the flagged variable names.
+            // endregion
+
+            // region Keep the embedded variables that are still flagged, along with
+            //          any that derived variables use.  Read the rest from the library.
+            let oldvec = std::mem::take(&mut quest.var_vec);
+            let mut usepath: Option<String> = None;  // Only asked for if a file must be read.
+            let mut idx = 0;
+            while idx < usevec.len() {
+                let item = usevec[idx].clone();
+                idx += 1;
+
+                let newvar = match vrbl_find_by_name(&oldvec, &item) {
+                    Some(oldvar) => oldvar.clone(),
+                    None => {
+                        let usepath = usepath.get_or_insert_with(|| {
+                            let lastdir = glob_check_lastdirused("Variables");
+                            let usepath = file_pathonly(&lastdir, "Choose the folder that contains your variable files.");
+                            glob_update_lastdirused("Variables", &usepath);
+                            usepath
+                        });
+                        match vrbl_read_embedded(usepath, &item) {
+                            Ok(newvar) => newvar,
+                            Err(err) => {
+                                eprintln!("\n Error reading the Variable file {}: {} \n", item, err);
+                                fltk_custom_message(&err.to_string(), "Return to the question editor.");
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                for name in vrbl_formula_names(&newvar) {
+                    if !usevec.contains(&name) {
+                        usevec.push(name);
+                    }
+                }
+                quest.var_vec.push(newvar);
+            }
+            // endregion
+        }
+
+        /// Calls up an FLTK TextEditor for entering/editing question text
+        /// and variables.
+        pub fn qst_editor(startertxt: &str, winlabel: &str) -> String {
+            let mut buf = TextBuffer::default();
+            let mut edtrwin = window::Window::default().with_size(800, 300);
+            let textsize;
+            {
+                let config = CONFIG.lock().unwrap();
+                set_font_size(config.font_size);
+                textsize = config.text_size;
+            }
+            edtrwin.set_color(Color::Blue);
+            edtrwin.set_label(winlabel);
+            edtrwin.make_resizable(true);
+
+            buf.set_text(startertxt);
+            let mut edtr = TextEditor::default().with_size(770, 222).center_of_parent();
+
+            qst_editor_menubar(&edtr, &mut edtrwin, &mut buf);
 
+            edtr.set_buffer(buf.clone()); // Clone is used here to avoid an ownership error.
+            edtr.wrap_mode(text::WrapMode::AtBounds, 0);
+            edtr.set_color(Color::White);
+            edtr.set_text_size(textsize);
+            edtr.set_text_color(Color::Black);
 
+            edtrwin.end();
+            edtrwin.show();
 
+            while edtrwin.shown() {
+                app::wait();
+            }
+
+            println!(
+                "\n W5:  End of qst_editor().  The quesion text is:  {} \n",
+                buf.text()
+            );
+
+            buf.text()
+        }
+
+        /// Menu bar for the `qst_editor`.
+        ///
+        pub fn qst_editor_menubar(edtr: &TextEditor, edtrwin: &mut window::Window, buf: &mut TextBuffer) -> menu::MenuBar {
+            let mut menubar = menu::MenuBar::new(0, 0, edtrwin.width(), 40, "");
+
+            // region  "Finished" menu item
+            let mut edtrwin_clone = edtrwin.clone();
+            let quit_idx = menubar.add(
+                "Finished\t",
+                Shortcut::None,
+                menu::MenuFlag::Normal,
+                move |_| {
+                    edtrwin_clone.hide();
+                },
+            );
+            menubar.at(quit_idx).unwrap().set_label_color(Color::Red);
+            // endregion
+
+            // region "Insert Variable" menu item
+            let edtr_clone = edtr.clone();
+            let mut buf_clone = buf.clone();
+            menubar.add(
+                "Insert_Variable\t",
+                Shortcut::None,
+                menu::MenuFlag::Normal,
+                move |_| {
+                    let newtext = qst_make_var_replace_text();
+                    fltk_replace_highlighted_text(&edtr_clone, &mut buf_clone, &newtext);
+                },
+            );
+            // endregion
+
+            menubar
+        }
+
+        /// This function is called when the user highlights text that is
+        /// to be replaced by a variable. This function uses the variable name
+        /// to create text -- between flags -- that then replaces the highlighted text.
+        /// Returns the replacement text.
+        pub fn qst_make_var_replace_text() -> String {
+            // todo: Allow for user to input a more readable variable name
+            //          than the name of the variable file name on disk.
+            // todo: Change the display of the variable name to be more readable without using
+            //          flags.
+
+            let lastdir = glob_check_lastdirused("Variables");
+
+            let path = file_pathonly(&lastdir, "Choose the folder that contains your variable files.");
+            glob_update_lastdirused("Variables", &path);
+
+            let flist = file_get_dir_list(&path);
+            let varname = fltk_radio_lightbtn_menu(&flist, "");
+            let rpltxt = format!("§{}§", varname);
+
+            rpltxt
+        }
+
+    } // End   gui   submodule
 
     /*
        -- Answers will be calculated from the current variable values.
@@ -828,24 +2780,15 @@ pub mod questions {
 ///
 pub mod variable {
 
-    use crate::global::{glob_check_lastdirused, TypeWrapper, TypeWrapper::*};
-    use crate::{lists::*, math_functions::*, APP_FLTK, LAST_DIR_USED};
-    use fltk::app;
-    use fltk::button::{Button, CheckButton, RadioLightButton};
-    use fltk::enums::{Color, FrameType};
-    use fltk::input::{FloatInput, IntInput};
-    use fltk::prelude::{ButtonExt, GroupExt, InputExt, WidgetBase, WidgetExt, WindowExt};
-    use fltk::{frame::Frame, group::Group, window::Window};
-    use lib_file::{file_fltk::*, file_mngmnt::*};
-    use lib_utils::vec::*;
+    use crate::errors::QbcError;
+    use crate::formulas::{frml_eval, frml_eval_exact, frml_dims, frml_parse, frml_variables};
+    use crate::global::{TypeWrapper, TypeWrapper::*};
+    use crate::{lists::*, math_functions::*, units::unit_parse};
+    use rand::Rng;
     use serde::{Deserialize, Serialize};
-    use std::cell::RefCell;
-    use std::rc::Rc;
-    use std::{fs::File, io::Write};
-    use lib_file::dir_mngmnt::dir_normalize_path;
-    use lib_myfltk::fltkutils::fltk_custom_message;
-    use lib_myfltk::input_fltk::input_string;
-    use crate::misc::dir_is_empty;
+    use std::{fs, io};
+    #[cfg(feature = "gui")]
+    pub use gui::*;
 
     //region Struct Section
 
@@ -859,6 +2802,10 @@ pub mod variable {
         pub uselist: String,
         pub content: TypeWrapper,
         pub var_type: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub source_path: String,  // The library .vrbl file this was read from.  Empty when detached.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub list_data: Option<List>,  // Copy of the `uselist` List, embedded in the bank.
     }
 
     impl Default for Variable {
@@ -870,6 +2817,8 @@ pub mod variable {
                 uselist: "".to_string(),
                 content: Integer(0),
                 var_type: "Strings".to_string(), // "Strings", "chars", "ints", "floats"
+                source_path: "".to_string(),
+                list_data: None,
             }
         }
     }
@@ -898,6 +2847,44 @@ pub mod variable {
         pub num_max_float: f64,
         pub num_dcml_places: usize,
         pub num_comma_frmttd: bool,
+        #[serde(default)]
+        pub num_step_int: i64,  // Values are min, min + step, ...  0 allows every value.
+        #[serde(default)]
+        pub num_step_float: f64,
+        #[serde(default)]
+        pub num_excluded: Vec<f64>,  // Values that are never chosen.
+        #[serde(default)]
+        pub num_rules: Vec<ValueRule>,  // Every rule must hold for a value to be chosen.
+        #[serde(default)]
+        pub is_derived: bool,  // Calculated from `derived_formula` instead of drawn at random.
+        #[serde(default)]
+        pub derived_formula: String,  // e.g.  §a§ * §b§
+        #[serde(default)]
+        pub is_fraction: bool,
+        #[serde(default)]
+        pub frac_num_min: i64,
+        #[serde(default)]
+        pub frac_num_max: i64,
+        #[serde(default)]
+        pub frac_den_min: i64,
+        #[serde(default)]
+        pub frac_den_max: i64,
+        #[serde(default)]
+        pub frac_dens: Vec<i64>,  // When not empty, only these denominators are used.
+        #[serde(default)]
+        pub frac_proper: bool,  // Only fractions smaller than 1 (ignoring sign).
+        #[serde(default)]
+        pub frac_reduced: bool,  // Put each fraction in lowest terms.
+        #[serde(default)]
+        pub frac_format: FracFormat,
+        #[serde(default)]
+        pub num_sig_figs: usize,  // Decimals are rounded to this many significant figures.  0 uses `num_dcml_places`.
+        #[serde(default)]
+        pub num_notation: Notation,
+        #[serde(default)]
+        pub num_currency: bool,  // Shown with the Bank's currency symbol.
+        #[serde(default)]
+        pub unit: String,  // e.g.  km  or  m/s^2.  Blank for a plain number.
     }
 
     impl Default for VarPrmtrs {
@@ -921,1153 +2908,6479 @@ pub mod variable {
                 num_min_float: 0.0,
                 num_max_float: 0.0,
                 num_dcml_places: 0,
-                num_comma_frmttd: false, // Leave implementing this until you need to output it.
+                num_comma_frmttd: false, // Groups the digits with the Bank's separator.  See `math_format_number()`.
+                num_step_int: 0,
+                num_step_float: 0.0,
+                num_excluded: Vec::new(),
+                num_rules: Vec::new(),
+                is_derived: false,
+                derived_formula: String::new(),
+                is_fraction: false,
+                frac_num_min: 0,
+                frac_num_max: 0,
+                frac_den_min: 0,
+                frac_den_max: 0,
+                frac_dens: Vec::new(),
+                frac_proper: false,
+                frac_reduced: false,
+                frac_format: FracFormat::Fraction,
+                num_sig_figs: 0,
+                num_notation: Notation::Standard,
+                num_currency: false,
+                unit: String::new(),
 
                                          // Default values all assume that the variable is an i64.
             }
         }
     } // ~~~~~ End VarPrmtrs impl ~~~~~
 
-      //endregion
+    /// Conditions a numeric Variable's value must meet.  Values that
+    /// break a rule are rejected and a new value is drawn.
+    #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+    pub enum ValueRule {
+        Nonzero,
+        Positive,
+        Even,
+        Odd,
+        Prime,
+        NotPerfectSquare,
+    }
 
-    /// Create a new variable.
-    ///
-    pub fn vrbl_create() {
-        let mut var1 = Variable::new();
+    impl ValueRule {
+        /// Every rule, in the order they are offered to the user.
+        pub const ALL: [ValueRule; 6] = [
+            ValueRule::Nonzero,
+            ValueRule::Positive,
+            ValueRule::Even,
+            ValueRule::Odd,
+            ValueRule::Prime,
+            ValueRule::NotPerfectSquare,
+        ];
+
+        /// Returns the name shown to the user for this rule.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                ValueRule::Nonzero => "Nonzero",
+                ValueRule::Positive => "Positive",
+                ValueRule::Even => "Even",
+                ValueRule::Odd => "Odd",
+                ValueRule::Prime => "Prime",
+                ValueRule::NotPerfectSquare => "Not a perfect square",
+            }
+        }
+
+        /// Returns `true` if the rule only makes sense for whole numbers.
+        ///
+        pub fn is_int_only(&self) -> bool {
+            !matches!(self, ValueRule::Nonzero | ValueRule::Positive)
+        }
 
-        vrbl_parameters_input_box(&mut var1);
+        /// Returns `true` if the value meets the rule.  Whole-number rules
+        /// are never met by a number with a fractional part.
+        pub fn allows(&self, num: f64) -> bool {
+            match self {
+                ValueRule::Nonzero => num != 0.0,
+                ValueRule::Positive => num > 0.0,
+                _ => num.fract() == 0.0 && num.abs() < i64::MAX as f64 && self.allows_int(num as i64),
+            }
+        }
 
-        vrbl_setvalues(&mut var1);
+        /// Returns `true` if the whole number meets the rule.  The number is
+        /// checked as is, so values too large for an `f64` are judged exactly.
+        pub fn allows_int(&self, num: i64) -> bool {
+            match self {
+                ValueRule::Nonzero => num != 0,
+                ValueRule::Positive => num > 0,
+                ValueRule::Even => num % 2 == 0,
+                ValueRule::Odd => num % 2 != 0,
+                ValueRule::Prime => math_is_prime(num),
+                ValueRule::NotPerfectSquare => !math_is_perfect_square(num),
+            }
+        }
+    } // End   ValueRule   impl
 
-        println!(
-            "\n W3 -- Back in vrbl_create():  The Variable struct now contains:  {:?} \n",
-            var1
-        );
+      //endregion
 
-        vrbl_save(&mut var1);
+    /// Save a Variable in json format.
+    ///
+    pub fn vrbl_save_as_json(var: &Variable, usepath: &str) -> Result<(), QbcError> {
+        let var_as_json = serde_json::to_string(var).map_err(|err| QbcError::json(usepath, err))?;
+        fs::write(usepath, var_as_json).map_err(|err| QbcError::io(usepath, err))
     }
 
-    /// Input and save a new variable's parameters into the Variable struct.
+    /// Read a variable from a file.
     ///
-    pub fn vrbl_parameters_input_box(var1: &mut Variable) {
-        // Note:  This is only called from vrbl_create().
+    pub fn vrbl_read_with_pathfname(usepath: &str, fname: &str) -> Result<Variable, QbcError> {
+    // Note:  This function requires that the usepath & fname parameters
+    //          have already been validated & normalized before passing them
+    //          to the function.
 
-        // todo: Grey out the input fields when the variable type is not "int" or "float".
-        //          Use the `deactivate()` method.  First attempt didn't work.
+        let readpath = format!("{}/{}", usepath, fname);
 
-        // region Set up the window.
-        let mut win = Window::new(900, 100, 600, 400, "Variable Parameters");
-        win.set_color(Color::Cyan);
-        win.make_resizable(true);
-        // endregion
+        let contents = match fs::read_to_string(&readpath) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(QbcError::MissingVariable { name: fname.to_string(), path: usepath.to_string() });
+            }
+            Err(err) => return Err(QbcError::io(&readpath, err)),
+        };
+        let mut newvar: Variable = serde_json::from_str(&contents).map_err(|err| QbcError::json(&readpath, err))?;
+        newvar.source_path = readpath;
+        Ok(newvar)
+    }
 
-        // region Create the radio buttons for the variable type.
-        let radio_group = Group::new(0, 0, 600, 50, None);
-
-        // Create horizontal radio light buttons across the top -- initial spacing.
-        let bttn_w = 120;
-        let bttn_h = 30;
-        let spacing = 20;
-        let types_xxx = 40;
-        let types_yyy = 20;
-
-        let mut strings_btn =
-            RadioLightButton::new(types_xxx, types_yyy, bttn_w, bttn_h, "Strings");
-        let chars_btn = RadioLightButton::new(
-            types_xxx + bttn_w + spacing,
-            types_yyy,
-            bttn_w,
-            bttn_h,
-            "Characters",
-        );
-        let ints_btn = RadioLightButton::new(
-            types_xxx + 2 * (bttn_w + spacing),
-            types_yyy,
-            bttn_w,
-            bttn_h,
-            "Integers",
-        );
-        let decimals_btn = RadioLightButton::new(
-            types_xxx + 3 * (bttn_w + spacing),
-            types_yyy,
-            bttn_w,
-            bttn_h,
-            "Decimals",
-        );
+    /// Reads a Variable from the library and embeds a copy of its List, so
+    /// the Variable can be stored in a bank without depending on loose files.
+    pub fn vrbl_read_embedded(usepath: &str, fname: &str) -> Result<Variable, QbcError> {
+        let mut newvar = vrbl_read_with_pathfname(usepath, fname)?;
+        vrbl_embed_list(&mut newvar)?;
+        Ok(newvar)
+    }
 
-        // Set Integers as default selection
-        strings_btn.set_value(true);
+    /// Reads the List named in `uselist` and stores a copy of it in the
+    /// Variable's `list_data` field.  Does nothing if the Variable doesn't
+    /// use a list.
+    pub fn vrbl_embed_list(var1: &mut Variable) -> Result<(), QbcError> {
+        if !var1.params.is_from_list {
+            return Ok(());
+        }
+        let uselist = list_read_with_pathfname(&var1.uselist)?;
+        list_check_typematch(&uselist, vrbl_list_typechoice(&var1.var_type), &var1.uselist)?;
+        var1.list_data = Some(uselist);
+        Ok(())
+    }
 
-        radio_group.end();
-        // endregion
+    /// Replaces a Variable's definition and embedded List with the current
+    /// versions from the shared library.  The Variable's current content is
+    /// kept until it is next recalculated.  Detached Variables are left as is.
+    /// Returns `true` if the Variable was synced.
+    pub fn vrbl_sync_from_library(var1: &mut Variable) -> Result<bool, QbcError> {
+        if var1.source_path.is_empty() {
+            return Ok(false);
+        }
 
-        // region Create "comma" & "list" check boxes in row below the radio buttons.
-
-        // Calculate the position & size of the check boxes.
-        let ckbx_y = types_yyy + bttn_h + 20; // Position below radio buttons
-        let ckbx_w = 150;
-        let ckbx_h = 25;
-        let ckbx_spacing = 55;
-
-        let total_radio_width = bttn_w * 4 + spacing * 2; // Width of all radio buttons + spacing
-        let start_x = types_xxx + (total_radio_width - (ckbx_w * 2 + ckbx_spacing)) / 2;
-
-        // Create the check boxes.
-        let usecommas = CheckButton::new(start_x, ckbx_y, ckbx_w, ckbx_h, "Comma Formatted");
-        let fromlist = CheckButton::new(
-            start_x + ckbx_w + ckbx_spacing,
-            ckbx_y,
-            ckbx_w,
-            ckbx_h,
-            "Value to come from a List",
-        );
+        let contents = match fs::read_to_string(&var1.source_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(QbcError::MissingVariable { name: var1.fname.clone(), path: var1.source_path.clone() });
+            }
+            Err(err) => return Err(QbcError::io(&var1.source_path, err)),
+        };
+        let mut libvar: Variable = serde_json::from_str(&contents)
+            .map_err(|err| QbcError::json(&var1.source_path, err))?;
+        libvar.source_path = var1.source_path.clone();
+        libvar.content = var1.content.clone();
+        vrbl_embed_list(&mut libvar)?;
+
+        *var1 = libvar;
+        Ok(true)
+    }
 
-        // endregion
+    /// Detaches a Variable from the shared library.  From then on only the
+    /// copy in the bank is used, and syncing leaves it alone.
+    pub fn vrbl_detach(var1: &mut Variable) {
+        var1.source_path.clear();
+    }
 
-        // region Set up frames -- for Integer & Decimal parameter entry.
-
-        // region Set up frame parameters
-        let frame_y = ckbx_y + ckbx_h + 20; // Position below checkboxes
-        let frame_w = 250;
-        let input_w = 100;
-        let input_h = 25;
-        let label_h = 20;
-        let field_spacing = 10;
-        let frame_spacing = 20;
-        let frame_h = 30 + (3 * (label_h + input_h + field_spacing)) + 15;
-        // endregion
+    /// Regenerates the `content` field of a Variable using the parameters
+    /// and list file already stored in the Variable.  Unlike `vrbl_setvalues()`,
+    /// the user is never asked to choose a list file, so this can be
+    /// used to recalculate every Variable in a Bank in one pass.
+    ///
+    /// Derived Variables are left alone, since their value depends on the other
+    /// Variables in the question.  See `vrbl_calc_derived()`.
+    pub fn vrbl_regen_content(var1: &mut Variable, rng: &mut impl Rng) -> Result<(), QbcError> {
+        if var1.params.is_derived {
+            Ok(())
+        } else if var1.params.is_from_list {
+            if var1.list_data.is_none() {  // Banks made before lists were embedded.
+                vrbl_embed_list(var1)?;
+            }
+            let uselist = var1.list_data.clone().unwrap_or_default();
+            vrbl_choose_from_list(var1, &uselist, &[], rng)
+        } else {
+            vrbl_gen_numeric(var1, rng)
+        }
+    }
 
-        // region Create Integers frame & input fields
-        let mut int_frame = Group::new(types_xxx, frame_y, frame_w, frame_h, None);
-        let mut int_label = Frame::new(types_xxx, frame_y, frame_w, 30, "Integer Parameters");
-        int_label.set_label_size(14);
-
-        // Calculate centered position for input fields in integer frame
-        let int_input_x = types_xxx + (frame_w - input_w) / 2;
-        let int_first_y = frame_y + 35; // Start below the frame label
-
-        // Integer Minimum Value
-        let _intmin_label = Frame::new(int_input_x, int_first_y, input_w, label_h, "Minimum Value");
-        let mut intmin = IntInput::new(int_input_x, int_first_y + label_h, input_w, input_h, "");
-
-        // Integer Maximum Value
-        let _intmax_label = Frame::new(
-            int_input_x,
-            int_first_y + label_h + input_h + field_spacing,
-            input_w,
-            label_h,
-            "Maximum Value",
-        );
-        let mut intmax = IntInput::new(
-            int_input_x,
-            int_first_y + label_h + input_h + field_spacing + label_h,
-            input_w,
-            input_h,
-            "",
-        );
+    /// Regenerates a Variable like `vrbl_regen_content()`, but a Variable
+    /// that comes from a List never gets one of the `taken` values.  Used so
+    /// Variables sharing a List, e.g. two student names, differ.
+    pub fn vrbl_regen_distinct(var1: &mut Variable, taken: &[TypeWrapper], rng: &mut impl Rng) -> Result<(), QbcError> {
+        if var1.params.is_derived || !var1.params.is_from_list {
+            return vrbl_regen_content(var1, rng);
+        }
+        if var1.list_data.is_none() {
+            vrbl_embed_list(var1)?;
+        }
+        let uselist = var1.list_data.clone().unwrap_or_default();
+        vrbl_choose_from_list(var1, &uselist, taken, rng)
+    }
 
-        int_frame.set_frame(FrameType::DownBox); // Add frame border
-        int_frame.end();
-        // endregion
+    /// Randomly chooses an item from a List and stores it in the Variable's
+    /// `content` field.  Entries are drawn in proportion to their weights, and
+    /// entries equal to one of the `taken` values are skipped.  Fails if no
+    /// entry can be drawn or the list's `typechoice` is not recognized.
+    pub fn vrbl_choose_from_list(var1: &mut Variable, uselist: &List, taken: &[TypeWrapper], rng: &mut impl Rng) -> Result<(), QbcError> {
+        if !matches!(uselist.typechoice.as_str(), "Strings" | "chars" | "ints" | "floats") {
+            let reason = format!("the list {} has the unknown type '{}'.", var1.uselist, uselist.typechoice);
+            return Err(QbcError::invalid(&var1.fname, &reason));
+        }
+        let count = list_len(uselist);
+        if count == 0 {
+            let reason = format!("the list {} is empty.", var1.uselist);
+            return Err(QbcError::invalid(&var1.fname, &reason));
+        }
 
-        // region Create Decimals frame & input fields
-        let mut decimal_frame = Group::new(
-            types_xxx + frame_w + frame_spacing,
-            frame_y,
-            frame_w,
-            frame_h,
-            None,
-        );
-        let mut decimal_label = Frame::new(
-            types_xxx + frame_w + frame_spacing,
-            frame_y,
-            frame_w,
-            30,
-            "Decimal Parameters",
-        );
-        decimal_label.set_label_size(14);
-
-        // Calculate centered position for input fields in decimal frame
-        let dec_input_x = types_xxx + frame_w + frame_spacing + (frame_w - input_w) / 2;
-        let dec_first_y = frame_y + 35; // Start below the frame label
-
-        // Decimal Minimum Value
-        let _decmin_label = Frame::new(dec_input_x, dec_first_y, input_w, label_h, "Minimum Value");
-        let mut decmin = FloatInput::new(dec_input_x, dec_first_y + label_h, input_w, input_h, "");
-
-        // Decimal Maximum Value
-        let _decmax_label = Frame::new(
-            dec_input_x,
-            dec_first_y + label_h + input_h + field_spacing,
-            input_w,
-            label_h,
-            "Maximum Value",
-        );
-        let mut decmax = FloatInput::new(
-            dec_input_x,
-            dec_first_y + label_h + input_h + field_spacing + label_h,
-            input_w,
-            input_h,
-            "",
-        );
+        // Lists without weights draw as they always have, so seeded versions don't change.
+        let chosen = if uselist.weights.is_empty() && taken.is_empty() {
+            Some(rng.gen_range(0..count))
+        } else {
+            let weights: Vec<f64> = list_weights(uselist).into_iter()
+                .enumerate()
+                .map(|(idx, weight)| if taken.contains(&vrbl_list_item(uselist, idx)) { 0.0 } else { weight })
+                .collect();
+            math_weighted_index(rng, &weights)
+        };
 
-        // Decimal Places
-        let _decplaces_label = Frame::new(
-            dec_input_x,
-            dec_first_y + 2 * (label_h + input_h + field_spacing),
-            input_w,
-            label_h,
-            "Decimal Places",
-        );
-        let mut decplaces = IntInput::new(
-            dec_input_x,
-            dec_first_y + 2 * (label_h + input_h + field_spacing) + label_h,
-            input_w,
-            input_h,
-            "",
-        );
+        match chosen {
+            Some(idx) => {
+                var1.content = vrbl_list_item(uselist, idx);
+                Ok(())
+            }
+            None if taken.is_empty() => {
+                let reason = format!("every entry in the list {} has a weight of 0.", var1.uselist);
+                Err(QbcError::invalid(&var1.fname, &reason))
+            }
+            None => {
+                let reason = format!("the list {} doesn't have enough different entries for every Variable that uses it.", var1.uselist);
+                Err(QbcError::invalid(&var1.fname, &reason))
+            }
+        }
+    }
 
-        decimal_frame.set_frame(FrameType::DownBox); // Add frame border
-        decimal_frame.end();
-        // endregion
-
-        // endregion
-
-        // region Create the Submit button
-        let submit_btn_w = 100;
-        let submit_btn_h = 40;
-
-        // Calculate center position based on the frames
-        let total_frames_width = frame_w * 2 + frame_spacing;
-        let submit_btn_x = types_xxx + (total_frames_width - submit_btn_w) / 2;
-        let submit_btn_y = frame_y + frame_h + 20; // 20 pixels gap after frames
-
-        let mut submit_btn = Button::new(
-            submit_btn_x,
-            submit_btn_y,
-            submit_btn_w,
-            submit_btn_h,
-            "Submit",
-        );
-        // endregion
-
-        win.end();
-        win.show();
-
-        // region Clone variables for the callback
-        let strings_btn = strings_btn.clone();
-        let chars_btn = chars_btn.clone();
-        let ints_btn = ints_btn.clone();
-        let decimals_btn = decimals_btn.clone();
-        let mut win_clone = win.clone();
-
-        let datavar = Rc::new(RefCell::new(Variable::new()));
-        let datavar_outside = datavar.clone(); // Create a second Rc pointing to the same RefCell
-
-        // endregion
+    /// Returns the entry of a List at `idx` as Variable content.  `idx` must
+    /// be less than `list_len()`.
+    fn vrbl_list_item(uselist: &List, idx: usize) -> TypeWrapper {
+        match uselist.typechoice.as_str() {
+            "chars" => Letter(uselist.runes[idx]),
+            "ints" => Integer(uselist.intsigned[idx]),
+            "floats" => Floating(uselist.decimals[idx]),
+            _ => Alphanum(uselist.words[idx].clone()),
+        }
+    }
 
-        // region Do the callback for the Submit button
-
-        submit_btn.set_callback(move |_| {
-
-            // region Deal with the radio buttons.
-
-            let vartype = if strings_btn.value() {
-                decmin.deactivate();  // todo: Grey out the input fields when the variable type is not "int" or "float".
-                decmax.deactivate();
-                decplaces.deactivate();
-                intmin.deactivate();
-                intmax.deactivate();
-                "Strings"
-
-            } else if chars_btn.value() {
-                decmin.deactivate();
-                decmax.deactivate();
-                decplaces.deactivate();
-                intmin.deactivate();
-                intmax.deactivate();
-                "Characters"
-            } else if ints_btn.value() {
-                decmin.deactivate();
-                decmax.deactivate();
-                decplaces.deactivate();
-                "Integers"
-            } else if decimals_btn.value() {
-                intmin.deactivate();
-                intmax.deactivate();
-                "Decimals"
+    /// Generates a random numeric value for a Variable using the ranges
+    /// and step stored in its parameters.  Fails if the parameters are not
+    /// valid, e.g. the minimum is larger than the maximum.
+    pub fn vrbl_gen_numeric(var1: &mut Variable, rng: &mut impl Rng) -> Result<(), QbcError> {
+        if var1.params.is_fraction {
+            return vrbl_gen_fraction(var1, rng);
+        }
+        vrbl_check_numeric(var1)?;
+        let prms = &var1.params;
+
+        // Draw until a value passes the rules & exclusions.  With no rules
+        // the first value is always kept.
+        for _ in 0..VRBL_MAX_TRIES {
+            let content = if prms.is_int {
+                // Numeric values will always be randomly generated.
+                Integer(math_gen_stepped_int(rng, prms.num_min_int, prms.num_max_int, prms.num_step_int))
             } else {
-                "None"
+                // The content is a float.
+                let numfloat = math_gen_stepped_float(rng, prms.num_min_float, prms.num_max_float, prms.num_step_float);
+                Floating(vrbl_round_decimal(prms, numfloat))
             };
-
-            datavar.borrow_mut().var_type = vartype.to_string();
-            // endregion
-
-            //region Deal with the "comma" & "list" check boxes.
-            if usecommas.is_checked() {
-                datavar.borrow_mut().params.num_comma_frmttd = true;
-                print!("\n Comma Formatted == true \n");
-            } else {
-                datavar.borrow_mut().params.num_comma_frmttd = false;
-                print!("\n Comma Formatted == false \n");
-            }
-
-            if fromlist.is_checked() {
-                datavar.borrow_mut().params.is_from_list = true;
-                print!("\n List == true \n");
-            } else {
-                datavar.borrow_mut().params.is_from_list = false;
-                print!("\nList == false \n");
+            if vrbl_is_allowed(prms, &content) {
+                var1.content = content;
+                return Ok(());
             }
-            // endregion
-
-            // region Deal with the Integer input fields.
-            if vartype == "Integers" {
-                datavar.borrow_mut().params.is_int = true;
-                datavar.borrow_mut().params.is_float = false;
-                datavar.borrow_mut().params.num_min_int = intmin.value().parse::<i64>().unwrap();
-                datavar.borrow_mut().params.num_max_int = intmax.value().parse::<i64>().unwrap();
-            }
-            // endregion
+        }
 
-            // region Deal with the Decimal input fields.
-            if vartype == "Decimals" {
-                datavar.borrow_mut().params.is_int = false;
-                datavar.borrow_mut().params.is_float = true;
-                datavar.borrow_mut().params.num_min_float = decmin.value().parse::<f64>().unwrap();
-                datavar.borrow_mut().params.num_max_float = decmax.value().parse::<f64>().unwrap();
-                datavar.borrow_mut().params.num_dcml_places =
-                decplaces.value().parse::<usize>().unwrap();
+        // The allowed values are rare.  If the range is small enough to list, choose among them directly.
+        if prms.is_int && let Some(allowed) = vrbl_allowed_ints(prms) {
+            if let Some(numint) = math_random_choice(rng, &allowed) {
+                var1.content = Integer(*numint);
+                return Ok(());
             }
-            // endregion
+            return Err(QbcError::invalid(&var1.fname, &vrbl_no_values_reason(prms)));
+        }
+        let reason = format!("{}  None was found in {} tries.", vrbl_no_values_reason(prms), VRBL_MAX_TRIES);
+        Err(QbcError::invalid(&var1.fname, &reason))
+    }
 
-            println!("\n In the callback, datavar == {:?} \n", datavar);
+    // region Fractions
 
-            // Close the window
-            win_clone.hide();
-        });
-        // endregion
+    /// Generates a random fraction for a Variable from its numerator &
+    /// denominator ranges (or its list of denominators).  Fractions that
+    /// break the Variable's settings, rules, or exclusions are redrawn.
+    pub fn vrbl_gen_fraction(var1: &mut Variable, rng: &mut impl Rng) -> Result<(), QbcError> {
+        vrbl_check_fraction(var1)?;
+        let prms = &var1.params;
 
-        // Keep window active until hidden
-        while win.shown() {
-            app::wait();
+        for _ in 0..VRBL_MAX_TRIES {
+            let den = match math_random_choice(rng, &prms.frac_dens) {
+                Some(den) => *den,
+                None => math_gen_random_num_with(rng, prms.frac_den_min, prms.frac_den_max),
+            };
+            let num = math_gen_random_num_with(rng, prms.frac_num_min, prms.frac_num_max);
+            if let Some(ratio) = vrbl_fraction_if_allowed(prms, num, den) {
+                var1.content = Rational(ratio);
+                return Ok(());
+            }
         }
 
-        *var1 = datavar_outside.borrow().clone();  // This works because the `datavar_outside` Rc
-        // is pointing to the same RefCell as the `datavar` Rc that is used in the callback.
+        let reason = format!("{}  None was found in {} tries.", vrbl_no_fractions_reason(prms), VRBL_MAX_TRIES);
+        Err(QbcError::invalid(&var1.fname, &reason))
     }
 
-    /// Prepare a Variable for saving.
-    ///
-    pub fn vrbl_save(var1: &mut Variable) {
-        // Probably should return either an option or a Result here.
-
-        // region Create the file name to use in the dialog.
-
-        let app;
-        {
-            app = *APP_FLTK.lock().unwrap();
+    /// Builds the fraction num/den the way the Variable's settings ask for,
+    /// or returns `None` if the settings, rules, or exclusions rule it out.
+    fn vrbl_fraction_if_allowed(prms: &VarPrmtrs, num: i64, den: i64) -> Option<Ratio> {
+        let mut ratio = Ratio::new(num, den)?;
+        if prms.frac_proper && ratio.num.unsigned_abs() >= ratio.den as u64 {
+            return None;
         }
+        if prms.frac_reduced {
+            ratio = ratio.reduced();
+        }
+        vrbl_is_allowed(prms, &Rational(ratio)).then_some(ratio)
+    }
 
-        var1.fname = match var1.var_type.as_str() {
-            "Strings" => {
-                let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
-                format!("str.{}", preferred_fname)
-            },
-            "Characters" => {
-                let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
-                format!("str.{}", preferred_fname)
-            },
-            "Integers" => {
-                format!("int.{}..{}", var1.params.num_min_int, var1.params.num_max_int)
-            },
-            "Decimals" => {
-                format!("float.{}_{}", var1.params.num_min_float, var1.params.num_max_float)
-            },
-            _ => "default".to_string(),
-
+    /// Checks the numerator & denominator settings of a fraction Variable.
+    /// When the ranges are small enough, also checks that at least one
+    /// fraction can be made.
+    fn vrbl_check_fraction(var1: &Variable) -> Result<(), QbcError> {
+        let prms = &var1.params;
+        let reason = if prms.is_from_list {
+            "Fractions can't come from a List.".to_string()
+        } else if prms.frac_num_min > prms.frac_num_max {
+            format!("the smallest numerator {} is larger than the largest {}.", prms.frac_num_min, prms.frac_num_max)
+        } else if prms.frac_dens.contains(&0) {
+            "0 can't be a denominator.".to_string()
+        } else if prms.frac_dens.is_empty() && prms.frac_den_min > prms.frac_den_max {
+            format!("the smallest denominator {} is larger than the largest {}.", prms.frac_den_min, prms.frac_den_max)
+        } else if prms.frac_dens.is_empty() && prms.frac_den_min == 0 && prms.frac_den_max == 0 {
+            "0 can't be a denominator.".to_string()
+        } else if let Some(rule) = prms.num_rules.iter().find(|rule| rule.is_int_only()) {
+            format!("the rule '{}' can only be used with Integers.", rule.name())
+        } else if vrbl_any_fraction(prms) == Some(false) {
+            vrbl_no_fractions_reason(prms)
+        } else {
+            return Ok(());
         };
-        // endregion
-
-        // region Create file path & update `LastDirUsed`.
-
-        let lastdir = glob_check_lastdirused();
-
-
-        let filters = vec!["Variables", "*.vrbl", "Banks", "*.bnk", "Lists", "*.lst", "Text", "*.txt", "All Files", "*.*"];
-        let usepath = file_browse_tosave(&lastdir, &var1.fname, &filters);
+        Err(QbcError::invalid(&var1.fname, &reason))
+    }
 
-        {  // Set LAST_DIR_USED to the new path.
-            let purepath: String = dir_normalize_path(&usepath);
-            *LAST_DIR_USED.lock().unwrap() = purepath.clone();
+    /// Returns whether any fraction meets a Variable's settings, or `None`
+    /// if there are too many numerator & denominator pairs to check.
+    fn vrbl_any_fraction(prms: &VarPrmtrs) -> Option<bool> {
+        let dens: Vec<i64> = if prms.frac_dens.is_empty() {
+            if (prms.frac_den_max as i128 - prms.frac_den_min as i128) >= VRBL_MAX_LISTED {
+                return None;
+            }
+            (prms.frac_den_min..=prms.frac_den_max).collect()
+        } else {
+            prms.frac_dens.clone()
+        };
+        let num_count = prms.frac_num_max as i128 - prms.frac_num_min as i128 + 1;
+        if num_count * dens.len() as i128 > VRBL_MAX_LISTED {
+            return None;
         }
-
-        var1.fname = file_path_to_fname(&usepath);
-
-        // endregion
-
-        vrbl_save_as_json(var1, &usepath);
-
-        println!("\n The variable has been saved \n");
+        let found = dens.iter().any(|den| {
+            (prms.frac_num_min..=prms.frac_num_max).any(|num| vrbl_fraction_if_allowed(prms, num, *den).is_some())
+        });
+        Some(found)
     }
 
-    /// Save a Variable in json format.
+    /// Describes the settings that left a fraction Variable with no values.
     ///
-    pub fn vrbl_save_as_json(var: &Variable, usepath: &str) {
-        let var_as_json = serde_json::to_string(var).unwrap();
-
-        let mut file = File::create(usepath).expect("Could not create file!");
-
-        file.write_all(var_as_json.as_bytes())
-            .expect("Cannot write to the file!");
+    fn vrbl_no_fractions_reason(prms: &VarPrmtrs) -> String {
+        let dens = if prms.frac_dens.is_empty() {
+            format!("from {} to {}", prms.frac_den_min, prms.frac_den_max)
+        } else {
+            let dens: Vec<String> = prms.frac_dens.iter().map(|den| den.to_string()).collect();
+            format!("of {}", dens.join(", "))
+        };
+        let proper = if prms.frac_proper { "proper " } else { "" };
+        format!("no {}fraction with a numerator from {} to {} and a denominator {} meets the settings.",
+            proper, prms.frac_num_min, prms.frac_num_max, dens)
     }
 
-    /// Read a variable from a file.
-    ///
-    pub fn vrbl_read() -> Option<Variable> {
-
-        // region Deal with the directory path
+    // endregion
 
-        let lastdir = glob_check_lastdirused();
-        let readpath: String;  // This will be the path to the file that is to be read.
+    /// How many values are drawn when looking for one that passes a numeric
+    /// Variable's rules & exclusions.
+    pub const VRBL_MAX_TRIES: usize = 1000;
 
-        let usepath = file_pathonly(&lastdir, "Choose the folder where you save your Variable files." );
+    /// Integer ranges with more values than this are never listed in full.
+    ///
+    const VRBL_MAX_LISTED: i128 = 100_000;
+
+    /// Returns `true` if the value is not excluded and meets every rule.
+    /// Integers are checked as integers.  Text always passes.
+    pub fn vrbl_is_allowed(prms: &VarPrmtrs, value: &TypeWrapper) -> bool {
+        let excluded = prms.num_excluded.iter().any(|item| match value {
+            Integer(num) => item.round() as i64 == *num,
+            Rational(ratio) => *item == ratio.to_f64(),  // Fractions aren't rounded, so an excluded value must match exactly.
+            Floating(num) => vrbl_round_decimal(prms, *item) == *num,
+            Alphanum(_) | Letter(_) => false,
+        });
+        let meets_rules = prms.num_rules.iter().all(|rule| match value {
+            Integer(num) => rule.allows_int(*num),
+            Rational(ratio) => rule.allows(ratio.to_f64()),
+            Floating(num) => rule.allows(*num),
+            Alphanum(_) | Letter(_) => true,
+        });
+        !excluded && meets_rules
+    }
 
-        {  // Set LAST_DIR_USED to the new path.
-            let purepath: String = dir_normalize_path(&usepath);
-            *LAST_DIR_USED.lock().unwrap() = purepath.clone();
+    /// Rounds a Decimal Variable's value to its significant figures, or to
+    /// its decimal places when no significant figures are set.
+    pub fn vrbl_round_decimal(prms: &VarPrmtrs, num: f64) -> f64 {
+        match prms.num_sig_figs {
+            0 => math_round_to_place_f64(&num, prms.num_dcml_places),
+            sig_figs => math_round_to_sig_figs(num, sig_figs),
         }
+    }
 
-        if !dir_is_empty(&usepath) {
-            readpath = file_fullpath(&usepath, "Choose the Variable file you want to read.");
-        } else {
-            fltk_custom_message("The directory you chose is empty.","Return to program.");
+    /// Lists every value an integer Variable can take once its step, rules,
+    /// and exclusions are applied.  Returns `None` if the range is too large
+    /// to list.
+    pub fn vrbl_allowed_ints(prms: &VarPrmtrs) -> Option<Vec<i64>> {
+        let step = prms.num_step_int.max(1) as i128;
+        let (min, max) = (prms.num_min_int as i128, prms.num_max_int as i128);
+        if (max - min) / step >= VRBL_MAX_LISTED {
             return None;
         }
+        let allowed = (0..=(max - min) / step)
+            .map(|idx| (min + idx * step) as i64)
+            .filter(|num| vrbl_is_allowed(prms, &Integer(*num)))
+            .collect();
+        Some(allowed)
+    }
 
-        // endregion
-
-        //region Read the file & return the Variable struct
-
-        match file_read_to_string(&readpath) {
-            Ok(contents) => {
-                serde_json::from_str(&contents).unwrap()
-            }
-            Err(err) => {
-                eprintln!("\n Error reading the file: {} \n", err);
-                fltk_custom_message("Could not read the file.","Return to the question editor.");
-                None
-            }
+    /// Describes the rules & exclusions that left a Variable with no values.
+    ///
+    fn vrbl_no_values_reason(prms: &VarPrmtrs) -> String {
+        let mut limits: Vec<String> = Vec::new();
+        if !prms.num_rules.is_empty() {
+            let names: Vec<&str> = prms.num_rules.iter().map(|rule| rule.name()).collect();
+            let plural = if names.len() > 1 { "s" } else { "" };
+            limits.push(format!("the rule{} {}", plural, names.join(", ")));
         }
-
-        // endregion
+        if !prms.num_excluded.is_empty() {
+            let excluded: Vec<String> = prms.num_excluded.iter().map(|item| item.to_string()).collect();
+            let plural = if excluded.len() > 1 { "s" } else { "" };
+            limits.push(format!("the excluded value{} {}", plural, excluded.join(", ")));
+        }
+        let (min, max) = if prms.is_int {
+            (prms.num_min_int.to_string(), prms.num_max_int.to_string())
+        } else {
+            (prms.num_min_float.to_string(), prms.num_max_float.to_string())
+        };
+        format!("no value from {} to {} meets {}.", min, max, limits.join(" and "))
     }
 
-    /// Read a variable from a file.
+    /// Checks the range, step, & decimal places of a numeric Variable.
     ///
-    pub fn vrbl_read_with_pathfname(usepath: &str, fname: &str) -> Option<Variable> {
-    // Note:  This function requires that the usepath & fname parameters
-    //          have already been validated & normalized before passing them
-    //          to the function.
-
-        let readpath = format!("{}/{}", usepath, fname);
-
-        match file_read_to_string(&readpath) {
-            Ok(contents) => {
-                serde_json::from_str(&contents).unwrap()
+    fn vrbl_check_numeric(var1: &Variable) -> Result<(), QbcError> {
+        let prms = &var1.params;
+        let reason = if prms.is_int {
+            let (min, max, step) = (prms.num_min_int, prms.num_max_int, prms.num_step_int);
+            if min > max {
+                format!("the minimum {} is larger than the maximum {}.", min, max)
+            } else if step < 0 {
+                format!("the step {} is negative.", step)
+            } else if let Some(item) = prms.num_excluded.iter().find(|item| item.fract() != 0.0) {
+                format!("the excluded value {} is not a whole number.", item)
+            } else {
+                return Ok(());
             }
-            Err(err) => {
-                eprintln!("\n Error reading the file: {} \n", err);
-                fltk_custom_message("Could not read the file.","Return to the question editor.");
-                None
+        } else {
+            let (min, max, step) = (prms.num_min_float, prms.num_max_float, prms.num_step_float);
+            if !(min <= max && min.is_finite() && max.is_finite()) {  // Also catches NaN.
+                format!("the range {} to {} is not valid.", min, max)
+            } else if !(step >= 0.0 && step.is_finite()) {
+                format!("the step {} is not valid.", step)
+            } else if prms.num_dcml_places > VRBL_MAX_DCML_PLACES {
+                format!("no more than {} decimal places can be used.", VRBL_MAX_DCML_PLACES)
+            } else if prms.num_sig_figs > VRBL_MAX_DCML_PLACES {
+                format!("no more than {} significant figures can be used.", VRBL_MAX_DCML_PLACES)
+            } else if prms.num_sig_figs == 0 && math_decimal_places(step) > prms.num_dcml_places {
+                // Rounding would move the values off the step.
+                format!("the step {} needs at least {} decimal places.", step, math_decimal_places(step))
+            } else if let Some(rule) = prms.num_rules.iter().find(|rule| rule.is_int_only()) {
+                format!("the rule '{}' can only be used with Integers.", rule.name())
+            } else {
+                return Ok(());
             }
-        }
+        };
+        Err(QbcError::invalid(&var1.fname, &reason))
     }
 
-    /// Sets and calculates the values of non-boolean fields in the Variable struct.
-    ///
-    pub fn vrbl_setvalues(var1: &mut Variable) {
-        if var1.params.is_from_list {  // If the variable content comes from a list.
-
-            match var1.var_type.as_str() { // Set the variable content field.
-                "Strings" => {
-                    let read_optn = list_read("Strings");
-                    match read_optn {
-                        Some((fname, newlist)) => {
-                            var1.uselist = fname; // Sets the value of the variable's listname field
-                            let usevec = newlist.words.clone(); // Clones the list content vector so you can mess with it.
-                            let item = vec_random_choice(&usevec);
-                            match item {
-                                Some(x) => {
-                                    println!("\n The chosen item is:  {:?}", x);
-                                    var1.content = Alphanum(x.0.to_string());
-                                }
-                                None => {
-                                    fltk_custom_message("No item was chosen.","Return.");
-                                    eprintln!("\n The function `vec_random_choice()` returned `None`. \n");
-                                    return;
-                                },
-                            }
+    /// The most decimal places a Decimal Variable may be rounded to.  An f64
+    /// doesn't hold more significant digits than this.
+    pub const VRBL_MAX_DCML_PLACES: usize = 15;
 
-                        }
-                        None => {
-                            eprintln!("No list file selected.");
-                            fltk_custom_message("No list file selected.","Return to the question editor.");
-                            return;
-                        }
+    /// Checks that a Variable's parameters can be used to generate values.
+    /// Used before saving a new or edited Variable.
+    pub fn vrbl_validate(var1: &Variable) -> Result<(), QbcError> {
+        let prms = &var1.params;
+        let is_text = matches!(vrbl_list_typechoice(&var1.var_type), "Strings" | "chars");
 
-                    }
-                }
+        if !prms.unit.trim().is_empty() {
+            if is_text {
+                return Err(QbcError::invalid(&var1.fname, "only numbers can have a unit."));
+            }
+            if let Err(err) = unit_parse(&prms.unit) {
+                return Err(QbcError::invalid(&var1.fname, &format!("its unit can't be used.  {}", err)));
+            }
+        }
+        if prms.is_fraction && !prms.is_derived {
+            return vrbl_check_fraction(var1);
+        }
+        if prms.is_derived {
+            let reason = if prms.is_from_list {
+                "it can't both come from a List and be calculated from a formula.".to_string()
+            } else if is_text {
+                "only Integers, Decimals, and Fractions can be calculated from a formula.".to_string()
+            } else if prms.num_dcml_places > VRBL_MAX_DCML_PLACES {
+                format!("no more than {} decimal places can be used.", VRBL_MAX_DCML_PLACES)
+            } else if prms.num_sig_figs > VRBL_MAX_DCML_PLACES {
+                format!("no more than {} significant figures can be used.", VRBL_MAX_DCML_PLACES)
+            } else if let Err(err) = frml_parse(&prms.derived_formula) {
+                format!("its formula can't be used.  {}", err)
+            } else {
+                return Ok(());  // The Variables the formula uses are checked in the question.
+            };
+            return Err(QbcError::invalid(&var1.fname, &reason));
+        }
 
-                "chars" => {
-                    let read_optn = list_read("chars");
-                    match read_optn {
-                        Some((fname, newlist)) => {
-                            var1.uselist = fname; // Sets the value of the variable's listname field
-                            let usevec = newlist.runes.clone(); // Clones the list content vector so you can mess with it.
-                            let item = vec_random_choice(&usevec);
-                            match item {
-                                Some(x) => {
-                                    println!("\n The chosen item is:  {:?}", x);
-                                    var1.content = Letter(*x.0);
-                                }
-                                None => {
-                                    fltk_custom_message("No item was chosen.","Return.");
-                                    eprintln!("\n The function `vec_random_choice()` returned `None`. \n");
-                                    return;
-                                },
-                            }
+        if is_text && !prms.is_from_list {
+            return Err(QbcError::invalid(&var1.fname, "Strings and Characters must come from a List."));
+        }
+        if prms.is_from_list {
+            return Ok(());  // The List is checked when it is chosen.
+        }
+        vrbl_check_numeric(var1)?;
 
-                        }
-                        None => {
-                            eprintln!("No list file selected.");
-                            fltk_custom_message("No list file selected.","Return to the question editor.");
-                            return;
-                        }
+        if prms.is_int && vrbl_allowed_ints(prms).is_some_and(|allowed| allowed.is_empty()) {
+            return Err(QbcError::invalid(&var1.fname, &vrbl_no_values_reason(prms)));
+        }
+        Ok(())
+    }
 
-                    }
-                }
+    // region Derived Variables
 
-                "ints" => {
-                    let read_optn = list_read("ints");
-                    match read_optn {
-                        Some((fname, newlist)) => {
-                            var1.uselist = fname; // Sets the value of the variable's listname field
-                            let usevec = newlist.intsigned.clone(); // Clones the list content vector so you can mess with it.
-                            let item = vec_random_choice(&usevec);
-                            match item {
-                                Some(x) => {
-                                    println!("\n The chosen item is:  {:?}", x);
-                                    var1.content = Integer(*x.0);
-                                }
-                                None => {
-                                    fltk_custom_message("No item was chosen.", "Return.");
-                                    eprintln!("\n The function `vec_random_choice()` returned `None`. \n");
-                                    return;
-                                },
-                            }
-                        }
-                        None => {
-                            eprintln!("No list file selected.");
-                            fltk_custom_message("No list file selected.", "Return to the question editor.");
-                            return;
-                        }
-                    }
-                }
+    /// Returns the names of the Variables a derived Variable's formula uses.
+    /// Returns an empty vector for other Variables, or if the formula
+    /// doesn't parse.
+    pub fn vrbl_formula_names(var1: &Variable) -> Vec<String> {
+        if !var1.params.is_derived {
+            return Vec::new();
+        }
+        frml_parse(&var1.params.derived_formula)
+            .map(|expr| frml_variables(&expr))
+            .unwrap_or_default()
+    }
 
-                "floats" => {
-
-                    let read_optn = list_read("floats");
-                    match read_optn {
-                        Some((fname, newlist)) => {
-                            var1.uselist = fname; // Sets the value of the variable's listname field
-                            let usevec = newlist.decimals.clone(); // Clones the list content vector so you can mess with it.
-                            let item = vec_random_choice(&usevec);
-                            match item {
-                                Some(x) => {
-                                    println!("\n The chosen item is:  {:?}", x);
-                                    var1.content = Floating(*x.0);
-                                }
-                                None => {
-                                    fltk_custom_message("No item was chosen.", "Return.");
-                                    eprintln!("\n The function `vec_random_choice()` returned `None`. \n");
-                                    return;
-                                },
-                            }
-                        }
-                        None => {
-                            eprintln!("No list file selected.");
-                            fltk_custom_message("No list file selected.", "Return to the question editor.");
-                            return;
-                        }
+    /// Works out the order in which the derived Variables in `var_vec` must be
+    /// calculated, so each one comes after the derived Variables it uses.
+    /// Returns their indexes in `var_vec`.  Fails if a formula doesn't parse,
+    /// uses a Variable that isn't in `var_vec`, or depends on itself.
+    pub fn vrbl_derived_order(var_vec: &[Variable]) -> Result<Vec<usize>, QbcError> {
+        // region Find what each derived Variable depends on.
+        let mut deps: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (idx, item) in var_vec.iter().enumerate().filter(|(_, item)| item.params.is_derived) {
+            let expr = frml_parse(&item.params.derived_formula)
+                .map_err(|reason| QbcError::invalid(&item.fname, &format!("its formula can't be used.  {}", reason)))?;
+            let mut usedeps: Vec<usize> = Vec::new();
+            for name in frml_variables(&expr) {
+                match vrbl_position_by_name(var_vec, &name) {
+                    Some(depidx) => usedeps.push(depidx),
+                    None => {
+                        let reason = format!("its formula uses §{}§, which is not in the question.", name);
+                        return Err(QbcError::invalid(&item.fname, &reason));
                     }
                 }
+            }
+            deps.push((idx, usedeps));
+        }
+        // endregion
 
-                _ => {}
+        // region Depth-first search, keeping the path so a cycle can be shown.
+        fn visit(idx: usize, var_vec: &[Variable], deps: &[(usize, Vec<usize>)],
+                 path: &mut Vec<usize>, order: &mut Vec<usize>) -> Result<(), QbcError> {
+            if order.contains(&idx) {
+                return Ok(());
+            }
+            if let Some(start) = path.iter().position(|item| *item == idx) {
+                let mut names: Vec<String> = path[start..].iter().map(|item| format!("§{}§", var_vec[*item].fname)).collect();
+                names.push(format!("§{}§", var_vec[idx].fname));
+                let reason = format!("its formula depends on itself:  {}", names.join(" → "));
+                return Err(QbcError::invalid(&var_vec[idx].fname, &reason));
+            }
+            let Some((_, usedeps)) = deps.iter().find(|(item, _)| *item == idx) else {
+                return Ok(());  // Not derived, so it doesn't depend on anything.
+            };
+            path.push(idx);
+            for depidx in usedeps.iter() {
+                visit(*depidx, var_vec, deps, path, order)?;
             }
+            path.pop();
+            order.push(idx);
+            Ok(())
+        }
 
+        let mut order: Vec<usize> = Vec::new();
+        for (idx, _) in deps.iter() {
+            visit(*idx, var_vec, &deps, &mut Vec::new(), &mut order)?;
+        }
+        // endregion
 
+        Ok(order)
+    }
 
-        } else if var1.params.is_int {
-            // Numeric values will always be randomly generated.
-            let numint: i64 =
-                math_gen_random_num(var1.params.num_min_int, var1.params.num_max_int);
-            var1.content = Integer(numint);
-        } else {
-            // The content is a float.
-            let mut numfloat: f64 =
-                math_gen_random_num(var1.params.num_min_float, var1.params.num_max_float);
-            numfloat = math_round_to_place_f64(&numfloat, var1.params.num_dcml_places);
-            var1.content = Floating(numfloat);
+    /// Calculates the content of every derived Variable in `var_vec` from the
+    /// current content of the others.  Integer results are rounded to the
+    /// nearest whole number;  decimals to the Variable's decimal places or
+    /// significant figures.
+    pub fn vrbl_calc_derived(var_vec: &mut [Variable]) -> Result<(), QbcError> {
+        for idx in vrbl_derived_order(var_vec)? {
+            let fail = |reason: &str| QbcError::invalid(&var_vec[idx].fname, &format!("its formula can't be calculated.  {}", reason));
+            let expr = frml_parse(&var_vec[idx].params.derived_formula).map_err(|reason| fail(&reason))?;
+            let dim = frml_dims(&expr, var_vec).map_err(|reason| fail(&reason))?;
+            let unit = unit_parse(&var_vec[idx].params.unit).map_err(|reason| fail(&reason))?;
+            if unit.dim != dim {
+                let reason = match var_vec[idx].params.unit.trim() {
+                    "" => format!("the formula gives {}, so the Variable needs a unit.", dim),
+                    usetxt => format!("the formula gives {}, which can't be written in {}.", dim, usetxt),
+                };
+                return Err(QbcError::invalid(&var_vec[idx].fname, &reason));
+            }
+            // Formulas work in SI base units, so convert back to the Variable's own.
+            let value = frml_eval(&expr, var_vec).map_err(|reason| fail(&reason))? / unit.factor;
+            let content = match vrbl_list_typechoice(&var_vec[idx].var_type) {
+                "ints" => Integer(value.round() as i64),
+                "fractions" => match frml_eval_exact(&expr, var_vec) {
+                    Some(ratio) => Rational(ratio.reduced()),
+                    None => return Err(fail("The result can't be worked out exactly as a fraction.")),
+                },
+                _ => Floating(vrbl_round_decimal(&var_vec[idx].params, value)),
+            };
+            var_vec[idx].content = content;
         }
+        Ok(())
     }
 
-    /// Recalculates the values in the non-boolean fields of a Variable struct.
-    ///
-    pub fn vrbl_recalc() {
-        match vrbl_read() {
-            Some(mut vrbl) => {
-
-                println!("\n The variable before recalc is: \n {:?}", vrbl);
-
-                vrbl_setvalues(&mut vrbl);
+    // endregion
 
-                println!("\n The variable after recalc is: \n {:?} \n", vrbl);
+    /// Returns `true` if the Variable was read from the library file at
+    /// `libpath`.  Detached Variables never match.
+    pub fn vrbl_is_from_file(var1: &Variable, libpath: &str) -> bool {
+        if var1.source_path.is_empty() {
+            return false;
+        }
+        // Compare the resolved paths so "dir//x.vrbl" & "dir/x.vrbl" match.
+        match (fs::canonicalize(&var1.source_path), fs::canonicalize(libpath)) {
+            (Ok(varpath), Ok(libpath)) => varpath == libpath,
+            _ => var1.source_path == libpath,
+        }
+    }
 
-                vrbl_save(&mut vrbl);
+    /// Formats the content of a Variable for display in a question, using
+    /// the Variable's decimal place, grouping, currency, & unit settings and
+    /// the separators of the Bank's `locale`.
+    pub fn vrbl_format_content(var1: &Variable, locale: &NumLocale) -> String {
+        let prms = &var1.params;
+        let numtxt = match &var1.content {
+            Alphanum(txt) => return txt.clone(),
+            Letter(ch) => return ch.to_string(),
+            Rational(ratio) => math_format_ratio(ratio, &prms.frac_format),
+            Integer(num) => math_format_number(&num.to_string(), prms.num_comma_frmttd, locale),
+            Floating(num) if prms.num_sig_figs > 0 || prms.num_notation != Notation::Standard => {
+                math_format_number(&math_format_sig_figs(*num, prms.num_sig_figs, &prms.num_notation), prms.num_comma_frmttd, locale)
             }
-            None => {}
+            Floating(num) => math_format_number(&format!("{:.*}", prms.num_dcml_places, num), prms.num_comma_frmttd, locale),
+        };
+        let numtxt = if prms.num_currency { math_format_currency(&numtxt, locale) } else { numtxt };
+        match prms.unit.trim() {
+            "" => numtxt,
+            unit => format!("{} {}", numtxt, unit),
         }
-
-        //let mut usevar = vrbl_read();
-
-        //vrbl_setvalues(&mut usevar);
-        //vrbl_save(&mut usevar);
-
     }
 
-} // End   variable   module
-
-/// Functions for creating and manipulating lists.
-///
-pub mod lists {
-
-    use crate::{APP_FLTK, LAST_DIR_USED};
-    use lib_file::file_fltk::{file_browse_tosave, file_fullpath_fltr, file_pathonly};
-    use lib_file::file_mngmnt::file_read_to_string;
-    use lib_myfltk::input_fltk::*;
-    use serde::{Deserialize, Serialize};
-    use std::{fs::File, io::Write};
-    use lib_file::dir_mngmnt::dir_normalize_path;
-    use lib_myfltk::fltkutils::fltk_custom_message;
-    use crate::global::glob_check_lastdirused;
-    use crate::misc::dir_is_empty;
-
-    // region Struct section
+    /// Returns the attributes of the List entry a Variable holds, e.g. the
+    /// pronouns of a name.  Variables that don't come from a List, or whose
+    /// entry has none, get blank attributes.
+    pub fn vrbl_entry_attrs(var1: &Variable) -> EntryAttrs {
+        let Some(uselist) = var1.list_data.as_ref().filter(|_| var1.params.is_from_list) else {
+            return EntryAttrs::default();
+        };
+        (0..list_len(uselist))
+            .find(|idx| vrbl_list_item(uselist, *idx) == var1.content)
+            .and_then(|idx| uselist.attributes.get(idx).cloned())
+            .unwrap_or_default()
+    }
 
-    /// Contains a vector field for each of the four data types
-    /// allowed in lists associated with QBC.
-    #[derive(Debug, Serialize, Deserialize)]
-    pub struct List {
-        pub words: Vec<String>,
-        pub runes: Vec<char>,
-        pub intsigned: Vec<i64>,
-        pub decimals: Vec<f64>,
-        pub typechoice: String, // "Strings", "chars", "ints", "floats"
+    /// Finds the Variable in `var_vec` that goes with a name flagged in a
+    /// question's text or answer formula.  The name may be given with or
+    /// without the `.vrbl` extension.
+    pub fn vrbl_find_by_name<'a>(var_vec: &'a [Variable], name: &str) -> Option<&'a Variable> {
+        vrbl_position_by_name(var_vec, name).map(|idx| &var_vec[idx])
     }
 
-    impl Default for List {
-        fn default() -> Self {
-            Self::new()
-        }
+    /// Like `vrbl_find_by_name()`, but returns the Variable's index in `var_vec`.
+    ///
+    pub fn vrbl_position_by_name(var_vec: &[Variable], name: &str) -> Option<usize> {
+        var_vec.iter().position(|item| {
+            item.fname == name || item.fname.strip_suffix(".vrbl") == Some(name)
+        })
     }
 
-    impl List {
-        /// Initializes a new list struct.
-        pub fn new() -> List {
-            Self {
-                words: Vec::new(),
-                runes: Vec::new(),
-                intsigned: Vec::new(),
-                decimals: Vec::new(),
-                typechoice: "Strings".to_string(),
-            }
+    /// Converts a Variable's `var_type` into the matching List `typechoice`.
+    ///
+    pub fn vrbl_list_typechoice(var_type: &str) -> &'static str {
+        // Note:  The parameter window stores "Characters", "Integers", & "Decimals",
+        //          while lists use "chars", "ints", & "floats".  Accept both.
+        match var_type {
+            "chars" | "Characters" => "chars",
+            "ints" | "Integers" => "ints",
+            "floats" | "Decimals" => "floats",
+            "fractions" | "Fractions" => "fractions",  // Lists can't hold fractions.
+            _ => "Strings",
         }
-    } // ----------  End List impl ----------
+    }
 
-    // endregion
+    /// An Integers variable drawn from `min` to `max`, for tests in any module.
+    #[cfg(test)]
+    pub(crate) fn test_int_var(name: &str, min: i64, max: i64) -> Variable {
+        let mut var1 = Variable::new();
+        var1.fname = name.to_string();
+        var1.var_type = "Integers".to_string();
+        var1.content = TypeWrapper::Integer(min);
+        var1.params.is_int = true;
+        var1.params.num_min_int = min;
+        var1.params.num_max_int = max;
+        var1
+    }
 
-    /// Create a new list.
-    /// Note that the function saves the list, but does not return it.
-    pub fn list_create(typech: &str) {
-        let mut newlist = List::new();
-        newlist.typechoice = typech.to_string();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-        let app;
-        {
-            app = APP_FLTK.lock().unwrap();
+        fn int_content(var1: &Variable) -> i64 {
+            match var1.content {
+                Integer(num) => num,
+                _ => panic!("{} is not an integer", var1.fname),
+            }
         }
 
-        match typech {
-            "String" | "Strings" => {
-                // String
-                let uselist = input_strvec(&app, "Please enter a string.", 790, 300);
-                newlist.words = uselist.clone();
-                list_save(&newlist);
+        #[test]
+        fn drawn_values_meet_rules_and_exclusions() {
+            let mut rng = math_seeded_rng(11);
+            let mut var1 = test_int_var("odd", -20, 20);
+            var1.params.num_rules = vec![ValueRule::Odd, ValueRule::Positive];
+            var1.params.num_excluded = vec![7.0, 13.0];
+            for _ in 0..200 {
+                vrbl_gen_numeric(&mut var1, &mut rng).unwrap();
+                let num = int_content(&var1);
+                assert!(num > 0 && num % 2 != 0 && num != 7 && num != 13, "{} was drawn", num);
             }
 
-            "char" | "chars" => {
-                // char
-                let uselist = input_charvec(&app, "Please enter a character.");
-                newlist.runes = uselist.clone();
-                list_save(&newlist);
-            }
+            // Primes are rare in a large range, so most draws are rejected.
+            let mut var1 = test_int_var("prime", 1_000_000, 1_000_100);
+            var1.params.num_rules = vec![ValueRule::Prime];
+            vrbl_gen_numeric(&mut var1, &mut rng).unwrap();
+            assert!(math_is_prime(int_content(&var1)));
+        }
 
-            "int" | "ints" => {
-                // i64
-                let uselist = input_i64vec(&app, "Please enter an integer.");
-                newlist.intsigned = uselist.clone();
-                list_save(&newlist);
-            }
+        #[test]
+        fn no_values_left_is_an_error() {
+            let mut var1 = test_int_var("none", 2, 8);
+            var1.params.num_step_int = 2;
+            var1.params.num_rules = vec![ValueRule::Odd];
+            let err = vrbl_gen_numeric(&mut var1, &mut math_seeded_rng(1)).unwrap_err();
+            assert!(err.to_string().contains("no value from 2 to 8 meets the rule Odd"), "{}", err);
+
+            let mut var1 = test_int_var("excluded", 1, 3);
+            var1.params.num_excluded = vec![1.0, 2.0, 3.0];
+            assert!(vrbl_gen_numeric(&mut var1, &mut math_seeded_rng(1)).is_err());
+        }
 
-            "float" | "floats" => {
-                // f64
+        fn derived_var(name: &str, formula: &str) -> Variable {
+            let mut var1 = test_int_var(name, 0, 0);
+            var1.params.is_derived = true;
+            var1.params.derived_formula = formula.to_string();
+            var1
+        }
 
-                let uselist = input_f64vec(&app, "Please enter a floating point number.");
-                newlist.decimals = uselist.clone();
-                list_save(&newlist);
-            }
+        #[test]
+        fn derived_variables_follow_their_inputs() {
+            // Listed before the Variables they use, to check the order is worked out.
+            let mut var_vec = vec![
+                derived_var("area", "§side§ * §side§"),
+                derived_var("side", "§base§ + 1"),
+                test_int_var("base", 4, 4),
+            ];
+            var_vec[2].content = Integer(4);
+            assert_eq!(vrbl_derived_order(&var_vec).unwrap(), vec![1, 0]);
+            vrbl_calc_derived(&mut var_vec).unwrap();
+            assert_eq!((int_content(&var_vec[1]), int_content(&var_vec[0])), (5, 25));
+        }
 
-            _ => {
-                fltk_custom_message("Invalid list type.","Return");
-                // So, then what happens in the calling function?  Probably
-                // need to return an option here.
-            }
+        #[test]
+        fn derived_cycles_are_errors() {
+            let var_vec = vec![
+                derived_var("aaa", "§bbb§ + 1"),
+                derived_var("bbb", "§ccc§ * 2"),
+                derived_var("ccc", "§aaa§ - 3"),
+            ];
+            let err = vrbl_derived_order(&var_vec).unwrap_err().to_string();
+            assert!(err.contains("§aaa§ → §bbb§ → §ccc§ → §aaa§"), "{}", err);
+
+            let var_vec = vec![derived_var("self", "§self§ + 1")];
+            assert!(vrbl_derived_order(&var_vec).is_err());
+            let var_vec = vec![derived_var("lost", "§nowhere§ + 1")];
+            assert!(vrbl_derived_order(&var_vec).unwrap_err().to_string().contains("not in the question"));
         }
 
+        #[test]
+        fn integer_rules_are_exact_for_large_values() {
+            let big = (1_i64 << 53) + 1;  // Odd, but rounds to an even f64.
+            assert!(ValueRule::Odd.allows_int(big));
+            assert!(!ValueRule::Even.allows_int(big));
+            let mut prms = test_int_var("big", big, big).params;
+            prms.num_rules = vec![ValueRule::Odd];
+            assert!(vrbl_is_allowed(&prms, &Integer(big)));
+            assert!(!vrbl_is_allowed(&prms, &Integer(big + 1)));
+            assert!(ValueRule::NotPerfectSquare.allows_int(3_037_000_499 * 3_037_000_499 + 1));
+            assert!(!ValueRule::NotPerfectSquare.allows_int(3_037_000_499 * 3_037_000_499));
+        }
     }
 
-    /// Read a list (in json format) from a file.  Returns a tuple (filename, List)
-    /// containing the file name that was read along with the reconstituted list.
-    pub fn list_read(typech: &str) -> Option<(String, List)> {  // Do you really need to return the filename?
+    /// FLTK dialogs for creating, editing, saving, and reading Variables.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::global::{glob_check_lastdirused, glob_update_lastdirused};
+        use crate::misc::dir_is_empty;
+        use crate::APP_FLTK;
+        use fltk::app;
+        use fltk::button::{Button, CheckButton, RadioLightButton};
+        use fltk::enums::{Color, FrameType};
+        use fltk::input::{FloatInput, Input, IntInput};
+        use fltk::menu::Choice;
+        use fltk::prelude::{ButtonExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::{frame::Frame, group::Group, window::Window};
+        use lib_file::{file_fltk::*, file_mngmnt::*};
+        use lib_myfltk::fltkutils::fltk_custom_message;
+        use crate::banks::bnk_find_variable_uses;
+        use crate::errors::err_join_messages;
+        use crate::CONFIG;
+        use fltk::dialog::choice2_default;
+        use lib_myfltk::input_fltk::input_string;
+        use rand::thread_rng;
+        use std::cell::RefCell;
+        use std::path::Path;
+        use std::rc::Rc;
+
+        /// Create a new variable.
+        ///
+        pub fn vrbl_create() {
+            let mut var1 = Variable::new();
 
-        // region Deal with the directory path
-        let lastdir = glob_check_lastdirused();
-        let usepath = file_pathonly(&lastdir, "Choose the folder where you save your List files." );
+            if !vrbl_parameters_input_box(&mut var1) {
+                return;  // The window was closed without submitting.
+            }
 
-        {  // Set LAST_DIR_USED to the new path.
-            let purepath: String = dir_normalize_path(&usepath);
-            *LAST_DIR_USED.lock().unwrap() = purepath.clone();
-        }
+            vrbl_setvalues(&mut var1);
 
-        // endregion
+            println!(
+                "\n W3 -- Back in vrbl_create():  The Variable struct now contains:  {:?} \n",
+                var1
+            );
 
-        //region Read the file & return the List struct
+            vrbl_save(&mut var1);
+        }
 
-        let readlist = loop {
+        /// Input a variable's parameters into the Variable struct.  The window
+        /// starts out showing the parameters already in `var1`, so it is used both
+        /// for new Variables and for editing saved ones.  Returns `false` if the
+        /// window was closed without submitting, leaving `var1` unchanged.
+        pub fn vrbl_parameters_input_box(var1: &mut Variable) -> bool {
+            // Note:  This is called from vrbl_create() & vrbl_edit().
 
-            if dir_is_empty(&usepath) {
-                fltk_custom_message("The directory you chose is empty.","Return to program.");
-                return None;
-            }
+            // todo: Grey out the input fields when the variable type is not "int" or "float".
+            //          Use the `deactivate()` method.  First attempt didn't work.
 
-            {  // Set LAST_DIR_USED to the new path.
-                let purepath: String = dir_normalize_path(&usepath);
-                *LAST_DIR_USED.lock().unwrap() = purepath.clone();
-            }
+            // region Set up the window.
+            let mut win = Window::new(900, 100, 600, 830, "Variable Parameters");
+            win.set_color(Color::Cyan);
+            win.make_resizable(true);
+            // endregion
 
-            let uselistname = file_fullpath_fltr(&usepath, "*.lst");
+            // region Create the radio buttons for the variable type.
+            let radio_group = Group::new(0, 0, 600, 50, None);
+
+            // Create horizontal radio light buttons across the top -- initial spacing.
+            let bttn_w = 100;
+            let bttn_h = 30;
+            let spacing = 12;
+            let types_xxx = 40;
+            let types_yyy = 20;
+
+            let mut strings_btn =
+                RadioLightButton::new(types_xxx, types_yyy, bttn_w, bttn_h, "Strings");
+            let mut chars_btn = RadioLightButton::new(
+                types_xxx + bttn_w + spacing,
+                types_yyy,
+                bttn_w,
+                bttn_h,
+                "Characters",
+            );
+            let mut ints_btn = RadioLightButton::new(
+                types_xxx + 2 * (bttn_w + spacing),
+                types_yyy,
+                bttn_w,
+                bttn_h,
+                "Integers",
+            );
+            let mut decimals_btn = RadioLightButton::new(
+                types_xxx + 3 * (bttn_w + spacing),
+                types_yyy,
+                bttn_w,
+                bttn_h,
+                "Decimals",
+            );
+            let mut fractions_btn = RadioLightButton::new(
+                types_xxx + 4 * (bttn_w + spacing),
+                types_yyy,
+                bttn_w,
+                bttn_h,
+                "Fractions",
+            );
 
-            match file_read_to_string(&uselistname) {
-                Ok(contents) => {
-                    let newlist = serde_json::from_str(&contents).unwrap();
-                    let typechk = list_check_typematch(&newlist, typech);
-                    if !typechk {
-                        continue;
-                    } else {
-                        break (uselistname, newlist);
-                    }
-                }
-                Err(err) => {
-                    eprintln!("\n Error reading the file: {} \n", err);
-                    fltk_custom_message("Could not read the file.","Return.");
-                    return None;
-                }
+            // Select the Variable's current type.  New Variables start as Strings.
+            match vrbl_list_typechoice(&var1.var_type) {
+                "chars" => chars_btn.set_value(true),
+                "ints" => ints_btn.set_value(true),
+                "floats" => decimals_btn.set_value(true),
+                "fractions" => fractions_btn.set_value(true),
+                _ => strings_btn.set_value(true),
             }
-        };
-        // endregion
 
-        Some(readlist)
-    }
+            radio_group.end();
+            // endregion
 
-    /// Edit an existing list.  Not yet implementd
-    ///
-    pub fn list_edit() {
-        println!("\n Someday I'll write this function to edit a list. \n");
-    }
+            // region Create "comma", "money", & "list" check boxes in row below the radio buttons.
+
+            // Calculate the position & size of the check boxes.
+            let ckbx_y = types_yyy + bttn_h + 20; // Position below radio buttons
+            let ckbx_w = 150;
+            let ckbx_h = 25;
+            let ckbx_spacing = 20;
+
+            let total_radio_width = bttn_w * 5 + spacing * 4; // Width of all radio buttons + spacing
+            let start_x = types_xxx + (total_radio_width - (ckbx_w * 3 + ckbx_spacing * 2)) / 2;
+
+            // Create the check boxes.  The separators & currency symbol are set for the whole Bank.
+            let usecommas = CheckButton::new(start_x, ckbx_y, ckbx_w, ckbx_h, "Group Digits (1,000)");
+            usecommas.set_checked(var1.params.num_comma_frmttd);
+            let usecurrency = CheckButton::new(start_x + ckbx_w + ckbx_spacing, ckbx_y, ckbx_w, ckbx_h, "Money ($)");
+            usecurrency.set_checked(var1.params.num_currency);
+            let fromlist = CheckButton::new(
+                start_x + 2 * (ckbx_w + ckbx_spacing),
+                ckbx_y,
+                ckbx_w,
+                ckbx_h,
+                "Value to come from a List",
+            );
+            fromlist.set_checked(var1.params.is_from_list);
 
-    /// Prepare a list for saving to a file.
-    ///
-    pub fn list_save(list: &List) -> String {
-        // Why does this return the path?  Is it necessary?
+            // endregion
 
-        // region Set up directories.
+            // region Set up frames -- for Integer & Decimal parameter entry.
+
+            // region Set up frame parameters
+            let frame_y = ckbx_y + ckbx_h + 20; // Position below checkboxes
+            let frame_w = 250;
+            let input_w = 100;
+            let input_h = 25;
+            let label_h = 20;
+            let field_spacing = 10;
+            let frame_spacing = 20;
+            let frame_h = 30 + (5 * (label_h + input_h + field_spacing)) + 15;
+            // endregion
 
-        let usedir = glob_check_lastdirused();
+            // region Create Integers frame & input fields
+            let mut int_frame = Group::new(types_xxx, frame_y, frame_w, frame_h, None);
+            let mut int_label = Frame::new(types_xxx, frame_y, frame_w, 30, "Integer Parameters");
+            int_label.set_label_size(14);
+
+            // Calculate centered position for input fields in integer frame
+            let int_input_x = types_xxx + (frame_w - input_w) / 2;
+            let int_first_y = frame_y + 35; // Start below the frame label
+
+            // Integer Minimum Value
+            let _intmin_label = Frame::new(int_input_x, int_first_y, input_w, label_h, "Minimum Value");
+            let mut intmin = IntInput::new(int_input_x, int_first_y + label_h, input_w, input_h, "");
+
+            // Integer Maximum Value
+            let _intmax_label = Frame::new(
+                int_input_x,
+                int_first_y + label_h + input_h + field_spacing,
+                input_w,
+                label_h,
+                "Maximum Value",
+            );
+            let mut intmax = IntInput::new(
+                int_input_x,
+                int_first_y + label_h + input_h + field_spacing + label_h,
+                input_w,
+                input_h,
+                "",
+            );
 
-        let filters = vec!["Lists", "*.lst", "Variables", "*.vrbl", "Banks", "*.bnk", "Text", "*.txt", "All Files", "*.*"];
-        let path = file_browse_tosave(&usedir, "", &filters);
+            // Integer Step -- blank allows every value in the range.
+            let _intstep_label = Frame::new(
+                int_input_x,
+                int_first_y + 2 * (label_h + input_h + field_spacing),
+                input_w,
+                label_h,
+                "Step (optional)",
+            );
+            let mut intstep = IntInput::new(
+                int_input_x,
+                int_first_y + 2 * (label_h + input_h + field_spacing) + label_h,
+                input_w,
+                input_h,
+                "",
+            );
 
-        // Store the current path in global variable LAST_DIR_USED.
-        let purepath: String = dir_normalize_path(&path);
-        {
-            *LAST_DIR_USED.lock().unwrap() = purepath.clone();
+            int_frame.set_frame(FrameType::DownBox); // Add frame border
+            int_frame.end();
+            // endregion
+
+            // region Create Decimals frame & input fields
+            let mut decimal_frame = Group::new(
+                types_xxx + frame_w + frame_spacing,
+                frame_y,
+                frame_w,
+                frame_h,
+                None,
+            );
+            let mut decimal_label = Frame::new(
+                types_xxx + frame_w + frame_spacing,
+                frame_y,
+                frame_w,
+                30,
+                "Decimal Parameters",
+            );
+            decimal_label.set_label_size(14);
+
+            // Calculate centered position for input fields in decimal frame
+            let dec_input_x = types_xxx + frame_w + frame_spacing + (frame_w - input_w) / 2;
+            let dec_first_y = frame_y + 35; // Start below the frame label
+
+            // Decimal Minimum Value
+            let _decmin_label = Frame::new(dec_input_x, dec_first_y, input_w, label_h, "Minimum Value");
+            let mut decmin = FloatInput::new(dec_input_x, dec_first_y + label_h, input_w, input_h, "");
+
+            // Decimal Maximum Value
+            let _decmax_label = Frame::new(
+                dec_input_x,
+                dec_first_y + label_h + input_h + field_spacing,
+                input_w,
+                label_h,
+                "Maximum Value",
+            );
+            let mut decmax = FloatInput::new(
+                dec_input_x,
+                dec_first_y + label_h + input_h + field_spacing + label_h,
+                input_w,
+                input_h,
+                "",
+            );
+
+            // Decimal Places
+            let _decplaces_label = Frame::new(
+                dec_input_x,
+                dec_first_y + 2 * (label_h + input_h + field_spacing),
+                input_w,
+                label_h,
+                "Decimal Places",
+            );
+            let mut decplaces = IntInput::new(
+                dec_input_x,
+                dec_first_y + 2 * (label_h + input_h + field_spacing) + label_h,
+                input_w,
+                input_h,
+                "",
+            );
+
+            // Decimal Step -- blank allows any value in the range.
+            let _decstep_label = Frame::new(
+                dec_input_x,
+                dec_first_y + 3 * (label_h + input_h + field_spacing),
+                input_w,
+                label_h,
+                "Step (optional)",
+            );
+            let mut decstep = FloatInput::new(
+                dec_input_x,
+                dec_first_y + 3 * (label_h + input_h + field_spacing) + label_h,
+                input_w,
+                input_h,
+                "",
+            );
+
+            // Significant Figures & Notation, side by side -- blank figures use the decimal places.
+            let decsig_x = types_xxx + frame_w + frame_spacing + 15;
+            let decnotation_x = types_xxx + 2 * frame_w + frame_spacing - 15 - input_w;
+            let decsig_y = dec_first_y + 4 * (label_h + input_h + field_spacing);
+            let _decsig_label = Frame::new(decsig_x, decsig_y, input_w, label_h, "Sig. Figures");
+            let mut decsig = IntInput::new(decsig_x, decsig_y + label_h, input_w, input_h, "");
+            decsig.set_tooltip("Round to this many significant figures instead of to the decimal places.");
+            let _decnotation_label = Frame::new(decnotation_x, decsig_y, input_w, label_h, "Notation");
+            let mut decnotation = Choice::new(decnotation_x, decsig_y + label_h, input_w, input_h, "");
+            for notation in Notation::ALL.iter() {
+                decnotation.add_choice(notation.name());
+            }
+            decnotation.set_value(Notation::ALL.iter().position(|notation| *notation == var1.params.num_notation).unwrap_or(0) as i32);
+
+            decimal_frame.set_frame(FrameType::DownBox); // Add frame border
+            decimal_frame.end();
+            // endregion
+
+            // region Fill in the current values when editing a numeric Variable.
+            if !var1.params.is_from_list {
+                match vrbl_list_typechoice(&var1.var_type) {
+                    "ints" => {
+                        intmin.set_value(&var1.params.num_min_int.to_string());
+                        intmax.set_value(&var1.params.num_max_int.to_string());
+                        if var1.params.num_step_int > 0 {
+                            intstep.set_value(&var1.params.num_step_int.to_string());
+                        }
+                    }
+                    "floats" => {
+                        decmin.set_value(&var1.params.num_min_float.to_string());
+                        decmax.set_value(&var1.params.num_max_float.to_string());
+                        decplaces.set_value(&var1.params.num_dcml_places.to_string());
+                        if var1.params.num_step_float > 0.0 {
+                            decstep.set_value(&var1.params.num_step_float.to_string());
+                        }
+                        if var1.params.num_sig_figs > 0 {
+                            decsig.set_value(&var1.params.num_sig_figs.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // endregion
+
+            // endregion
+
+            // region Create the rule check boxes & the excluded values input.
+            let rules_y = frame_y + frame_h + 15;
+            let rule_w = (frame_w * 2 + frame_spacing) / ValueRule::ALL.len() as i32;
+            let rule_btns: Vec<CheckButton> = ValueRule::ALL
+                .iter()
+                .enumerate()
+                .map(|(idx, rule)| {
+                    let label = if *rule == ValueRule::NotPerfectSquare { "Not a square" } else { rule.name() };
+                    let rulebtn = CheckButton::new(types_xxx + idx as i32 * rule_w, rules_y, rule_w, ckbx_h, label);
+                    rulebtn.set_checked(var1.params.num_rules.contains(rule));
+                    rulebtn
+                })
+                .collect();
+
+            let mut excluded = Input::new(types_xxx + 150, rules_y + 40, frame_w * 2 + frame_spacing - 150, input_h, "Excluded values:");
+            let excluded_txt: Vec<String> = var1.params.num_excluded.iter().map(|item| item.to_string()).collect();
+            excluded.set_value(&excluded_txt.join(", "));
+            // endregion
+
+            // region Create the formula input for derived Variables.
+            let mut formula = Input::new(types_xxx + 150, rules_y + 75, frame_w * 2 + frame_spacing - 150, input_h, "Formula (optional):");
+            formula.set_tooltip("Calculate the value from the question's other variables, e.g.  §a§ * §b§");
+            formula.set_value(&var1.params.derived_formula);
+
+            let mut unit = Input::new(types_xxx + 150, rules_y + 110, 150, input_h, "Unit (optional):");
+            unit.set_tooltip("The unit the value is measured in, e.g.  km,  min,  m/s^2,  or  kg*m^2");
+            unit.set_value(&var1.params.unit);
+            // endregion
+
+            // region Create the Fractions frame & input fields.
+            let frac_y = rules_y + 150;
+            let frac_w = frame_w * 2 + frame_spacing;
+            let frac_col_w = (frac_w - 20) / 4;
+            let mut frac_frame = Group::new(types_xxx, frac_y, frac_w, 150, None);
+            let mut frac_label = Frame::new(types_xxx, frac_y, frac_w, 30, "Fraction Parameters");
+            frac_label.set_label_size(14);
+
+            let frac_inputs: Vec<IntInput> = ["Numerator Min", "Numerator Max", "Denominator Min", "Denominator Max"]
+                .iter()
+                .enumerate()
+                .map(|(idx, label)| {
+                    let col_x = types_xxx + 10 + idx as i32 * frac_col_w + (frac_col_w - input_w) / 2;
+                    let _frac_label = Frame::new(col_x - 10, frac_y + 30, input_w + 20, label_h, *label);
+                    IntInput::new(col_x, frac_y + 30 + label_h, input_w, input_h, "")
+                })
+                .collect();
+
+            let mut fracdens = Input::new(types_xxx + 190, frac_y + 85, frac_w - 200, input_h, "Only these denominators:");
+            fracdens.set_tooltip("e.g.  2, 4, 8  -- leave blank to use the denominator range");
+            let fracproper = CheckButton::new(types_xxx + 10, frac_y + 118, 170, ckbx_h, "Proper fractions only");
+            let fracreduced = CheckButton::new(types_xxx + 180, frac_y + 118, 180, ckbx_h, "Reduce to lowest terms");
+            let mut fracformat = Choice::new(types_xxx + frac_w - 130, frac_y + 118, 120, ckbx_h, "Show as:");
+            for style in FracFormat::ALL.iter() {
+                fracformat.add_choice(style.name());
+            }
+            fracformat.set_value(FracFormat::ALL.iter().position(|style| *style == var1.params.frac_format).unwrap_or(0) as i32);
+
+            if vrbl_list_typechoice(&var1.var_type) == "fractions" {
+                let prms = &var1.params;
+                let frac_vals = [prms.frac_num_min, prms.frac_num_max, prms.frac_den_min, prms.frac_den_max];
+                for (mut fracinput, num) in frac_inputs.iter().cloned().zip(frac_vals) {
+                    fracinput.set_value(&num.to_string());
+                }
+                let dens_txt: Vec<String> = prms.frac_dens.iter().map(|item| item.to_string()).collect();
+                fracdens.set_value(&dens_txt.join(", "));
+                fracproper.set_checked(prms.frac_proper);
+                fracreduced.set_checked(prms.frac_reduced);
+            }
+
+            frac_frame.set_frame(FrameType::DownBox); // Add frame border
+            frac_frame.end();
+            // endregion
+
+            // region Create the Submit button
+            let submit_btn_w = 100;
+            let submit_btn_h = 40;
+
+            // Calculate center position based on the frames
+            let total_frames_width = frame_w * 2 + frame_spacing;
+            let submit_btn_x = types_xxx + (total_frames_width - submit_btn_w) / 2;
+            let submit_btn_y = frac_y + 165; // Below the rules, excluded values, formula, unit, & fractions
+
+            let mut submit_btn = Button::new(
+                submit_btn_x,
+                submit_btn_y,
+                submit_btn_w,
+                submit_btn_h,
+                "Submit",
+            );
+            // endregion
+
+            win.end();
+            win.show();
+
+            // region Clone variables for the callback
+            let strings_btn = strings_btn.clone();
+            let chars_btn = chars_btn.clone();
+            let ints_btn = ints_btn.clone();
+            let decimals_btn = decimals_btn.clone();
+            let fractions_btn = fractions_btn.clone();
+            let mut win_clone = win.clone();
+
+            let datavar = Rc::new(RefCell::new(var1.clone()));
+            let datavar_outside = datavar.clone(); // Create a second Rc pointing to the same RefCell
+            let submitted = Rc::new(RefCell::new(false));
+            let submitted_outside = submitted.clone();
+
+            // endregion
+
+            // region Do the callback for the Submit button
+
+            submit_btn.set_callback(move |_| {
+
+                // region Deal with the radio buttons.
+
+                let vartype = if strings_btn.value() {
+                    decmin.deactivate();  // todo: Grey out the input fields when the variable type is not "int" or "float".
+                    decmax.deactivate();
+                    decplaces.deactivate();
+                    decstep.deactivate();
+                    decsig.deactivate();
+                    intmin.deactivate();
+                    intmax.deactivate();
+                    intstep.deactivate();
+                    "Strings"
+
+                } else if chars_btn.value() {
+                    decmin.deactivate();
+                    decmax.deactivate();
+                    decplaces.deactivate();
+                    decstep.deactivate();
+                    decsig.deactivate();
+                    intmin.deactivate();
+                    intmax.deactivate();
+                    intstep.deactivate();
+                    "Characters"
+                } else if ints_btn.value() {
+                    decmin.deactivate();
+                    decmax.deactivate();
+                    decplaces.deactivate();
+                    decstep.deactivate();
+                    decsig.deactivate();
+                    intmin.activate();  // In case an earlier Submit was rejected.
+                    intmax.activate();
+                    intstep.activate();
+                    "Integers"
+                } else if decimals_btn.value() {
+                    intmin.deactivate();
+                    intmax.deactivate();
+                    intstep.deactivate();
+                    decmin.activate();
+                    decmax.activate();
+                    decplaces.activate();
+                    decstep.activate();
+                    decsig.activate();
+                    "Decimals"
+                } else if fractions_btn.value() {
+                    decmin.deactivate();
+                    decmax.deactivate();
+                    decplaces.deactivate();
+                    decstep.deactivate();
+                    decsig.deactivate();
+                    intmin.deactivate();
+                    intmax.deactivate();
+                    intstep.deactivate();
+                    "Fractions"
+                } else {
+                    "None"
+                };
+
+                {
+                    let mut datavar = datavar.borrow_mut();
+                    datavar.var_type = vartype.to_string();
+                    datavar.params.is_string = vartype == "Strings";
+                    datavar.params.is_char = vartype == "Characters";
+                    datavar.params.is_int = vartype == "Integers";
+                    datavar.params.is_float = vartype == "Decimals";
+                    datavar.params.is_fraction = vartype == "Fractions";
+                }
+                // endregion
+
+                //region Deal with the "comma", "money", & "list" check boxes.
+                if usecommas.is_checked() {
+                    datavar.borrow_mut().params.num_comma_frmttd = true;
+                    print!("\n Comma Formatted == true \n");
+                } else {
+                    datavar.borrow_mut().params.num_comma_frmttd = false;
+                    print!("\n Comma Formatted == false \n");
+                }
+                datavar.borrow_mut().params.num_currency = usecurrency.is_checked();
+
+                if fromlist.is_checked() {
+                    datavar.borrow_mut().params.is_from_list = true;
+                    print!("\n List == true \n");
+                } else {
+                    datavar.borrow_mut().params.is_from_list = false;
+                    print!("\nList == false \n");
+                }
+                // endregion
+
+                // region Deal with the formula.  Derived Variables don't need a range.
+                let derived_formula = formula.value().trim().to_string();
+                let is_derived = !derived_formula.is_empty();
+                let optional = |txt: String| if is_derived && txt.trim().is_empty() { "0".to_string() } else { txt };
+                datavar.borrow_mut().params.is_derived = is_derived;
+                datavar.borrow_mut().params.derived_formula = derived_formula;
+                datavar.borrow_mut().params.unit = unit.value().trim().to_string();
+                // endregion
+
+                // region Deal with the Integer input fields.
+                if vartype == "Integers" {
+                    let step = match intstep.value().trim() {
+                        "" => Ok(0),  // A blank step allows every value.
+                        steptxt => steptxt.parse::<i64>(),
+                    };
+                    let (Ok(min), Ok(max), Ok(step)) = (optional(intmin.value()).parse::<i64>(), optional(intmax.value()).parse::<i64>(), step) else {
+                        let err = QbcError::invalid("", "the minimum, maximum, and step must be whole numbers.");
+                        fltk_custom_message(&err.to_string(), "Return to the parameters.");
+                        return;  // Leave the window open so the values can be fixed.
+                    };
+                    datavar.borrow_mut().params.is_int = true;
+                    datavar.borrow_mut().params.is_float = false;
+                    datavar.borrow_mut().params.num_min_int = min;
+                    datavar.borrow_mut().params.num_max_int = max;
+                    datavar.borrow_mut().params.num_step_int = step;
+                }
+                // endregion
+
+                // region Deal with the Decimal input fields.
+                if vartype == "Decimals" {
+                    let step = match decstep.value().trim() {
+                        "" => Ok(0.0),  // A blank step allows any value.
+                        steptxt => steptxt.parse::<f64>(),
+                    };
+                    let sig_figs = match decsig.value().trim() {
+                        "" => Ok(0),  // Blank rounds to the decimal places.
+                        sigtxt => sigtxt.parse::<usize>(),
+                    };
+                    // The decimal places aren't needed when rounding to significant figures.
+                    let places = match (decplaces.value().trim(), &sig_figs) {
+                        ("", Ok(sig_figs)) if *sig_figs > 0 => Ok(0),
+                        (placestxt, _) => placestxt.parse::<usize>(),
+                    };
+                    let (Ok(min), Ok(max), Ok(places), Ok(step), Ok(sig_figs)) = (
+                        optional(decmin.value()).parse::<f64>(),
+                        optional(decmax.value()).parse::<f64>(),
+                        places,
+                        step,
+                        sig_figs,
+                    ) else {
+                        let err = QbcError::invalid("",
+                            "the minimum, maximum, and step must be numbers and the decimal places & significant figures whole numbers.");
+                        fltk_custom_message(&err.to_string(), "Return to the parameters.");
+                        return;  // Leave the window open so the values can be fixed.
+                    };
+                    datavar.borrow_mut().params.is_int = false;
+                    datavar.borrow_mut().params.is_float = true;
+                    datavar.borrow_mut().params.num_min_float = min;
+                    datavar.borrow_mut().params.num_max_float = max;
+                    datavar.borrow_mut().params.num_dcml_places = places;
+                    datavar.borrow_mut().params.num_step_float = step;
+                    datavar.borrow_mut().params.num_sig_figs = sig_figs;
+                    datavar.borrow_mut().params.num_notation = Notation::ALL[decnotation.value().max(0) as usize];
+                }
+                // endregion
+
+                // region Deal with the Fraction input fields.
+                if vartype == "Fractions" {
+                    let dens: Result<Vec<i64>, _> = fracdens.value()
+                        .split(|cc: char| cc == ',' || cc.is_whitespace())
+                        .filter(|item| !item.is_empty())
+                        .map(|item| item.parse::<i64>())
+                        .collect();
+                    // The denominator range isn't needed when the denominators are listed.
+                    let has_dens = matches!(&dens, Ok(dens) if !dens.is_empty());
+                    let nums: Result<Vec<i64>, _> = frac_inputs.iter()
+                        .enumerate()
+                        .map(|(idx, fracinput)| {
+                            let txt = fracinput.value();
+                            if idx >= 2 && has_dens && txt.trim().is_empty() { Ok(0) } else { optional(txt).parse::<i64>() }
+                        })
+                        .collect();
+                    let (Ok(nums), Ok(dens)) = (nums, dens) else {
+                        let err = QbcError::invalid("", "the numerators and denominators must be whole numbers.");
+                        fltk_custom_message(&err.to_string(), "Return to the parameters.");
+                        return;  // Leave the window open so the values can be fixed.
+                    };
+                    let mut datavar = datavar.borrow_mut();
+                    datavar.params.is_int = false;
+                    datavar.params.is_float = false;
+                    datavar.params.frac_num_min = nums[0];
+                    datavar.params.frac_num_max = nums[1];
+                    datavar.params.frac_den_min = nums[2];
+                    datavar.params.frac_den_max = nums[3];
+                    datavar.params.frac_dens = dens;
+                    datavar.params.frac_proper = fracproper.is_checked();
+                    datavar.params.frac_reduced = fracreduced.is_checked();
+                    datavar.params.frac_format = FracFormat::ALL[fracformat.value().max(0) as usize];
+                }
+                // endregion
+
+                // region Deal with the rules & excluded values.
+                let excluded: Result<Vec<f64>, _> = excluded.value()
+                    .split(|cc: char| cc == ',' || cc.is_whitespace())
+                    .filter(|item| !item.is_empty())
+                    .map(|item| match item.split_once('/') {
+                        // A fraction such as 1/2 is taken as the value it stands for.
+                        Some((num, den)) => Ok(num.parse::<i64>().map_err(|_| ())? as f64 / den.parse::<i64>().map_err(|_| ())? as f64),
+                        None => item.parse::<f64>().map_err(|_| ()),
+                    })
+                    .collect();
+                let Ok(excluded) = excluded else {
+                    let err = QbcError::invalid("", "the excluded values must be numbers separated by commas.");
+                    fltk_custom_message(&err.to_string(), "Return to the parameters.");
+                    return;  // Leave the window open so the values can be fixed.
+                };
+                datavar.borrow_mut().params.num_excluded = excluded;
+                datavar.borrow_mut().params.num_rules = ValueRule::ALL
+                    .iter()
+                    .zip(rule_btns.iter())
+                    .filter(|(_, rulebtn)| rulebtn.is_checked())
+                    .map(|(rule, _)| *rule)
+                    .collect();
+                // endregion
+
+                println!("\n In the callback, datavar == {:?} \n", datavar);
+
+                if let Err(err) = vrbl_validate(&datavar.borrow()) {
+                    fltk_custom_message(&err.to_string(), "Return to the parameters.");
+                    return;  // Leave the window open so the values can be fixed.
+                }
+
+                // Close the window
+                *submitted.borrow_mut() = true;
+                win_clone.hide();
+            });
+            // endregion
+
+            // Keep window active until hidden
+            while win.shown() {
+                app::wait();
+            }
+
+            if !*submitted_outside.borrow() {
+                return false;
+            }
+            *var1 = datavar_outside.borrow().clone();  // This works because the `datavar_outside` Rc
+            // is pointing to the same RefCell as the `datavar` Rc that is used in the callback.
+            true
+        }
+
+        /// Prepare a Variable for saving.
+        ///
+        pub fn vrbl_save(var1: &mut Variable) {
+            // Probably should return either an option or a Result here.
+
+            // region Create the file name to use in the dialog.
+
+            let app;
+            {
+                app = *APP_FLTK.lock().unwrap();
+            }
+
+            var1.fname = match var1.var_type.as_str() {
+                _ if var1.params.is_derived => {
+                    let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
+                    format!("calc.{}", preferred_fname)
+                },
+                "Strings" => {
+                    let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
+                    format!("str.{}", preferred_fname)
+                },
+                "Characters" => {
+                    let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
+                    format!("str.{}", preferred_fname)
+                },
+                "Integers" => {
+                    format!("int.{}..{}", var1.params.num_min_int, var1.params.num_max_int)
+                },
+                "Decimals" => {
+                    format!("float.{}_{}", var1.params.num_min_float, var1.params.num_max_float)
+                },
+                "Fractions" => {
+                    let preferred_fname = input_string(&app, "Please enter your preferred file name:", 300, 90);
+                    format!("frac.{}", preferred_fname)
+                },
+                _ => "default".to_string(),
+
+            };
+            // endregion
+
+            // region Create file path & update `LastDirUsed`.
+
+            let lastdir = glob_check_lastdirused("Variables");
+
+            let filters = vec!["Variables", "*.vrbl", "Banks", "*.bnk", "Lists", "*.lst", "Text", "*.txt", "All Files", "*.*"];
+            let usepath = file_browse_tosave(&lastdir, &var1.fname, &filters);
+            glob_update_lastdirused("Variables", &usepath);
+
+            var1.fname = file_path_to_fname(&usepath);
+
+            // endregion
+
+            match vrbl_save_as_json(var1, &usepath) {
+                Ok(()) => println!("\n The variable has been saved \n"),
+                Err(err) => {
+                    eprintln!("\n Error saving the variable: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return.");
+                }
+            }
+        }
+
+        /// Read a variable from a file.
+        ///
+        pub fn vrbl_read() -> Option<Variable> {
+
+            // region Deal with the directory path
+
+            let lastdir = glob_check_lastdirused("Variables");
+            let usepath = file_pathonly(&lastdir, "Choose the folder where you save your Variable files." );
+            glob_update_lastdirused("Variables", &usepath);
+
+            if dir_is_empty(&usepath) {
+                fltk_custom_message("The directory you chose is empty.","Return to program.");
+                return None;
+            }
+            let readpath = file_fullpath(&usepath, "Choose the Variable file you want to read.");  // The path to the file that is to be read.
+
+            // endregion
+
+            //region Read the file & return the Variable struct
+
+            let readpath = Path::new(&readpath);
+            let fname = readpath.file_name().unwrap_or_default().to_string_lossy();
+            let dirpath = readpath.parent().unwrap_or(Path::new("")).to_string_lossy();
+            match vrbl_read_with_pathfname(&dirpath, &fname) {
+                Ok(newvar) => Some(newvar),
+                Err(err) => {
+                    eprintln!("\n Error reading the variable: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return to the question editor.");
+                    None
+                }
+            }
+
+            // endregion
+        }
+
+        /// Sets and calculates the values of non-boolean fields in the Variable struct.
+        ///
+        pub fn vrbl_setvalues(var1: &mut Variable) {
+            if var1.params.is_from_list {  // If the variable content comes from a list.
+
+                let typech = vrbl_list_typechoice(&var1.var_type);
+                match list_read(typech) {
+                    Some((fname, newlist)) => {
+                        var1.uselist = fname; // Sets the value of the variable's listname field
+                        if let Err(err) = vrbl_choose_from_list(var1, &newlist, &[], &mut thread_rng()) {
+                            fltk_custom_message(&err.to_string(), "Return.");
+                        }
+                    }
+                    None => {
+                        eprintln!("No list file selected.");
+                        fltk_custom_message("No list file selected.","Return to the question editor.");
+                    }
+                }
+            } else if var1.params.is_derived {
+                // The value is calculated from the other Variables when its question is recalculated.
+            } else if let Err(err) = vrbl_gen_numeric(var1, &mut thread_rng()) {
+                fltk_custom_message(&err.to_string(), "Return.");
+            }
+        }
+
+        /// Recalculates the values in the non-boolean fields of a Variable struct.
+        ///
+        pub fn vrbl_recalc() {
+            if let Some(mut vrbl) = vrbl_read() {
+
+                println!("\n The variable before recalc is: \n {:?}", vrbl);
+
+                vrbl_setvalues(&mut vrbl);
+
+                println!("\n The variable after recalc is: \n {:?} \n", vrbl);
+
+                vrbl_save(&mut vrbl);
+            }
+
+            //let mut usevar = vrbl_read();
+
+            //vrbl_setvalues(&mut usevar);
+            //vrbl_save(&mut usevar);
+
+        }
+
+        /// Edit a saved Variable.  The Banks that use the Variable are listed
+        /// first so the user knows what the edit will affect.  The parameter
+        /// window starts out with the Variable's current settings, and the
+        /// changes are saved back to the same file.
+        pub fn vrbl_edit() {
+            let Some(mut var1) = vrbl_read() else {
+                return;
+            };
+            let libpath = var1.source_path.clone();
+            let oldtype = var1.var_type.clone();
+
+            fltk_custom_message(&vrbl_uses_report(&libpath), "Edit the Variable.");
+
+            if !vrbl_parameters_input_box(&mut var1) {
+                return;  // The window was closed without submitting.
+            }
+
+            // region Choose the List & calculate a new value.
+            let same_listtype = vrbl_list_typechoice(&var1.var_type) == vrbl_list_typechoice(&oldtype);
+            let keep_list = var1.params.is_from_list
+                && same_listtype
+                && list_read_with_pathfname(&var1.uselist).is_ok()
+                && choice2_default(&format!("Keep using the list {}?", var1.uselist), "Keep", "Choose another", "") == Some(0);
+
+            if !var1.params.is_from_list || !same_listtype {
+                var1.uselist.clear();
+            }
+            if keep_list {
+                if let Err(err) = vrbl_regen_content(&mut var1, &mut thread_rng()) {
+                    fltk_custom_message(&err.to_string(), "Return.");
+                }
+            } else {
+                vrbl_setvalues(&mut var1);
+            }
+
+            if var1.params.is_from_list && var1.uselist.is_empty() {
+                fltk_custom_message("No list was chosen, so the Variable was not changed.", "Return.");
+                return;
+            }
+            var1.list_data = None;  // Library Variables refer to their List by path.
+            // endregion
+
+            match vrbl_save_as_json(&var1, &libpath) {
+                Ok(()) => fltk_custom_message(
+                    "The Variable has been saved.  Banks that use it will get the changes \
+                    the next time you use Bank/Library/Sync from Library.",
+                    "OK",
+                ),
+                Err(err) => {
+                    eprintln!("\n Error saving the variable: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return.");
+                }
+            }
+        }
+
+        /// Describes which Banks & Questions use the library Variable saved
+        /// at `libpath`.  Both the Banks folder from the settings and the last
+        /// folder Banks were opened from are searched.
+        fn vrbl_uses_report(libpath: &str) -> String {
+            let mut bankdirs: Vec<String> = {
+                let config = CONFIG.lock().unwrap();
+                vec![config.kind_dir("Banks").to_string(), config.last_dir("Banks").to_string()]
+            };
+            bankdirs.dedup();
+
+            let mut lines: Vec<String> = Vec::new();
+            let mut failed: Vec<QbcError> = Vec::new();
+            for bankdir in bankdirs.iter().filter(|dir| !dir.is_empty()) {
+                let (uses, errs) = bnk_find_variable_uses(bankdir, libpath);
+                for (bankpath, qnums) in uses {
+                    let qnums: Vec<String> = qnums.iter().map(|num| num.to_string()).collect();
+                    lines.push(format!("{}:  question(s) {}", bankpath, qnums.join(", ")));
+                }
+                failed.extend(errs);
+            }
+
+            let mut report = if lines.is_empty() {
+                format!("No Banks in {} use this Variable.", bankdirs.join(" or "))
+            } else {
+                format!("This Variable is used by:\n{}", lines.join("\n"))
+            };
+            if !failed.is_empty() {
+                report.push_str(&format!("\n\nThese Banks could not be checked:\n{}", err_join_messages(&failed)));
+            }
+            report
+        }
+
+    } // End   gui   submodule
+
+} // End   variable   module
+
+/// Functions for creating and manipulating lists.
+///
+pub mod lists {
+
+    use crate::errors::QbcError;
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    #[cfg(feature = "gui")]
+    pub use gui::*;
+
+    // region Struct section
+
+    /// Contains a vector field for each of the four data types
+    /// allowed in lists associated with QBC.
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct List {
+        pub words: Vec<String>,
+        pub runes: Vec<char>,
+        pub intsigned: Vec<i64>,
+        pub decimals: Vec<f64>,
+        pub typechoice: String, // "Strings", "chars", "ints", "floats"
+        #[serde(default)]
+        pub weights: Vec<f64>,  // How often each entry is drawn, relative to the others.  Empty weighs them all equally.
+        #[serde(default)]
+        pub attributes: Vec<EntryAttrs>,  // Grammar for each entry, e.g. the pronouns of a name.  Empty if none are set.
+    }
+
+    /// How an entry in a list of words is used in a sentence.  Blank fields
+    /// fall back to the defaults described on `list_entry_form()`.
+    #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+    pub struct EntryAttrs {
+        #[serde(default)]
+        pub pronouns: String,  // One of LIST_PRONOUN_SETS, e.g. "she"
+        #[serde(default)]
+        pub plural: String,  // e.g. "mice" for "mouse"
+        #[serde(default)]
+        pub article: String,  // "a" or "an", e.g. "an" for "hour"
+    }
+
+    impl Default for List {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl List {
+        /// Initializes a new list struct.
+        pub fn new() -> List {
+            Self {
+                words: Vec::new(),
+                runes: Vec::new(),
+                intsigned: Vec::new(),
+                decimals: Vec::new(),
+                typechoice: "Strings".to_string(),
+                weights: Vec::new(),
+                attributes: Vec::new(),
+            }
+        }
+    } // ----------  End List impl ----------
+
+    // endregion
+
+    /// Read a list from the file at the given path without prompting the user.
+    /// Used when recalculating Variables whose list file is already known.
+    pub fn list_read_with_pathfname(readpath: &str) -> Result<List, QbcError> {
+        // Note:  The readpath parameter is the full path stored in `Variable::uselist`.
+
+        let contents = fs::read_to_string(readpath).map_err(|err| QbcError::io(readpath, err))?;
+        let uselist: List = serde_json::from_str(&contents).map_err(|err| QbcError::json(readpath, err))?;
+        if !uselist.weights.is_empty() {
+            list_check_weights(&uselist, &uselist.weights)?;  // The file may have been edited by hand.
+        }
+        Ok(uselist)
+    }
+
+    /// Save a list in json format.
+    ///
+    pub fn list_save_as_json(list: &List, fname: &str) -> Result<(), QbcError> {
+        let list_as_json = serde_json::to_string(list).map_err(|err| QbcError::json(fname, err))?;
+        fs::write(fname, list_as_json).map_err(|err| QbcError::io(fname, err))
+    }
+
+    /// Check that a list contains the correct type of data.  The `listname`
+    /// is only used in the error message.
+    pub fn list_check_typematch(uselist: &List, typech: &str, listname: &str) -> Result<(), QbcError> {
+        if uselist.typechoice.as_str() != typech {
+            Err(QbcError::ListTypeMismatch {
+                list: listname.to_string(),
+                expected: typech.to_string(),
+                found: uselist.typechoice.clone(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    // region Entry editing
+
+    /// Returns the entries of a list as text, using whichever vector
+    /// matches the list's `typechoice`.
+    pub fn list_entries(uselist: &List) -> Vec<String> {
+        match uselist.typechoice.as_str() {
+            "chars" => uselist.runes.iter().map(|item| item.to_string()).collect(),
+            "ints" => uselist.intsigned.iter().map(|item| item.to_string()).collect(),
+            "floats" => uselist.decimals.iter().map(|item| item.to_string()).collect(),
+            _ => uselist.words.clone(),
+        }
+    }
+
+    /// Checks that a single entry can be stored in a list of the given
+    /// type.  Returns the entry as it will be stored, with surrounding
+    /// whitespace removed from numbers & characters.
+    pub fn list_parse_entry(entry: &str, typech: &str) -> Result<String, QbcError> {
+        let bad_entry = || QbcError::InvalidListEntry { entry: entry.to_string(), typechoice: typech.to_string() };
+
+        match typech {
+            "chars" => {
+                let mut chars = entry.trim().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(item), None) => Ok(item.to_string()),
+                    _ => Err(bad_entry()),
+                }
+            }
+            "ints" => entry.trim().parse::<i64>().map(|item| item.to_string()).map_err(|_| bad_entry()),
+            "floats" => match entry.trim().parse::<f64>() {
+                Ok(item) if item.is_finite() => Ok(item.to_string()),
+                _ => Err(bad_entry()),
+            },
+            _ if entry.trim().is_empty() => Err(bad_entry()),
+            _ => Ok(entry.to_string()),
+        }
+    }
+
+    /// Replaces the entries of a list with the given text entries, parsed
+    /// according to the list's `typechoice`.  The list is left unchanged
+    /// if any entry is not valid.  Weights & attributes are not changed;  see
+    /// `list_set_weights()` & `list_set_attributes()`.
+    pub fn list_set_entries(uselist: &mut List, entries: &[String]) -> Result<(), QbcError> {
+        let typech = uselist.typechoice.clone();
+        let parsed = entries
+            .iter()
+            .map(|entry| list_parse_entry(entry, &typech))
+            .collect::<Result<Vec<String>, QbcError>>()?;
+
+        // The entries were checked above, so the parses below can't fail.
+        match typech.as_str() {
+            "chars" => uselist.runes = parsed.iter().filter_map(|item| item.chars().next()).collect(),
+            "ints" => uselist.intsigned = parsed.iter().filter_map(|item| item.parse().ok()).collect(),
+            "floats" => uselist.decimals = parsed.iter().filter_map(|item| item.parse().ok()).collect(),
+            _ => uselist.words = parsed,
+        }
+        Ok(())
+    }
+
+    /// Sorts the entries of a list.  Numbers are sorted by value and
+    /// strings alphabetically without regard to case.  Each entry keeps its weight.
+    pub fn list_sort(uselist: &mut List) {
+        let mut order: Vec<usize> = (0..list_len(uselist)).collect();
+        match uselist.typechoice.as_str() {
+            "chars" => order.sort_by_key(|idx| uselist.runes[*idx]),
+            "ints" => order.sort_by_key(|idx| uselist.intsigned[*idx]),
+            "floats" => order.sort_by(|aaa, bbb| uselist.decimals[*aaa].total_cmp(&uselist.decimals[*bbb])),
+            _ => order.sort_by_key(|idx| uselist.words[*idx].to_lowercase()),
+        }
+        list_keep_order(uselist, &order);
+    }
+
+    /// Removes repeated entries from a list, keeping the first occurrence
+    /// of each, along with its weight.  Returns the number of entries removed.
+    pub fn list_dedup(uselist: &mut List) -> usize {
+        fn first_of_each<T: PartialEq>(items: &[T]) -> Vec<usize> {
+            (0..items.len()).filter(|idx| !items[..*idx].contains(&items[*idx])).collect()
+        }
+
+        let before = list_len(uselist);
+        let kept = match uselist.typechoice.as_str() {
+            "chars" => first_of_each(&uselist.runes),
+            "ints" => first_of_each(&uselist.intsigned),
+            "floats" => first_of_each(&uselist.decimals),
+            _ => first_of_each(&uselist.words),
+        };
+        list_keep_order(uselist, &kept);
+        before - kept.len()
+    }
+
+    /// Rebuilds a list from the entries at `order`, in that order, moving
+    /// their weights with them.
+    fn list_keep_order(uselist: &mut List, order: &[usize]) {
+        fn pick<T: Clone>(items: &[T], order: &[usize]) -> Vec<T> {
+            order.iter().map(|idx| items[*idx].clone()).collect()
+        }
+
+        // Weights & attributes are padded to the old length before the entries change.
+        if !uselist.weights.is_empty() {
+            uselist.weights = pick(&list_weights(uselist), order);
+        }
+        if !uselist.attributes.is_empty() {
+            uselist.attributes = pick(&list_attributes(uselist), order);
+        }
+        match uselist.typechoice.as_str() {
+            "chars" => uselist.runes = pick(&uselist.runes, order),
+            "ints" => uselist.intsigned = pick(&uselist.intsigned, order),
+            "floats" => uselist.decimals = pick(&uselist.decimals, order),
+            _ => uselist.words = pick(&uselist.words, order),
+        }
+    }
+
+    /// Splits pasted text into separate entries.  Each line is an entry;
+    /// for lists of numbers or characters, commas & spaces also separate
+    /// entries.  Blank entries are dropped.
+    pub fn list_split_bulk(text: &str, typech: &str) -> Vec<String> {
+        match typech {
+            "chars" | "ints" | "floats" => text
+                .split(|cc: char| cc == ',' || cc.is_whitespace())
+                .filter(|item| !item.is_empty())
+                .map(|item| item.to_string())
+                .collect(),
+            _ => text
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect(),
+        }
+    }
+
+    // endregion
+
+    // region Weights
+
+    /// Returns the number of entries in a list.
+    ///
+    pub fn list_len(uselist: &List) -> usize {
+        match uselist.typechoice.as_str() {
+            "chars" => uselist.runes.len(),
+            "ints" => uselist.intsigned.len(),
+            "floats" => uselist.decimals.len(),
+            _ => uselist.words.len(),
+        }
+    }
+
+    /// Returns the weight of every entry in a list.  Entries without a
+    /// weight, e.g. in lists saved before weights were added, weigh 1.
+    pub fn list_weights(uselist: &List) -> Vec<f64> {
+        (0..list_len(uselist)).map(|idx| uselist.weights.get(idx).copied().unwrap_or(1.0)).collect()
+    }
+
+    /// Sets the weight of every entry in a list.  A weight of 0 means the
+    /// entry is never drawn.  Fails, leaving the list unchanged, if a weight
+    /// is negative or there isn't one for each entry.
+    pub fn list_set_weights(uselist: &mut List, weights: &[f64]) -> Result<(), QbcError> {
+        list_check_weights(uselist, weights)?;
+        // Equal weights are left out, so the file stays as it was before weights existed.
+        uselist.weights = if weights.iter().all(|weight| *weight == 1.0) { Vec::new() } else { weights.to_vec() };
+        Ok(())
+    }
+
+    /// Checks that there is a weight for each entry of a list and that every
+    /// weight is a finite number no smaller than 0.
+    fn list_check_weights(uselist: &List, weights: &[f64]) -> Result<(), QbcError> {
+        if let Some(bad) = weights.iter().find(|weight| !weight.is_finite() || **weight < 0.0) {
+            return Err(QbcError::InvalidListEntry { entry: bad.to_string(), typechoice: "weights".to_string() });
+        }
+        if weights.len() != list_len(uselist) {
+            let entry = format!("{} weights for {} entries", weights.len(), list_len(uselist));
+            return Err(QbcError::InvalidListEntry { entry, typechoice: "weights".to_string() });
+        }
+        Ok(())
+    }
+
+    // endregion
+
+    // region Entry attributes
+
+    /// The pronoun sets an entry can use.  Each row gives the subject, object,
+    /// possessive, possessive pronoun, & reflexive forms and then the forms of
+    /// "to be" (present & past) and "to have" that agree with them.
+    pub const LIST_PRONOUN_SETS: [[&str; 8]; 4] = [
+        ["they", "them", "their", "theirs", "themselves", "are", "were", "have"],
+        ["he", "him", "his", "his", "himself", "is", "was", "has"],
+        ["she", "her", "her", "hers", "herself", "is", "was", "has"],
+        ["it", "it", "its", "its", "itself", "is", "was", "has"],
+    ];
+
+    /// Returns the attributes of every entry in a list.  Entries without
+    /// any get the default, blank attributes.
+    pub fn list_attributes(uselist: &List) -> Vec<EntryAttrs> {
+        (0..list_len(uselist)).map(|idx| uselist.attributes.get(idx).cloned().unwrap_or_default()).collect()
+    }
+
+    /// Sets the attributes of every entry in a list.  Fails, leaving the
+    /// list unchanged, if a pronoun set is unknown, an article isn't "a"
+    /// or "an", or there isn't one set of attributes for each entry.
+    pub fn list_set_attributes(uselist: &mut List, attributes: &[EntryAttrs]) -> Result<(), QbcError> {
+        let bad_attrs = |entry: String| QbcError::InvalidListEntry { entry, typechoice: "attributes".to_string() };
+        for attrs in attributes.iter() {
+            if !attrs.pronouns.is_empty() && !LIST_PRONOUN_SETS.iter().any(|set| set[0] == attrs.pronouns) {
+                return Err(bad_attrs(attrs.pronouns.clone()));
+            }
+            if !matches!(attrs.article.as_str(), "" | "a" | "an") {
+                return Err(bad_attrs(attrs.article.clone()));
+            }
+        }
+        if attributes.len() != list_len(uselist) {
+            return Err(bad_attrs(format!("{} attributes for {} entries", attributes.len(), list_len(uselist))));
+        }
+        let all_blank = attributes.iter().all(|attrs| *attrs == EntryAttrs::default());
+        uselist.attributes = if all_blank { Vec::new() } else { attributes.to_vec() };
+        Ok(())
+    }
+
+    /// Returns an entry written in the given grammatical form, or `None` if
+    /// the form isn't known.  The forms are:
+    ///
+    /// - a pronoun or verb from the "they" row of LIST_PRONOUN_SETS, e.g.
+    ///   `they`, `their`, or `were`, giving the matching word from the entry's
+    ///   pronoun set.  Entries without a set use "they".
+    /// - `plural`, giving the entry's plural.  Without one, an "s" or "es" is
+    ///   added, or a final consonant-y becomes "ies".
+    /// - `a`, giving the entry with "a" or "an" in front.  Without an article,
+    ///   "an" is used before a vowel.
+    ///
+    /// A form written with a capital, e.g. `They`, gives a capitalized word.
+    pub fn list_entry_form(entry: &str, attrs: &EntryAttrs, form: &str) -> Option<String> {
+        let lowform = form.to_lowercase();
+        let word = if let Some(col) = LIST_PRONOUN_SETS[0].iter().position(|word| *word == lowform) {
+            let set = LIST_PRONOUN_SETS.iter().find(|set| set[0] == attrs.pronouns).unwrap_or(&LIST_PRONOUN_SETS[0]);
+            set[col].to_string()
+        } else if lowform == "plural" {
+            if attrs.plural.is_empty() { list_default_plural(entry) } else { attrs.plural.clone() }
+        } else if lowform == "a" {
+            let article = match attrs.article.as_str() {
+                "" if entry.starts_with(|cc: char| "aeiouAEIOU".contains(cc)) => "an",
+                "" => "a",
+                article => article,
+            };
+            format!("{} {}", article, entry)
+        } else {
+            return None;
+        };
+
+        // Only the first letter of the form matters, so  They  &  A  capitalize.
+        match form.chars().next() {
+            Some(first) if first.is_uppercase() => {
+                let mut chars = word.chars();
+                Some(chars.next().map(|cc| cc.to_uppercase().collect::<String>() + chars.as_str()).unwrap_or_default())
+            }
+            _ => Some(word),
+        }
+    }
+
+    /// Makes the regular English plural of a word.
+    ///
+    fn list_default_plural(entry: &str) -> String {
+        let lower = entry.to_lowercase();
+        if ["s", "x", "z", "ch", "sh"].iter().any(|end| lower.ends_with(end)) {
+            format!("{}es", entry)
+        } else if lower.ends_with('y') && !lower.trim_end_matches('y').ends_with(['a', 'e', 'i', 'o', 'u']) {
+            format!("{}ies", &entry[..entry.len() - 1])
+        } else {
+            format!("{}s", entry)
+        }
+    }
+
+    // endregion
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn entry_forms_follow_attributes() {
+            let she = EntryAttrs { pronouns: "she".to_string(), ..Default::default() };
+            assert_eq!(list_entry_form("Ann", &she, "They"), Some("She".to_string()));
+            assert_eq!(list_entry_form("Ann", &she, "were"), Some("was".to_string()));
+            assert_eq!(list_entry_form("Bo", &EntryAttrs::default(), "their"), Some("their".to_string()));
+            let mice = EntryAttrs { plural: "mice".to_string(), ..Default::default() };
+            assert_eq!(list_entry_form("mouse", &mice, "plural"), Some("mice".to_string()));
+            assert_eq!(list_entry_form("city", &EntryAttrs::default(), "plural"), Some("cities".to_string()));
+            assert_eq!(list_entry_form("apple", &EntryAttrs::default(), "a"), Some("an apple".to_string()));
+            assert_eq!(list_entry_form("apple", &EntryAttrs::default(), "bogus"), None);
+        }
+
+        #[test]
+        fn bad_weights_are_rejected() {
+            let mut uselist = List::new();
+            uselist.typechoice = "ints".to_string();
+            uselist.intsigned = vec![1, 2];
+            assert!(list_set_weights(&mut uselist, &[1.0, -1.0]).is_err());
+            assert!(list_set_weights(&mut uselist, &[1.0]).is_err());
+            assert!(list_set_weights(&mut uselist, &[1.0, 3.0]).is_ok());
+
+            // A hand-edited file with a negative weight is rejected when it is read.
+            let usepath = std::env::temp_dir().join(format!("qbc_weights_{}.lst", std::process::id()));
+            let usepath = usepath.to_string_lossy().to_string();
+            let json = serde_json::to_string(&uselist).unwrap().replace("3.0", "-3.0");
+            std::fs::write(&usepath, json).unwrap();
+            let readback = list_read_with_pathfname(&usepath);
+            let _ = std::fs::remove_file(&usepath);
+            assert!(matches!(readback, Err(QbcError::InvalidListEntry { .. })));
+        }
+
+        fn word_list(words: &[&str]) -> List {
+            let mut uselist = List::new();
+            uselist.typechoice = "Strings".to_string();
+            uselist.words = words.iter().map(|word| word.to_string()).collect();
+            uselist
+        }
+
+        #[test]
+        fn sort_keeps_weights_and_attributes() {
+            let mut uselist = word_list(&["carol", "Ann", "bo"]);
+            list_set_weights(&mut uselist, &[3.0, 1.0, 2.0]).unwrap();
+            let she = EntryAttrs { pronouns: "she".to_string(), ..Default::default() };
+            list_set_attributes(&mut uselist, &[she.clone(), EntryAttrs::default(), EntryAttrs::default()]).unwrap();
+            list_sort(&mut uselist);
+            assert_eq!(uselist.words, vec!["Ann", "bo", "carol"]);
+            assert_eq!(list_weights(&uselist), vec![1.0, 2.0, 3.0]);
+            assert_eq!(list_attributes(&uselist)[2], she);
+
+            let mut uselist = List::new();
+            uselist.typechoice = "floats".to_string();
+            uselist.decimals = vec![2.5, -1.0, 10.0];
+            list_set_weights(&mut uselist, &[0.0, 1.0, 5.0]).unwrap();
+            list_sort(&mut uselist);
+            assert_eq!(uselist.decimals, vec![-1.0, 2.5, 10.0]);
+            assert_eq!(list_weights(&uselist), vec![1.0, 0.0, 5.0]);
+        }
+
+        #[test]
+        fn dedup_keeps_the_first_of_each() {
+            let mut uselist = List::new();
+            uselist.typechoice = "ints".to_string();
+            uselist.intsigned = vec![4, 7, 4, 4, 9, 7];
+            list_set_weights(&mut uselist, &[2.0, 1.0, 5.0, 5.0, 1.0, 3.0]).unwrap();
+            assert_eq!(list_dedup(&mut uselist), 3);
+            assert_eq!(uselist.intsigned, vec![4, 7, 9]);
+            assert_eq!(list_weights(&uselist), vec![2.0, 1.0, 1.0]);
+            assert_eq!(list_dedup(&mut uselist), 0);
+
+            // Strings that differ only in case are different entries.
+            let mut uselist = word_list(&["Ann", "ann", "Ann"]);
+            assert_eq!(list_dedup(&mut uselist), 1);
+            assert_eq!(uselist.words, vec!["Ann", "ann"]);
+        }
+
+        #[test]
+        fn bulk_text_splits_by_list_type() {
+            let text = "New York, NY\n  Paris \n\nRome";
+            assert_eq!(list_split_bulk(text, "Strings"), vec!["New York, NY", "Paris", "Rome"]);
+            assert_eq!(list_split_bulk("1, 2 3\n-4,,5", "ints"), vec!["1", "2", "3", "-4", "5"]);
+            assert_eq!(list_split_bulk("1.5,2e3", "floats"), vec!["1.5", "2e3"]);
+            assert_eq!(list_split_bulk("a b,c", "chars"), vec!["a", "b", "c"]);
+            assert!(list_split_bulk(" \n , ", "ints").is_empty());
+        }
+
+        #[test]
+        fn invalid_entries_leave_the_list_unchanged() {
+            let mut uselist = List::new();
+            uselist.typechoice = "ints".to_string();
+            uselist.intsigned = vec![1, 2];
+            let entries = vec![" 5".to_string(), "six".to_string()];
+            assert!(matches!(list_set_entries(&mut uselist, &entries), Err(QbcError::InvalidListEntry { .. })));
+            assert_eq!(uselist.intsigned, vec![1, 2]);
+            list_set_entries(&mut uselist, &[" 5".to_string(), "-6".to_string(), "7".to_string()]).unwrap();
+            assert_eq!(uselist.intsigned, vec![5, -6, 7]);
+
+            uselist.typechoice = "chars".to_string();
+            assert!(list_set_entries(&mut uselist, &["a".to_string(), "bc".to_string()]).is_err());
+            assert!(uselist.runes.is_empty());
+            uselist.typechoice = "floats".to_string();
+            assert!(list_set_entries(&mut uselist, &["1.5".to_string(), "inf".to_string()]).is_err());
+            assert!(uselist.decimals.is_empty());
+            let mut uselist = word_list(&["Ann"]);
+            assert!(list_set_entries(&mut uselist, &["Bo".to_string(), "  ".to_string()]).is_err());
+            assert_eq!(uselist.words, vec!["Ann"]);
+        }
+    }
+
+    /// FLTK dialogs for creating, reading, editing, and saving Lists.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::global::{glob_check_lastdirused, glob_update_lastdirused};
+        use crate::misc::dir_is_empty;
+        use crate::APP_FLTK;
+        use lib_file::file_fltk::{file_browse_tosave, file_fullpath_fltr, file_pathonly};
+        use crate::errors::err_join_messages;
+        use fltk::app;
+        use fltk::browser::HoldBrowser;
+        use fltk::button::Button;
+        use fltk::input::{Input, MultilineInput};
+        use fltk::menu::Choice;
+        use fltk::prelude::{BrowserExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::{frame::Frame, window::Window};
+        use lib_myfltk::fltkutils::fltk_custom_message;
+        use lib_myfltk::input_fltk::*;
+        use std::cell::RefCell;
+        use std::path::Path;
+        use std::rc::Rc;
+
+        /// Create a new list.
+        /// Note that the function saves the list, but does not return it.
+        pub fn list_create(typech: &str) {
+            let mut newlist = List::new();
+            newlist.typechoice = typech.to_string();
+
+            let app;
+            {
+                app = APP_FLTK.lock().unwrap();
+            }
+
+            match typech {
+                "String" | "Strings" => {
+                    // String
+                    let uselist = input_strvec(&app, "Please enter a string.", 790, 300);
+                    newlist.words = uselist.clone();
+                    list_save(&newlist);
+                }
+
+                "char" | "chars" => {
+                    // char
+                    let uselist = input_charvec(&app, "Please enter a character.");
+                    newlist.runes = uselist.clone();
+                    list_save(&newlist);
+                }
+
+                "int" | "ints" => {
+                    // i64
+                    let uselist = input_i64vec(&app, "Please enter an integer.");
+                    newlist.intsigned = uselist.clone();
+                    list_save(&newlist);
+                }
+
+                "float" | "floats" => {
+                    // f64
+
+                    let uselist = input_f64vec(&app, "Please enter a floating point number.");
+                    newlist.decimals = uselist.clone();
+                    list_save(&newlist);
+                }
+
+                _ => {
+                    fltk_custom_message("Invalid list type.","Return");
+                    // So, then what happens in the calling function?  Probably
+                    // need to return an option here.
+                }
+            }
+
+        }
+
+        /// Read a list (in json format) from a file.  Returns a tuple (filename, List)
+        /// containing the file name that was read along with the reconstituted list.
+        pub fn list_read(typech: &str) -> Option<(String, List)> {  // Do you really need to return the filename?
+
+            // region Deal with the directory path
+            let lastdir = glob_check_lastdirused("Lists");
+            let usepath = file_pathonly(&lastdir, "Choose the folder where you save your List files." );
+            glob_update_lastdirused("Lists", &usepath);
+
+            // endregion
+
+            //region Read the file & return the List struct
+
+            let readlist = loop {
+
+                if dir_is_empty(&usepath) {
+                    fltk_custom_message("The directory you chose is empty.","Return to program.");
+                    return None;
+                }
+
+                let uselistname = file_fullpath_fltr(&usepath, "*.lst");
+
+                match list_read_with_pathfname(&uselistname) {
+                    Ok(newlist) => {
+                        match list_check_typematch(&newlist, typech, &uselistname) {
+                            Ok(()) => break (uselistname, newlist),
+                            Err(err) => {  // Let the user choose a different list file.
+                                fltk_custom_message(&err.to_string(), "Choose a different list.");
+                                continue;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("\n Error reading the list: {} \n", err);
+                        fltk_custom_message(&err.to_string(), "Return.");
+                        return None;
+                    }
+                }
+            };
+            // endregion
+
+            Some(readlist)
+        }
+
+        /// Edit an existing list.  The entries are shown in a table where they
+        /// can be added, removed, reordered, sorted, de-duplicated, or pasted in
+        /// bulk.  Nothing is written until `Save` is pressed, which saves the
+        /// list back to the file it was read from.
+        pub fn list_edit() {
+
+            // region Choose & read the list file
+            let lastdir = glob_check_lastdirused("Lists");
+            let usepath = file_pathonly(&lastdir, "Choose the folder where you save your List files.");
+            glob_update_lastdirused("Lists", &usepath);
+
+            if dir_is_empty(&usepath) {
+                fltk_custom_message("The directory you chose is empty.", "Return to program.");
+                return;
+            }
+
+            let listpath = file_fullpath_fltr(&usepath, "*.lst");
+            let uselist = match list_read_with_pathfname(&listpath) {
+                Ok(uselist) => uselist,
+                Err(err) => {
+                    eprintln!("\n Error reading the list: {} \n", err);
+                    fltk_custom_message(&err.to_string(), "Return.");
+                    return;
+                }
+            };
+            let typech = uselist.typechoice.clone();
+            let entries = Rc::new(RefCell::new(list_entries(&uselist)));
+            let weights = Rc::new(RefCell::new(list_weights(&uselist)));  // One for each entry.
+            let attributes = Rc::new(RefCell::new(list_attributes(&uselist)));  // One for each entry.
+            // endregion
+
+            // region Build the editor window
+            let fname = Path::new(&listpath).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            let mut win = Window::default().with_size(640, 640).with_label("Edit List");
+            win.make_modal(true);
+
+            let mut title = Frame::new(20, 10, 600, 30, "");
+            title.set_label(&format!("{}  ({})", fname, typech));
+
+            let mut brow = HoldBrowser::new(20, 50, 420, 420, "");
+            brow.set_column_char('\t');
+            brow.set_column_widths(&[50, 60, 60, 250]);
+            list_edit_refresh(&mut brow, &entries.borrow(), &weights.borrow(), &attributes.borrow(), 0);
+
+            let mut removebtn = Button::new(460, 50, 160, 40, "Remove");
+            let mut upbtn = Button::new(460, 100, 160, 40, "Move Up");
+            let mut downbtn = Button::new(460, 150, 160, 40, "Move Down");
+            let mut sortbtn = Button::new(460, 220, 160, 40, "Sort");
+            let mut dedupbtn = Button::new(460, 270, 160, 40, "Remove Repeats");
+            let mut pastebtn = Button::new(460, 340, 160, 40, "Paste Entries...");
+
+            let mut weightinput = Input::new(540, 400, 80, 35, "Weight:");
+            weightinput.set_tooltip("How often the selected entry is drawn, relative to the others.  0 means never.");
+            let mut weightbtn = Button::new(460, 440, 160, 35, "Set Weight");
+            let mut attrbtn = Button::new(460, 490, 160, 35, "Grammar...");
+            attrbtn.set_tooltip("Set the pronouns, plural, & article of the selected entry.");
+            if typech != "Strings" {
+                attrbtn.deactivate();
+            }
+
+            let newentry = Input::new(20, 490, 300, 35, "");
+            let mut addbtn = Button::new(330, 490, 110, 35, "Add");
+
+            let mut savebtn = Button::new(180, 570, 120, 40, "Save");
+            let mut cancelbtn = Button::new(340, 570, 120, 40, "Cancel");
+
+            win.end();
+            win.show();
+            // endregion
+
+            // region Callbacks
+            {   // Add a single entry after the selected one, or at the end.
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                let mut newentry = newentry.clone();
+                let typech = typech.clone();
+                addbtn.set_callback(move |_| {
+                    match list_parse_entry(&newentry.value(), &typech) {
+                        Ok(item) => {
+                            let mut entries = entries.borrow_mut();
+                            let mut weights = weights.borrow_mut();
+                            let mut attributes = attributes.borrow_mut();
+                            let idx = list_edit_selected(&brow).map(|idx| idx + 1).unwrap_or(entries.len());
+                            entries.insert(idx, item);
+                            weights.insert(idx, 1.0);
+                            attributes.insert(idx, EntryAttrs::default());
+                            list_edit_refresh(&mut brow, &entries, &weights, &attributes, idx as i32 + 1);
+                            newentry.set_value("");
+                        }
+                        Err(err) => fltk_custom_message(&err.to_string(), "Return to the list."),
+                    }
+                });
+            }
+
+            {
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                removebtn.set_callback(move |_| {
+                    if let Some(idx) = list_edit_selected(&brow) {
+                        let mut entries = entries.borrow_mut();
+                        let mut weights = weights.borrow_mut();
+                        let mut attributes = attributes.borrow_mut();
+                        entries.remove(idx);
+                        weights.remove(idx);
+                        attributes.remove(idx);
+                        let select = idx.min(entries.len().saturating_sub(1)) as i32 + 1;
+                        list_edit_refresh(&mut brow, &entries, &weights, &attributes, select);
+                    }
+                });
+            }
+
+            {
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                upbtn.set_callback(move |_| {
+                    if let Some(idx) = list_edit_selected(&brow) && idx > 0 {
+                        let mut entries = entries.borrow_mut();
+                        let mut weights = weights.borrow_mut();
+                        let mut attributes = attributes.borrow_mut();
+                        entries.swap(idx, idx - 1);
+                        weights.swap(idx, idx - 1);
+                        attributes.swap(idx, idx - 1);
+                        list_edit_refresh(&mut brow, &entries, &weights, &attributes, idx as i32);
+                    }
+                });
+            }
+
+            {
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                downbtn.set_callback(move |_| {
+                    let mut entries = entries.borrow_mut();
+                    let mut weights = weights.borrow_mut();
+                    let mut attributes = attributes.borrow_mut();
+                    if let Some(idx) = list_edit_selected(&brow) && idx + 1 < entries.len() {
+                        entries.swap(idx, idx + 1);
+                        weights.swap(idx, idx + 1);
+                        attributes.swap(idx, idx + 1);
+                        list_edit_refresh(&mut brow, &entries, &weights, &attributes, idx as i32 + 2);
+                    }
+                });
+            }
+
+            {   // Sorting & de-duplicating work on a copy of the list so the
+                // rules for each typechoice stay in the core functions.
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                let mut templist = uselist.clone();
+                sortbtn.set_callback(move |_| {
+                    let mut entries = entries.borrow_mut();
+                    let mut weights = weights.borrow_mut();
+                    let mut attributes = attributes.borrow_mut();
+                    let copied = list_set_entries(&mut templist, &entries)
+                        .and_then(|_| list_set_weights(&mut templist, &weights))
+                        .and_then(|_| list_set_attributes(&mut templist, &attributes));
+                    if copied.is_ok() {
+                        list_sort(&mut templist);
+                        *entries = list_entries(&templist);
+                        *weights = list_weights(&templist);
+                        *attributes = list_attributes(&templist);
+                        list_edit_refresh(&mut brow, &entries, &weights, &attributes, 0);
+                    }
+                });
+            }
+
+            {
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                let mut templist = uselist.clone();
+                dedupbtn.set_callback(move |_| {
+                    let mut entries = entries.borrow_mut();
+                    let mut weights = weights.borrow_mut();
+                    let mut attributes = attributes.borrow_mut();
+                    let copied = list_set_entries(&mut templist, &entries)
+                        .and_then(|_| list_set_weights(&mut templist, &weights))
+                        .and_then(|_| list_set_attributes(&mut templist, &attributes));
+                    if copied.is_ok() {
+                        let removed = list_dedup(&mut templist);
+                        *entries = list_entries(&templist);
+                        *weights = list_weights(&templist);
+                        *attributes = list_attributes(&templist);
+                        list_edit_refresh(&mut brow, &entries, &weights, &attributes, 0);
+                        fltk_custom_message(&format!("{} repeated entries were removed.", removed), "OK");
+                    }
+                });
+            }
+
+            {   // Add many entries at once.  Entries that don't fit the list's
+                // type are reported, and the rest are added.
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                let typech = typech.clone();
+                pastebtn.set_callback(move |_| {
+                    let text = list_edit_paste_dialog(&typech);
+                    let mut failed: Vec<QbcError> = Vec::new();
+                    let mut entries = entries.borrow_mut();
+                    let mut weights = weights.borrow_mut();
+                    let mut attributes = attributes.borrow_mut();
+                    for item in list_split_bulk(&text, &typech) {
+                        match list_parse_entry(&item, &typech) {
+                            Ok(item) => {
+                                entries.push(item);
+                                weights.push(1.0);
+                                attributes.push(EntryAttrs::default());
+                            }
+                            Err(err) => failed.push(err),
+                        }
+                    }
+                    list_edit_refresh(&mut brow, &entries, &weights, &attributes, entries.len() as i32);
+                    if !failed.is_empty() {
+                        fltk_custom_message(&format!("These entries were not added:\n{}", err_join_messages(&failed)), "Return to the list.");
+                    }
+                });
+            }
+
+            {   // Change how often the selected entry is drawn.
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                weightbtn.set_callback(move |_| {
+                    let Some(idx) = list_edit_selected(&brow) else {
+                        fltk_custom_message("Please select an entry first.", "Return to the list.");
+                        return;
+                    };
+                    match weightinput.value().trim().parse::<f64>() {
+                        Ok(weight) if weight.is_finite() && weight >= 0.0 => {
+                            let mut weights = weights.borrow_mut();
+                            weights[idx] = weight;
+                            list_edit_refresh(&mut brow, &entries.borrow(), &weights, &attributes.borrow(), idx as i32 + 1);
+                        }
+                        _ => fltk_custom_message("A weight must be a number that is 0 or more.", "Return to the list."),
+                    }
+                });
+            }
+
+            {   // Set the grammar of the selected entry, used by forms like  §student:they§
+                let entries = entries.clone();
+                let weights = weights.clone();
+                let attributes = attributes.clone();
+                let mut brow = brow.clone();
+                attrbtn.set_callback(move |_| {
+                    let Some(idx) = list_edit_selected(&brow) else {
+                        fltk_custom_message("Please select an entry first.", "Return to the list.");
+                        return;
+                    };
+                    let entry = entries.borrow()[idx].clone();
+                    let current = attributes.borrow()[idx].clone();
+                    if let Some(attrs) = list_edit_attrs_dialog(&entry, &current) {
+                        attributes.borrow_mut()[idx] = attrs;
+                        list_edit_refresh(&mut brow, &entries.borrow(), &weights.borrow(), &attributes.borrow(), idx as i32 + 1);
+                    }
+                });
+            }
+
+            let mut win_clone = win.clone();
+            cancelbtn.set_callback(move |_| win_clone.hide());
+
+            let mut win_clone = win.clone();
+            let mut uselist = uselist;
+            savebtn.set_callback(move |_| {
+                let saved = list_set_entries(&mut uselist, &entries.borrow())
+                    .and_then(|_| list_set_weights(&mut uselist, &weights.borrow()))
+                    .and_then(|_| list_set_attributes(&mut uselist, &attributes.borrow()))
+                    .and_then(|_| list_save_as_json(&uselist, &listpath));
+                match saved {
+                    Ok(()) => win_clone.hide(),
+                    Err(err) => {
+                        eprintln!("\n Error saving the list: {} \n", err);
+                        fltk_custom_message(&err.to_string(), "Return to the list.");
+                    }
+                }
+            });
+            // endregion
+
+            while win.shown() {
+                app::wait();
+            }
+        }
+
+        /// Returns the index of the entry selected in the list editor, if any.
+        ///
+        fn list_edit_selected(brow: &HoldBrowser) -> Option<usize> {
+            match brow.value() {
+                0 => None,
+                line => Some(line as usize - 1),
+            }
+        }
+
+        /// Fills the list editor's table with numbered entries, their weights,
+        /// & their pronouns and selects the given line.  A `select` of 0 leaves
+        /// nothing selected.
+        fn list_edit_refresh(brow: &mut HoldBrowser, entries: &[String], weights: &[f64], attributes: &[EntryAttrs], select: i32) {
+            brow.clear();
+            for (idx, item) in entries.iter().enumerate() {
+                let weight = weights.get(idx).copied().unwrap_or(1.0);
+                let pronouns = attributes.get(idx).map(|attrs| attrs.pronouns.as_str()).unwrap_or_default();
+                brow.add(&format!("{}\t×{}\t{}\t{}", idx + 1, weight, pronouns, item));
+            }
+            if select > 0 {
+                brow.select(select);
+            }
+        }
+
+        /// Asks for the pronouns, plural, & article of a list entry.  Returns
+        /// `None` if the dialog was cancelled.
+        fn list_edit_attrs_dialog(entry: &str, current: &EntryAttrs) -> Option<EntryAttrs> {
+            let mut win = Window::default().with_size(420, 260).with_label("Entry Grammar");
+            win.make_modal(true);
+            Frame::new(20, 10, 380, 30, None).set_label(entry);
+
+            let mut pronouns = Choice::new(160, 50, 200, 30, "Pronouns:");
+            pronouns.add_choice("(they, if not set)");
+            for set in LIST_PRONOUN_SETS.iter() {
+                pronouns.add_choice(&format!("{}/{}", set[0], set[1]));
+            }
+            pronouns.set_value(LIST_PRONOUN_SETS.iter().position(|set| set[0] == current.pronouns).map(|pos| pos as i32 + 1).unwrap_or(0));
+
+            let mut plural = Input::new(160, 95, 200, 30, "Plural:");
+            plural.set_tooltip("Leave blank to add  s  or  es.");
+            plural.set_value(&current.plural);
+
+            let mut article = Choice::new(160, 140, 200, 30, "Article:");
+            article.add_choice("(automatic)|a|an");
+            article.set_value(match current.article.as_str() { "a" => 1, "an" => 2, _ => 0 });
+
+            let mut okbtn = Button::new(90, 200, 110, 40, "OK");
+            let mut cancelbtn = Button::new(220, 200, 110, 40, "Cancel");
+            win.end();
+            win.show();
+
+            let chosen: Rc<RefCell<Option<EntryAttrs>>> = Rc::new(RefCell::new(None));
+
+            let mut win_clone = win.clone();
+            cancelbtn.set_callback(move |_| win_clone.hide());
+
+            let mut win_clone = win.clone();
+            let chosen_clone = chosen.clone();
+            okbtn.set_callback(move |_| {
+                let pronouns = match pronouns.value() {
+                    pos if pos > 0 => LIST_PRONOUN_SETS[pos as usize - 1][0].to_string(),
+                    _ => String::new(),
+                };
+                let article = match article.value() { 1 => "a", 2 => "an", _ => "" };
+                *chosen_clone.borrow_mut() = Some(EntryAttrs { pronouns, plural: plural.value().trim().to_string(), article: article.to_string() });
+                win_clone.hide();
+            });
+
+            while win.shown() {
+                app::wait();
+            }
+
+            chosen.take()
+        }
+
+        /// Asks for many list entries at once.  Returns the pasted text, or
+        /// an empty string if the dialog was cancelled.
+        fn list_edit_paste_dialog(typech: &str) -> String {
+            let prompt = match typech {
+                "Strings" => "Paste the entries, one per line.",
+                _ => "Paste the entries, separated by lines, commas, or spaces.",
+            };
+
+            let mut win = Window::default().with_size(560, 420).with_label("Paste Entries");
+            win.make_modal(true);
+            Frame::new(20, 10, 520, 30, prompt);
+            let textinput = MultilineInput::new(20, 50, 520, 290, "");
+            let mut okbtn = Button::new(150, 360, 120, 40, "Add");
+            let mut cancelbtn = Button::new(290, 360, 120, 40, "Cancel");
+            win.end();
+            win.show();
+
+            let pasted = Rc::new(RefCell::new(String::new()));
+
+            let mut win_clone = win.clone();
+            cancelbtn.set_callback(move |_| win_clone.hide());
+
+            let mut win_clone = win.clone();
+            let pasted_clone = pasted.clone();
+            okbtn.set_callback(move |_| {
+                *pasted_clone.borrow_mut() = textinput.value();
+                win_clone.hide();
+            });
+
+            while win.shown() {
+                app::wait();
+            }
+
+            pasted.take()
+        }
+
+        /// Prepare a list for saving to a file.
+        ///
+        pub fn list_save(list: &List) -> String {
+            // Why does this return the path?  Is it necessary?
+
+            // region Set up directories.
+
+            let usedir = glob_check_lastdirused("Lists");
+
+            let filters = vec!["Lists", "*.lst", "Variables", "*.vrbl", "Banks", "*.bnk", "Text", "*.txt", "All Files", "*.*"];
+            let path = file_browse_tosave(&usedir, "", &filters);
+
+            glob_update_lastdirused("Lists", &path);  // Remember the folder for next time.
+
+            // endregion
+
+            if let Err(err) = list_save_as_json(list, path.as_str()) {
+                eprintln!("\n Error saving the list: {} \n", err);
+                fltk_custom_message(&err.to_string(), "Return.");
+            }
+
+            path
+        }
+
+    } // End   gui   submodule
+
+} // End  lists module
+
+/// Functions for use in creating menus.
+///
+#[cfg(feature = "gui")]
+pub mod menus {
+    use crate::config::cnfg_settings_dialog;
+    use crate::misc::check_for_bank_loaded;
+    use crate::{banks::*, export::export_dialog, lists::*, questions::*, variable::*, SHOW_VALUES};
+    use fltk::enums::{Color, Shortcut};
+    use fltk::prelude::{MenuExt, WidgetBase, WidgetExt};
+    use fltk::{app::quit, menu, window::Window};
+
+    /// Create a menubar for the primary window.
+    pub fn qbnk_menubar(primwin: &mut Window) -> menu::MenuBar {
+        // todo: `primwin` is in the global Widgets variable.  Access it
+        //          there rather than passing it to this function.
+
+        let mut menubar = menu::MenuBar::new(0, 0, primwin.width(), 40, "");
+
+        //region File section
+
+        menubar.add(
+            "File/Print/Question Bank\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    export_dialog(None);
+                }
+            },
+        );
+        menubar.add(
+            "File/Print/Question\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() && let Some(qst_idx) = qst_choose_number() {
+                    export_dialog(Some(vec![qst_idx]));
+                }
+            },
+        );
+        menubar.add(
+            "File/Print/Variable\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| println!("Printing a new Variable."),
+        );
+        menubar.add(
+            "File/Print/List\t", // Where does versioning come in?
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| println!("Printing a new List."),
+        );
+        menubar.add(
+            "File/Save\t", // Save always focuses on the Question Bank.
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| println!("Saving a Question Bank."),
+        );
+        menubar.add(
+            "File/Save-as\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| println!("Saving a Question Bank with a new name."),
+        );
+        menubar.add(
+            "File/Settings\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| cnfg_settings_dialog(),
+        );
+
+        let quit_idx = menubar.add(
+            "File/Quit\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if bnk_confirm_discard() {
+                    quit();
+                }
+            },
+        );
+        menubar.at(quit_idx).unwrap().set_label_color(Color::Red);
+        //endregion
+
+        //region Bank section
+
+        menubar.add(
+            "Bank/New\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                bnk_create();
+                bnk_refresh_widgets();
+            },
+        );
+
+        menubar.add(
+            "Bank/Open\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                bnk_read();
+                bnk_refresh_widgets();
+            },
+        );
+
+        // TODO: Add  ctrl-s  as option for saving.
+        // TODO: Right now  Save  and  Save-As  are the same thing.
+        //          Differentiate them.
+
+        menubar.add(
+            "Bank/Save\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                bnk_save();
+                println!("/n The question bank has been saved. \n")
+            },
+        );
+        menubar.add(
+            "Bank/Save-as\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                bnk_save();
+                println!("/n The question bank has been saved. \n")
+            },
+        );
+
+        menubar.add(
+            "Bank/Recalculate\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    bnk_recalc();
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+
+        menubar.add(
+            "Bank/Recalculate from Seed\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    bnk_recalc_from_seed();
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+
+        menubar.add(
+            "Bank/Library/Sync from Library\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    bnk_sync();
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+
+        menubar.add(
+            "Bank/Library/Detach from Library\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    bnk_detach();
+                }
+            },
+        );
+
+        let showvals_idx = menubar.add(
+            "Bank/Show Variable Values\t",
+            Shortcut::None,
+            menu::MenuFlag::Toggle,
+            |m| {
+                if let Some(item) = m.mvalue() {
+                    *SHOW_VALUES.lock().unwrap() = item.value();
+                }
+                if bnk_loaded() {
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+        menubar.at(showvals_idx).unwrap().set();  // Values are shown by default.
+
+        menubar.add(
+            "Bank/Number Format\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    bnk_number_format();
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+
+        menubar.add(
+            "Bank/Export\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() {
+                    export_dialog(None);
+                }
+            },
+        );
+
+        //endregion
+
+        //region Question Section
+
+        menubar.add(
+            "Question/New\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                if check_for_bank_loaded() {
+                    qst_create();
+                    bnk_refresh_widgets();
+                }
+
+                // Keep the window display open after this function finishes.
+                //  while primwin1.shown() {
+                //      app::wait();
+                //   }
+            },
+        );
+
+        menubar.add(
+            "Question/Recalculate\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            |_| {
+                if check_for_bank_loaded() && let Some(qst_idx) = qst_choose_number() {
+                    qst_recalc_in_bank(qst_idx);
+                    bnk_refresh_widgets();
+                }
+            },
+        );
+
+        //endregion
+
+        //region Variable Section
+
+        menubar.add(
+            "Variable/New\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                vrbl_create();
+            },
+        );
+
+        /*
+        menubar.add(
+            "Variable/New/String\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                vrbl_create("Strings");
+            },
+        );
+
+        menubar.add(
+            "Variable/New/Characters\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                vrbl_create("chars");
+            },
+        );
+
+        menubar.add(
+            "Variable/New/Integers\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                vrbl_create("ints");
+            },
+        );
+
+        menubar.add(
+            "Variable/New/Floats\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                vrbl_create("floats");
+            },
+        );
+
+        menubar.add(
+            "Variable/Edit\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| vrbl_edit(),
+        );
+
+        menubar.add(
+            "Variable/Recalculate\t",  // Does this make sense as a user task?  Yes, definitely.
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| vrbl_recalc(),
+        );
+
+        //endregion
+
+         */
+        // Uneeded sub menu items.
+        // endregion
+
+        //region List Section
+
+        menubar.add(
+            "List/New/Strings\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                list_create("Strings");
+            },
+        );
+
+        menubar.add(
+            "List/New/Characters\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                list_create("chars");
+            },
+        );
+
+        menubar.add(
+            "List/New/Integers\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                list_create("ints");
+            },
+        );
+
+        menubar.add(
+            "List/New/Floats\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                list_create("floats");
+            },
+        );
+
+        menubar.add(
+            "List/Edit\t",
+            Shortcut::None,
+            menu::MenuFlag::Normal,
+            move |_| {
+                list_edit();
+            },
+        );
+
+        // -------  End list section
+        //endregion
+
+        menubar
+    }
+} //  End   menus  module
+
+/// Math-based functions.
+///
+pub mod math_functions {
+    use rand::distributions::uniform::SampleUniform;
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use serde::{Deserialize, Serialize};
+
+    //region Struct Section
+
+    /// An exact fraction.  The denominator is always positive, but the
+    /// fraction is only reduced when asked, so  2/4  can be shown as is.
+    #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+    pub struct Ratio {
+        pub num: i64,
+        pub den: i64,
+    }
+
+    impl Ratio {
+        /// Makes a fraction, moving any sign to the numerator.  Returns
+        /// `None` if the denominator is 0.
+        pub fn new(num: i64, den: i64) -> Option<Ratio> {
+            match den {
+                0 => None,
+                _ if den < 0 => Some(Ratio { num: num.checked_neg()?, den: den.checked_neg()? }),
+                _ => Some(Ratio { num, den }),
+            }
+        }
+
+        /// Returns the fraction in lowest terms.
+        ///
+        pub fn reduced(&self) -> Ratio {
+            let divisor = math_gcd(self.num, self.den).max(1);
+            Ratio { num: self.num / divisor, den: self.den / divisor }
+        }
+
+        /// Returns the value of the fraction as a decimal.
+        ///
+        pub fn to_f64(&self) -> f64 {
+            self.num as f64 / self.den as f64
+        }
+
+        /// Adds two fractions.  Returns `None` if the result doesn't fit
+        /// in an i64.  The same holds for the other arithmetic below.
+        pub fn checked_add(&self, other: &Ratio) -> Option<Ratio> {
+            let num = self.num as i128 * other.den as i128 + other.num as i128 * self.den as i128;
+            math_ratio_from_i128(num, self.den as i128 * other.den as i128)
+        }
+
+        /// Subtracts a fraction from this one.
+        ///
+        pub fn checked_sub(&self, other: &Ratio) -> Option<Ratio> {
+            self.checked_add(&Ratio { num: other.num.checked_neg()?, den: other.den })
+        }
+
+        /// Multiplies two fractions.
+        ///
+        pub fn checked_mul(&self, other: &Ratio) -> Option<Ratio> {
+            math_ratio_from_i128(self.num as i128 * other.num as i128, self.den as i128 * other.den as i128)
+        }
+
+        /// Divides this fraction by another.  Returns `None` when dividing by 0.
+        ///
+        pub fn checked_div(&self, other: &Ratio) -> Option<Ratio> {
+            math_ratio_from_i128(self.num as i128 * other.den as i128, self.den as i128 * other.num as i128)
+        }
+
+        /// Raises the fraction to a whole-number power.
+        ///
+        pub fn checked_pow(&self, exponent: i64) -> Option<Ratio> {
+            let base = if exponent < 0 { Ratio::new(self.den, self.num)? } else { *self };
+            let mut result = Ratio { num: 1, den: 1 };
+            for _ in 0..exponent.unsigned_abs() {
+                result = result.checked_mul(&base)?;
+            }
+            Some(result)
+        }
+    } // End   Ratio   impl
+
+    /// How a fraction is written in a question.
+    #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+    pub enum FracFormat {
+        #[default]
+        Fraction,  // 3/2
+        Mixed,     // 1 1/2
+        Latex,     // \frac{3}{2}
+    }
+
+    impl FracFormat {
+        /// Every format, in the order they are offered to the user.
+        pub const ALL: [FracFormat; 3] = [FracFormat::Fraction, FracFormat::Mixed, FracFormat::Latex];
+
+        /// Returns the name shown to the user for this format.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                FracFormat::Fraction => "Fraction",
+                FracFormat::Mixed => "Mixed number",
+                FracFormat::Latex => "LaTeX",
+            }
+        }
+    } // End   FracFormat   impl
+
+    /// How a decimal is written in a question.
+    #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+    pub enum Notation {
+        #[default]
+        Standard,     // 602000
+        Scientific,   // 6.02 × 10^5
+        Engineering,  // 602 × 10^3
+    }
+
+    impl Notation {
+        /// Every notation, in the order they are offered to the user.
+        pub const ALL: [Notation; 3] = [Notation::Standard, Notation::Scientific, Notation::Engineering];
+
+        /// Returns the name shown to the user for this notation.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                Notation::Standard => "Standard",
+                Notation::Scientific => "Scientific",
+                Notation::Engineering => "Engineering",
+            }
+        }
+    } // End   Notation   impl
+
+    /// The separators a Bank writes its numbers with.
+    #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+    pub enum NumStyle {
+        #[default]
+        English,   // 1,234.56
+        European,  // 1.234,56
+        Si,        // 1 234.56  (thin space)
+        French,    // 1 234,56  (thin space)
+        Swiss,     // 1'234.56
+    }
+
+    impl NumStyle {
+        /// Every style, in the order they are offered to the user.
+        pub const ALL: [NumStyle; 5] = [NumStyle::English, NumStyle::European, NumStyle::Si, NumStyle::French, NumStyle::Swiss];
+
+        /// Returns the name shown to the user for this style.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                NumStyle::English => "1,234.56",
+                NumStyle::European => "1.234,56",
+                NumStyle::Si => "1 234.56  (SI)",
+                NumStyle::French => "1 234,56",
+                NumStyle::Swiss => "1'234.56",
+            }
+        }
+
+        /// Returns the character that separates the whole part from the fraction.
+        ///
+        pub fn decimal_sep(&self) -> char {
+            match self {
+                NumStyle::English | NumStyle::Si | NumStyle::Swiss => '.',
+                NumStyle::European | NumStyle::French => ',',
+            }
+        }
+
+        /// Returns the character that separates each group of three digits.
+        ///
+        pub fn group_sep(&self) -> char {
+            match self {
+                NumStyle::English => ',',
+                NumStyle::European => '.',
+                NumStyle::Si | NumStyle::French => '\u{2009}',  // Thin space
+                NumStyle::Swiss => '\'',
+            }
+        }
+    } // End   NumStyle   impl
+
+    /// How the numbers of a Bank are written:  its separators and the
+    /// currency symbol used for Variables that hold money.
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+    pub struct NumLocale {
+        pub style: NumStyle,
+        pub currency: String,     // e.g.  "$"  or  "€".  May be empty.
+        pub currency_after: bool, // "12,50 €"  rather than  "$12.50"
+    }
+
+    impl Default for NumLocale {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl NumLocale {
+        /// The locale QBC has always used:  1,234.56  with a leading "$".
+        ///
+        pub fn new() -> NumLocale {
+            Self {
+                style: NumStyle::English,
+                currency: "$".to_string(),
+                currency_after: false,
+            }
+        }
+    } // End   NumLocale   impl
+
+    //endregion
+
+    /// Round an f64 to a given decimal place.
+    ///
+    pub fn math_round_to_place_f64(num: &f64, place: usize) -> f64 {
+        let factor = 10f64.powi(place as i32);
+
+        (num * factor).round() / factor
+    }
+
+    /// Round an f64 to a given number of significant figures.  Zero
+    /// significant figures leaves the number as it is.
+    pub fn math_round_to_sig_figs(num: f64, sig_figs: usize) -> f64 {
+        if sig_figs == 0 || !num.is_finite() {
+            return num;
+        }
+        // Rust's exponent formatting already rounds to a number of digits.
+        format!("{:.*e}", sig_figs - 1, num).parse().unwrap_or(num)
+    }
+
+    /// Writes a number to a given number of significant figures, keeping
+    /// any trailing zeros, e.g.  2.5  to 3 figures is  "2.50".  Scientific &
+    /// engineering notation are written as  "6.02 × 10^23".  Zero significant
+    /// figures uses as many digits as the number needs.
+    pub fn math_format_sig_figs(num: f64, sig_figs: usize, notation: &Notation) -> String {
+        if !num.is_finite() {
+            return num.to_string();
+        }
+        let numtxt = match sig_figs {
+            0 => format!("{:e}", num),
+            _ => format!("{:.*e}", sig_figs - 1, num),
+        };
+        // e.g.  "-6.02e23"  gives the sign "-", the digits "602", & the exponent 23.
+        let (mantissa, exponent) = numtxt.split_once('e').unwrap_or((&numtxt, "0"));
+        let exponent: i32 = exponent.parse().unwrap_or(0);
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", mantissa),
+        };
+        let digits = mantissa.replace('.', "");
+
+        match notation {
+            Notation::Standard => format!("{}{}", sign, math_place_point(&digits, exponent + 1)),
+            Notation::Scientific => format!("{}{} × 10^{}", sign, math_place_point(&digits, 1), exponent),
+            Notation::Engineering => {
+                let eng_exponent = exponent.div_euclid(3) * 3;
+                format!("{}{} × 10^{}", sign, math_place_point(&digits, exponent - eng_exponent + 1), eng_exponent)
+            }
+        }
+    }
+
+    /// Puts a decimal point into a string of digits after the first
+    /// `whole_digits` of them, padding with zeros as needed, e.g.  "25"
+    /// with 3 whole digits is  "250"  and with -1 is  "0.025".
+    fn math_place_point(digits: &str, whole_digits: i32) -> String {
+        let len = digits.len() as i32;
+        if whole_digits <= 0 {
+            format!("0.{}{}", "0".repeat(whole_digits.unsigned_abs() as usize), digits)
+        } else if whole_digits >= len {
+            format!("{}{}", digits, "0".repeat((whole_digits - len) as usize))
+        } else {
+            let (whole, fraction) = digits.split_at(whole_digits as usize);
+            format!("{}.{}", whole, fraction)
+        }
+    }
+
+    /// Insert commas between each group of three digits in a number that
+    /// has already been converted to text, e.g.  "-1234567.89"  becomes
+    /// "-1,234,567.89".
+    pub fn math_comma_format(numtxt: &str) -> String {
+        math_group_digits(numtxt, ',')
+    }
+
+    /// Like `math_comma_format()`, but separates the groups with `sep`.
+    ///
+    pub fn math_group_digits(numtxt: &str, sep: char) -> String {
+        let (sign, unsigned) = match numtxt.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", numtxt),
+        };
+        let (whole, fraction) = match unsigned.find('.') {
+            Some(idx) => unsigned.split_at(idx),
+            None => (unsigned, ""),
+        };
+
+        let mut grouped = String::new();
+        for (idx, digit) in whole.chars().enumerate() {
+            if idx > 0 && (whole.len() - idx) % 3 == 0 {
+                grouped.push(sep);
+            }
+            grouped.push(digit);
+        }
+
+        format!("{}{}{}", sign, grouped, fraction)
+    }
+
+    /// Writes a number that has already been converted to text, e.g.
+    /// "-1234.5"  or  "6.02 × 10^23", with a locale's separators.  Digits are
+    /// only grouped when `group` is `true`, and never in scientific notation.
+    pub fn math_format_number(numtxt: &str, group: bool, locale: &NumLocale) -> String {
+        let (mantissa, power) = match numtxt.find(" × ") {
+            Some(idx) => numtxt.split_at(idx),
+            None => (numtxt, ""),
+        };
+        let (whole, fraction) = match mantissa.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (mantissa, None),
+        };
+        let mut localized = if group && power.is_empty() {
+            math_group_digits(whole, locale.style.group_sep())
+        } else {
+            whole.to_string()
+        };
+        if let Some(fraction) = fraction {
+            localized.push(locale.style.decimal_sep());
+            localized.push_str(fraction);
+        }
+        localized.push_str(power);
+        localized
+    }
+
+    /// Adds a locale's currency symbol to a number that has already been
+    /// formatted, keeping any minus sign in front, e.g.  "-$5.00"  or  "-5,00 €".
+    pub fn math_format_currency(numtxt: &str, locale: &NumLocale) -> String {
+        let (sign, unsigned) = match numtxt.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", numtxt),
+        };
+        match (locale.currency.is_empty(), locale.currency_after) {
+            (true, _) => numtxt.to_string(),
+            (false, true) => format!("{}{} {}", sign, unsigned, locale.currency),
+            (false, false) => format!("{}{}{}", sign, locale.currency, unsigned),
+        }
+    }
+
+    /// Generate and return a random number between the given min and max.
+    /// The result can't be reproduced.  Use `math_gen_random_num_with()` and
+    /// a generator from `math_seeded_rng()` when it needs to be.
+    ///
+    /// Example:
+    ///
+    ///     use question_bank_creator::math_functions::math_gen_random_num;
+    ///
+    ///     let choice: i64 = math_gen_random_num(-9, 9);
+    ///
+    ///     println!("\n Your random number is:   {} \n", choice);
+    ///
+    pub fn math_gen_random_num<T: SampleUniform + PartialOrd>(min: T, max: T) -> T {
+        let mut rng = thread_rng();
+        math_gen_random_num_with(&mut rng, min, max)
+    }
+
+    /// Picks a new seed at random.  Used when the user doesn't give one.
+    ///
+    pub fn math_new_seed() -> u64 {
+        thread_rng().r#gen()
+    }
+
+    /// Makes a random number generator from a seed.  The same seed always
+    /// gives the same sequence of numbers, on any machine and with any
+    /// version of `rand`, so recorded seeds stay valid.
+    pub fn math_seeded_rng(seed: u64) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(seed)
+    }
+
+    /// Mixes a seed with a version number to give the seed for that one
+    /// version, so any version can be regenerated without the others.
+    pub fn math_version_seed(seed: u64, version: usize) -> u64 {
+        // SplitMix64 -- spreads nearby inputs far apart.
+        let mut mixed = seed.wrapping_add((version as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// Generate a random number between the given min and max using the
+    /// random number generator that is passed in.  Passing a seeded
+    /// generator makes the result reproducible.
+    pub fn math_gen_random_num_with<T: SampleUniform + PartialOrd>(rng: &mut impl Rng, min: T, max: T) -> T {
+        rng.gen_range(min..=max)
+    }
+
+    /// Generate a random integer between min and max that lands on the grid
+    /// min, min + step, min + 2*step, ...  A step of 0 or 1 allows every
+    /// integer in the range.
+    pub fn math_gen_stepped_int(rng: &mut impl Rng, min: i64, max: i64, step: i64) -> i64 {
+        if step <= 1 {
+            return math_gen_random_num_with(rng, min, max);
+        }
+        let count = (max as i128 - min as i128) / step as i128;  // i128 so wide ranges can't overflow.
+        (min as i128 + step as i128 * rng.gen_range(0..=count)) as i64
+    }
+
+    /// Generate a random decimal number between min and max that lands on
+    /// the grid min, min + step, min + 2*step, ...  A step of 0 allows any
+    /// value in the range.
+    pub fn math_gen_stepped_float(rng: &mut impl Rng, min: f64, max: f64, step: f64) -> f64 {
+        if step <= 0.0 {
+            return math_gen_random_num_with(rng, min, max);
+        }
+        // The slack keeps a max that is on the grid from being lost to rounding,
+        // e.g. (120.0 - 60.0) / 0.1 == 599.9999999999999
+        let count = ((max - min) / step + 1e-9).floor() as u64;
+        min + step * rng.gen_range(0..=count) as f64
+    }
+
+    /// Returns the greatest common divisor of two numbers, which is never
+    /// negative.  The gcd of 0 & 0 is 0.
+    pub fn math_gcd(aaa: i64, bbb: i64) -> i64 {
+        let (mut aaa, mut bbb) = (aaa.unsigned_abs(), bbb.unsigned_abs());
+        while bbb != 0 {
+            (aaa, bbb) = (bbb, aaa % bbb);
+        }
+        aaa.min(i64::MAX as u64) as i64
+    }
+
+    /// Builds a reduced fraction from a wide numerator & denominator, as
+    /// left by fraction arithmetic.  Returns `None` if the denominator is 0
+    /// or the reduced fraction doesn't fit in an i64.
+    fn math_ratio_from_i128(num: i128, den: i128) -> Option<Ratio> {
+        if den == 0 {
+            return None;
+        }
+        let (mut aaa, mut bbb) = (num.unsigned_abs(), den.unsigned_abs());
+        while bbb != 0 {
+            (aaa, bbb) = (bbb, aaa % bbb);
+        }
+        let divisor = aaa.max(1) as i128;
+        let sign = if den < 0 { -1 } else { 1 };
+        Ratio::new(i64::try_from(sign * num / divisor).ok()?, i64::try_from(sign * den / divisor).ok()?)
+    }
+
+    /// Converts a decimal to the exact fraction it is written as, e.g.
+    /// 2.25  becomes  9/4.  Returns `None` for numbers that can't be
+    /// written that way, such as  1e-30  or infinity.
+    pub fn math_ratio_from_f64(value: f64) -> Option<Ratio> {
+        if !value.is_finite() {
+            return None;
+        }
+        let numtxt = value.to_string();  // The shortest decimal that gives back the same f64.
+        let (whole, fraction) = numtxt.split_once('.').unwrap_or((&numtxt, ""));
+        let den = 10i64.checked_pow(fraction.len() as u32)?;
+        let digits: i64 = format!("{}{}", whole, fraction).parse().ok()?;
+        Some(Ratio::new(digits, den)?.reduced())
+    }
+
+    /// Formats a fraction for display.  A fraction whose denominator is 1,
+    /// or a mixed number with no fractional part, is shown as a whole number.
+    pub fn math_format_ratio(ratio: &Ratio, style: &FracFormat) -> String {
+        if ratio.den == 1 {
+            return ratio.num.to_string();
+        }
+        match style {
+            FracFormat::Fraction => format!("{}/{}", ratio.num, ratio.den),
+            FracFormat::Latex if ratio.num < 0 => format!("-\\frac{{{}}}{{{}}}", ratio.num.unsigned_abs(), ratio.den),
+            FracFormat::Latex => format!("\\frac{{{}}}{{{}}}", ratio.num, ratio.den),
+            FracFormat::Mixed => {
+                let sign = if ratio.num < 0 { "-" } else { "" };
+                let (whole, part) = (ratio.num.unsigned_abs() / ratio.den as u64, ratio.num.unsigned_abs() % ratio.den as u64);
+                match (whole, part) {
+                    (_, 0) => format!("{}{}", sign, whole),
+                    (0, _) => format!("{}{}/{}", sign, part, ratio.den),
+                    _ => format!("{}{} {}/{}", sign, whole, part, ratio.den),
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the number is prime.
+    ///
+    pub fn math_is_prime(num: i64) -> bool {
+        if num < 2 {
+            return false;
+        }
+        let mut divisor: i64 = 2;
+        while divisor <= num / divisor {
+            if num % divisor == 0 {
+                return false;
+            }
+            divisor += 1;
+        }
+        true
+    }
+
+    /// Returns `true` if the number is the square of a whole number.
+    /// Negative numbers never are.
+    pub fn math_is_perfect_square(num: i64) -> bool {
+        if num < 0 {
+            return false;
+        }
+        let root = (num as f64).sqrt().round() as i64;
+        // Check the neighbours too, in case the f64 square root was off by one.
+        (root.saturating_sub(1)..=root.saturating_add(1)).any(|item| item.checked_mul(item) == Some(num))
+    }
+
+    /// Counts the decimal places needed to write a number exactly, e.g.
+    /// 0.25 needs 2 and 5.0 needs 0.
+    pub fn math_decimal_places(num: f64) -> usize {
+        let numtxt = num.to_string();
+        match numtxt.find('.') {
+            Some(idx) => numtxt.len() - idx - 1,
+            None => 0,
+        }
+    }
+
+    /// Randomly choose an item from a vector using the random number
+    /// generator that is passed in.  Returns `None` if the vector is empty.
+    pub fn math_random_choice<'a, T>(rng: &mut impl Rng, usevec: &'a [T]) -> Option<&'a T> {
+        if usevec.is_empty() {
+            return None;
+        }
+        usevec.get(rng.gen_range(0..usevec.len()))
+    }
+
+    /// Randomly chooses an index into `weights`, each in proportion to its
+    /// weight.  Weights that aren't finite count as 0.  Returns `None` if no
+    /// weight is above 0.
+    pub fn math_weighted_index(rng: &mut impl Rng, weights: &[f64]) -> Option<usize> {
+        let usable = |weight: &f64| weight.is_finite() && *weight > 0.0;
+        let largest = weights.iter().copied().filter(usable).fold(0.0, f64::max);
+        if largest <= 0.0 {
+            return None;
+        }
+        // Scaling by the largest weight keeps the total from overflowing.
+        let total: f64 = weights.iter().filter(|weight| usable(weight)).map(|weight| weight / largest).sum();
+        let mut point = rng.gen_range(0.0..total);
+        for (idx, weight) in weights.iter().enumerate().filter(|(_, weight)| usable(weight)) {
+            if point < weight / largest {
+                return Some(idx);
+            }
+            point -= weight / largest;
+        }
+        weights.iter().rposition(usable)  // Rounding left the point at the very end.
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn weighted_index_skips_zero_weights() {
+            let mut rng = math_seeded_rng(42);
+            let weights = [0.0, 3.0, 0.0, 1.0];
+            let mut counts = [0; 4];
+            for _ in 0..400 {
+                counts[math_weighted_index(&mut rng, &weights).unwrap()] += 1;
+            }
+            assert_eq!(counts[0] + counts[2], 0);
+            assert!(counts[1] > counts[3]);
+            assert_eq!(math_weighted_index(&mut rng, &[0.0, 0.0]), None);
+        }
+
+        #[test]
+        fn ratio_arithmetic_reduces_and_catches_overflow() {
+            let half = Ratio::new(1, 2).unwrap();
+            let third = Ratio::new(1, -3).unwrap();
+            assert_eq!(third, Ratio { num: -1, den: 3 });
+            assert_eq!(half.checked_add(&third), Ratio::new(1, 6));
+            assert_eq!(half.checked_sub(&half), Ratio::new(0, 1));
+            assert_eq!(Ratio::new(4, 6).unwrap().checked_mul(&Ratio::new(3, 2).unwrap()), Ratio::new(1, 1));
+            assert_eq!(half.checked_div(&third), Ratio::new(-3, 2));
+            assert_eq!(third.checked_pow(-2), Ratio::new(9, 1));
+            assert_eq!(Ratio::new(6, 8).unwrap().reduced(), Ratio { num: 3, den: 4 });
+            assert_eq!(Ratio::new(1, 0), None);
+            assert_eq!(half.checked_div(&Ratio::new(0, 1).unwrap()), None);
+
+            let huge = Ratio::new(i64::MAX, 1).unwrap();
+            let tiny = Ratio::new(i64::MIN, 1).unwrap();
+            assert_eq!(huge.checked_add(&huge), None);
+            assert_eq!(tiny.checked_sub(&huge), None);
+            assert_eq!(huge.checked_mul(&Ratio::new(2, 1).unwrap()), None);
+            assert_eq!(huge.checked_div(&Ratio::new(1, 2).unwrap()), None);
+            assert_eq!(Ratio::new(2, 1).unwrap().checked_pow(64), None);
+            assert_eq!(Ratio::new(i64::MIN, -1), None);
+        }
+
+        #[test]
+        fn negative_ratios_format_as_mixed_numbers() {
+            let style = FracFormat::Mixed;
+            assert_eq!(math_format_ratio(&Ratio::new(-7, 2).unwrap(), &style), "-3 1/2");
+            assert_eq!(math_format_ratio(&Ratio::new(-1, 2).unwrap(), &style), "-1/2");
+            assert_eq!(math_format_ratio(&Ratio::new(-8, 4).unwrap(), &style), "-2");
+            assert_eq!(math_format_ratio(&Ratio::new(7, 2).unwrap(), &style), "3 1/2");
+            assert_eq!(math_format_ratio(&Ratio::new(-7, 2).unwrap(), &FracFormat::Latex), "-\\frac{7}{2}");
+        }
+
+        #[test]
+        fn weighted_index_handles_huge_weights() {
+            let mut rng = math_seeded_rng(7);
+            for _ in 0..50 {
+                assert!(math_weighted_index(&mut rng, &[1e308, 1e308]).is_some());
+                assert_eq!(math_weighted_index(&mut rng, &[f64::INFINITY, f64::NAN, 2.0]), Some(2));
+            }
+            assert_eq!(math_weighted_index(&mut rng, &[f64::INFINITY]), None);
+        }
+
+        #[test]
+        fn stepped_values_stay_on_the_grid() {
+            let mut rng = math_seeded_rng(19);
+            for (min, max, step) in [(-3.0, 2.0, 0.25), (-50.0, -10.0, 5.0), (-47.0, 12.0, 5.0), (60.0, 120.0, 0.1)] {
+                let mut seen_max = false;
+                for _ in 0..2000 {
+                    let num = math_gen_stepped_float(&mut rng, min, max, step);
+                    let steps = (num - min) / step;
+                    assert!((steps - steps.round()).abs() < 1e-6, "{} is off the {} step grid from {}", num, step, min);
+                    assert!(num >= min && num <= max + 1e-9, "{} is outside {} to {}", num, min, max);
+                    seen_max |= (num - (min + step * ((max - min) / step + 1e-9).floor())).abs() < 1e-9;
+                }
+                assert!(seen_max, "the largest value on the grid from {} to {} was never drawn", min, max);
+            }
+            for _ in 0..500 {
+                let num = math_gen_stepped_int(&mut rng, -50, -10, 5);
+                assert!((-50..=-10).contains(&num) && num % 5 == 0, "{} is off the grid", num);
+            }
+        }
+
+        #[test]
+        fn round_to_the_most_decimal_places() {
+            let places = crate::variable::VRBL_MAX_DCML_PLACES;
+            assert_eq!(math_round_to_place_f64(&1.23456789, 10), 1.23456789);
+            assert_eq!(math_round_to_place_f64(&(1.0 / 3.0), places), 0.333_333_333_333_333);
+            assert_eq!(math_round_to_place_f64(&-2.5, places), -2.5);
+            assert_eq!(math_round_to_place_f64(&2.345, 2), 2.35);
+        }
+
+        #[test]
+        fn round_to_sig_figs() {
+            assert_eq!(math_round_to_sig_figs(6.0221e23, 3), 6.02e23);
+            assert_eq!(math_round_to_sig_figs(0.0012345, 2), 0.0012);
+            assert_eq!(math_round_to_sig_figs(-9.99, 2), -10.0);
+            assert_eq!(math_round_to_sig_figs(2.5, 3), 2.5);
+            assert_eq!(math_round_to_sig_figs(0.0, 3), 0.0);
+            assert_eq!(math_round_to_sig_figs(1.23456, 0), 1.23456);
+        }
+
+        #[test]
+        fn sig_figs_keep_trailing_zeros() {
+            assert_eq!(math_format_sig_figs(2.5, 3, &Notation::Standard), "2.50");
+            assert_eq!(math_format_sig_figs(0.00012, 3, &Notation::Standard), "0.000120");
+            assert_eq!(math_format_sig_figs(9.996, 3, &Notation::Standard), "10.0");
+            assert_eq!(math_format_sig_figs(-0.5, 2, &Notation::Standard), "-0.50");
+            assert_eq!(math_format_sig_figs(0.0, 3, &Notation::Standard), "0.00");
+        }
+
+        #[test]
+        fn sig_figs_in_whole_numbers() {
+            assert_eq!(math_format_sig_figs(123456.0, 3, &Notation::Standard), "123000");
+            assert_eq!(math_format_sig_figs(100.0, 5, &Notation::Standard), "100.00");
+            assert_eq!(math_format_sig_figs(7.0, 1, &Notation::Standard), "7");
+        }
+
+        #[test]
+        fn scientific_notation() {
+            assert_eq!(math_format_sig_figs(6.0221e23, 3, &Notation::Scientific), "6.02 × 10^23");
+            assert_eq!(math_format_sig_figs(0.000123, 2, &Notation::Scientific), "1.2 × 10^-4");
+            assert_eq!(math_format_sig_figs(2.5, 3, &Notation::Scientific), "2.50 × 10^0");
+            assert_eq!(math_format_sig_figs(-9.99, 2, &Notation::Scientific), "-1.0 × 10^1");
+            assert_eq!(math_format_sig_figs(6.02e23, 0, &Notation::Scientific), "6.02 × 10^23");
+        }
+
+        #[test]
+        fn engineering_notation() {
+            assert_eq!(math_format_sig_figs(12345.0, 3, &Notation::Engineering), "12.3 × 10^3");
+            assert_eq!(math_format_sig_figs(0.00047, 2, &Notation::Engineering), "470 × 10^-6");
+            assert_eq!(math_format_sig_figs(1500.0, 2, &Notation::Engineering), "1.5 × 10^3");
+            assert_eq!(math_format_sig_figs(0.5, 1, &Notation::Engineering), "500 × 10^-3");
+        }
+
+        #[test]
+        fn locale_separators() {
+            let mut locale = NumLocale::new();
+            assert_eq!(math_format_number("-1234567.5", true, &locale), "-1,234,567.5");
+            locale.style = NumStyle::European;
+            assert_eq!(math_format_number("1234567.5", true, &locale), "1.234.567,5");
+            assert_eq!(math_format_number("1234567.5", false, &locale), "1234567,5");
+            locale.style = NumStyle::Si;
+            assert_eq!(math_format_number("1234.56", true, &locale), "1\u{2009}234.56");
+            locale.style = NumStyle::French;
+            assert_eq!(math_format_number("6.02 × 10^23", true, &locale), "6,02 × 10^23");
+        }
+
+        #[test]
+        fn currency_symbols() {
+            let mut locale = NumLocale::new();
+            assert_eq!(math_format_currency("-5.00", &locale), "-$5.00");
+            locale.currency = "€".to_string();
+            locale.currency_after = true;
+            assert_eq!(math_format_currency("-5,00", &locale), "-5,00 €");
+            locale.currency.clear();
+            assert_eq!(math_format_currency("5", &locale), "5");
+        }
+
+        #[test]
+        fn non_finite_numbers() {
+            assert_eq!(math_format_sig_figs(f64::INFINITY, 3, &Notation::Scientific), "inf");
+            assert!(math_round_to_sig_figs(f64::NAN, 3).is_nan());
+        }
+    }
+} // End   math_functions   module
+
+/// Units of measure for Variables & answers.
+///
+/// A unit is written the way it would be in a textbook, e.g.  `km`,
+/// `m/s^2`, or  `J/(kg*K)`.  Every unit is stored as a factor that converts
+/// it to SI base units together with its dimension, so  `km/min`  &  `m/s`
+/// can be compared and converted.
+pub mod units {
+    use std::fmt;
+
+    //region Struct Section
+
+    /// The powers of the seven SI base units:  m, kg, s, A, K, mol, cd.
+    /// A plain number has every power 0.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct Dim(pub [i32; 7]);
+
+    impl Dim {
+        /// The dimension of a plain number.
+        pub const NONE: Dim = Dim([0; 7]);
+
+        /// Returns `true` for a plain number.
+        ///
+        pub fn is_none(&self) -> bool {
+            *self == Dim::NONE
+        }
+
+        /// The dimension of a product, e.g.  N * m.
+        ///
+        pub fn times(&self, other: &Dim) -> Dim {
+            Dim(std::array::from_fn(|idx| self.0[idx] + other.0[idx]))
+        }
+
+        /// The dimension of a quotient, e.g.  m / s.
+        ///
+        pub fn over(&self, other: &Dim) -> Dim {
+            Dim(std::array::from_fn(|idx| self.0[idx] - other.0[idx]))
+        }
+
+        /// The dimension raised to a whole power, e.g.  m^2.
+        ///
+        pub fn pow(&self, exponent: i32) -> Dim {
+            Dim(self.0.map(|power| power * exponent))
+        }
+
+        /// The `nth` root of the dimension, or `None` if a power doesn't
+        /// divide evenly, e.g. the square root of m^3.
+        pub fn root(&self, nth: i32) -> Option<Dim> {
+            if nth == 0 || self.0.iter().any(|power| power % nth != 0) {
+                return None;
+            }
+            Some(Dim(self.0.map(|power| power / nth)))
+        }
+    } // End   Dim   impl
+
+    impl fmt::Display for Dim {
+        /// Writes the dimension in SI base units, e.g.  m*kg/s^2.  A plain
+        /// number is written as  "no unit".
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.is_none() {
+                return write!(f, "no unit");
+            }
+            let write_part = |power: i32, symbol: &str| match power {
+                1 => symbol.to_string(),
+                _ => format!("{}^{}", symbol, power),
+            };
+            let numer: Vec<String> = UNIT_BASE_SYMBOLS.iter().zip(self.0)
+                .filter(|(_, power)| *power > 0)
+                .map(|(symbol, power)| write_part(power, symbol))
+                .collect();
+            let denom: Vec<String> = UNIT_BASE_SYMBOLS.iter().zip(self.0)
+                .filter(|(_, power)| *power < 0)
+                .map(|(symbol, power)| write_part(-power, symbol))
+                .collect();
+
+            let numer = if numer.is_empty() { "1".to_string() } else { numer.join("*") };
+            match denom.len() {
+                0 => write!(f, "{}", numer),
+                1 => write!(f, "{}/{}", numer, denom[0]),
+                _ => write!(f, "{}/({})", numer, denom.join("*")),
+            }
+        }
+    }
+
+    /// A unit of measure:  the number of SI base units in one of it, and its dimension.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Unit {
+        pub factor: f64,
+        pub dim: Dim,
+    }
+
+    impl Unit {
+        /// The unit of a plain number.
+        pub const NONE: Unit = Unit { factor: 1.0, dim: Dim::NONE };
+
+        /// The unit of a product, e.g.  N * m.
+        ///
+        pub fn times(&self, other: &Unit) -> Unit {
+            Unit { factor: self.factor * other.factor, dim: self.dim.times(&other.dim) }
+        }
+
+        /// The unit of a quotient, e.g.  km / min.
+        ///
+        pub fn over(&self, other: &Unit) -> Unit {
+            Unit { factor: self.factor / other.factor, dim: self.dim.over(&other.dim) }
+        }
+
+        /// The unit raised to a whole power, e.g.  cm^3.
+        ///
+        pub fn pow(&self, exponent: i32) -> Unit {
+            Unit { factor: self.factor.powi(exponent), dim: self.dim.pow(exponent) }
+        }
+    } // End   Unit   impl
+
+    //endregion
+
+    //region Unit Tables
+
+    /// The symbols of the SI base units, in the order of the powers in `Dim`.
+    pub const UNIT_BASE_SYMBOLS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+    // Powers of the base units:    m   kg  s   A   K  mol cd
+    const LENGTH: [i32; 7] =      [1, 0, 0, 0, 0, 0, 0];
+    const MASS: [i32; 7] =        [0, 1, 0, 0, 0, 0, 0];
+    const TIME: [i32; 7] =        [0, 0, 1, 0, 0, 0, 0];
+    const CURRENT: [i32; 7] =     [0, 0, 0, 1, 0, 0, 0];
+    const TEMPERATURE: [i32; 7] = [0, 0, 0, 0, 1, 0, 0];
+    const AMOUNT: [i32; 7] =      [0, 0, 0, 0, 0, 1, 0];
+    const LUMINOSITY: [i32; 7] =  [0, 0, 0, 0, 0, 0, 1];
+    const VOLUME: [i32; 7] =      [3, 0, 0, 0, 0, 0, 0];
+    const SPEED: [i32; 7] =       [1, 0, -1, 0, 0, 0, 0];
+    const FREQUENCY: [i32; 7] =   [0, 0, -1, 0, 0, 0, 0];
+    const FORCE: [i32; 7] =       [1, 1, -2, 0, 0, 0, 0];
+    const PRESSURE: [i32; 7] =    [-1, 1, -2, 0, 0, 0, 0];
+    const ENERGY: [i32; 7] =      [2, 1, -2, 0, 0, 0, 0];
+    const POWER: [i32; 7] =       [2, 1, -3, 0, 0, 0, 0];
+    const CHARGE: [i32; 7] =      [0, 0, 1, 1, 0, 0, 0];
+    const VOLTAGE: [i32; 7] =     [2, 1, -3, -1, 0, 0, 0];
+    const RESISTANCE: [i32; 7] =  [2, 1, -3, -2, 0, 0, 0];
+
+    /// Every unit QBC knows:  (symbol, SI factor, dimension, takes a metric prefix).
+    /// Temperatures are in kelvins only, since °C & °F don't start at zero.
+    const UNIT_TABLE: [(&str, f64, [i32; 7], bool); 35] = [
+        ("m", 1.0, LENGTH, true),
+        ("g", 1e-3, MASS, true),
+        ("s", 1.0, TIME, true),
+        ("A", 1.0, CURRENT, true),
+        ("K", 1.0, TEMPERATURE, true),
+        ("mol", 1.0, AMOUNT, true),
+        ("cd", 1.0, LUMINOSITY, true),
+        ("L", 1e-3, VOLUME, true),
+        ("Hz", 1.0, FREQUENCY, true),
+        ("N", 1.0, FORCE, true),
+        ("Pa", 1.0, PRESSURE, true),
+        ("J", 1.0, ENERGY, true),
+        ("W", 1.0, POWER, true),
+        ("C", 1.0, CHARGE, true),
+        ("V", 1.0, VOLTAGE, true),
+        ("Ω", 1.0, RESISTANCE, true),
+        ("ohm", 1.0, RESISTANCE, true),
+        ("eV", 1.602176634e-19, ENERGY, true),
+        ("cal", 4.184, ENERGY, true),
+        ("bar", 1e5, PRESSURE, true),
+        ("t", 1e3, MASS, false),
+        ("min", 60.0, TIME, false),
+        ("h", 3600.0, TIME, false),
+        ("d", 86400.0, TIME, false),
+        ("day", 86400.0, TIME, false),
+        ("yr", 31_557_600.0, TIME, false),  // Julian year
+        ("in", 0.0254, LENGTH, false),
+        ("ft", 0.3048, LENGTH, false),
+        ("yd", 0.9144, LENGTH, false),
+        ("mi", 1609.344, LENGTH, false),
+        ("mph", 0.44704, SPEED, false),
+        ("lb", 0.453_592_37, MASS, false),
+        ("oz", 0.028_349_523_125, MASS, false),
+        ("gal", 3.785_411_784e-3, VOLUME, false),
+        ("atm", 101_325.0, PRESSURE, false),
+    ];
+
+    /// The metric prefixes, longest first so  "da"  is tried before  "d".
+    const UNIT_PREFIXES: [(&str, f64); 12] = [
+        ("da", 1e1), ("G", 1e9), ("M", 1e6), ("k", 1e3), ("h", 1e2), ("d", 1e-1),
+        ("c", 1e-2), ("m", 1e-3), ("µ", 1e-6), ("u", 1e-6), ("n", 1e-9), ("p", 1e-12),
+    ];
+
+    //endregion
+
+    /// Reads a unit such as  `km/h`,  `m/s^2`,  `kg·m²`,  or  `J/(kg*K)`.  Units
+    /// are multiplied by  `*`,  `·`,  or a space.  A blank unit is a plain number.
+    pub fn unit_parse(text: &str) -> Result<Unit, String> {
+        let chars: Vec<char> = text.trim().chars().collect();
+        if chars.is_empty() {
+            return Ok(Unit::NONE);
+        }
+        let mut pos = 0;
+        let unit = unit_parse_product(&chars, &mut pos)?;
+        if pos < chars.len() {
+            return Err(format!("'{}' is not a unit QBC can read.", text.trim()));
+        }
+        Ok(unit)
+    }
+
+    /// Reads units joined by  `*`,  `·`,  `/`,  or spaces.
+    ///
+    fn unit_parse_product(chars: &[char], pos: &mut usize) -> Result<Unit, String> {
+        let mut unit = unit_parse_power(chars, pos)?;
+        loop {
+            let start = *pos;
+            while chars.get(*pos).is_some_and(|ch| ch.is_whitespace()) {
+                *pos += 1;
+            }
+            match chars.get(*pos) {
+                Some('*') | Some('·') => {
+                    *pos += 1;
+                    unit_skip_spaces(chars, pos);
+                    unit = unit.times(&unit_parse_power(chars, pos)?);
+                }
+                Some('/') => {
+                    *pos += 1;
+                    unit_skip_spaces(chars, pos);
+                    unit = unit.over(&unit_parse_power(chars, pos)?);
+                }
+                Some(ch) if *pos > start && (ch.is_alphabetic() || *ch == '(') => {
+                    unit = unit.times(&unit_parse_power(chars, pos)?);  // A space multiplies, as in  N m.
+                }
+                _ => {
+                    *pos = start;  // Leave any trailing spaces for the caller.
+                    return Ok(unit);
+                }
+            }
+        }
+    }
+
+    fn unit_skip_spaces(chars: &[char], pos: &mut usize) {
+        while chars.get(*pos).is_some_and(|ch| ch.is_whitespace()) {
+            *pos += 1;
+        }
+    }
+
+    /// Reads a single unit, a  `1`  (as in  1/s), or a unit in parentheses,
+    /// followed by an optional power:  `^2`,  `^-1`,  `2`,  or  `²`.
+    fn unit_parse_power(chars: &[char], pos: &mut usize) -> Result<Unit, String> {
+        let base = match chars.get(*pos) {
+            Some('(') => {
+                *pos += 1;
+                unit_skip_spaces(chars, pos);
+                let inner = unit_parse_product(chars, pos)?;
+                unit_skip_spaces(chars, pos);
+                if chars.get(*pos) != Some(&')') {
+                    return Err("A '(' in the unit has no matching ')'.".to_string());
+                }
+                *pos += 1;
+                inner
+            }
+            Some('1') => {
+                *pos += 1;
+                Unit::NONE
+            }
+            Some(ch) if ch.is_alphabetic() => {
+                let start = *pos;
+                while chars.get(*pos).is_some_and(|ch| ch.is_alphabetic()) {
+                    *pos += 1;
+                }
+                let symbol: String = chars[start..*pos].iter().collect();
+                unit_lookup(&symbol).ok_or_else(|| format!("'{}' is not a unit QBC knows.", symbol))?
+            }
+            Some(ch) => return Err(format!("'{}' can't be used in a unit.", ch)),
+            None => return Err("The unit ends too soon.".to_string()),
+        };
+
+        // region Read the power, if any.
+        let mut powtxt = String::new();
+        if chars.get(*pos) == Some(&'^') {
+            *pos += 1;
+            if chars.get(*pos) == Some(&'-') {
+                powtxt.push('-');
+                *pos += 1;
+            }
+            while let Some(ch) = chars.get(*pos).filter(|ch| ch.is_ascii_digit()) {
+                powtxt.push(*ch);
+                *pos += 1;
+            }
+        } else {
+            while let Some(ch) = chars.get(*pos) {
+                let digit = match ch {
+                    '⁻' => '-',
+                    '¹' => '1',
+                    '²' => '2',
+                    '³' => '3',
+                    '⁴' => '4',
+                    _ if ch.is_ascii_digit() => *ch,
+                    _ => break,
+                };
+                powtxt.push(digit);
+                *pos += 1;
+            }
+        }
+        // endregion
+
+        if powtxt.is_empty() {
+            return Ok(base);
+        }
+        match powtxt.parse::<i32>() {
+            Ok(power) => Ok(base.pow(power)),
+            Err(_) => Err(format!("'{}' is not a whole number power.", powtxt)),
+        }
+    }
+
+    /// Finds a unit by its symbol, with or without a metric prefix, e.g.  "km".
+    /// A symbol in the table wins over a prefixed one, so  "min"  is minutes.
+    pub fn unit_lookup(symbol: &str) -> Option<Unit> {
+        if let Some((_, factor, dim, _)) = UNIT_TABLE.iter().find(|(name, ..)| *name == symbol) {
+            return Some(Unit { factor: *factor, dim: Dim(*dim) });
+        }
+        UNIT_PREFIXES.iter().find_map(|(prefix, scale)| {
+            let rest = symbol.strip_prefix(prefix)?;
+            let (_, factor, dim, _) = UNIT_TABLE.iter().find(|(name, _, _, prefixed)| *prefixed && *name == rest)?;
+            Some(Unit { factor: factor * scale, dim: Dim(*dim) })
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn close(aaa: f64, bbb: f64) -> bool {
+            (aaa - bbb).abs() <= 1e-12 * aaa.abs().max(bbb.abs())
+        }
+
+        #[test]
+        fn parses_prefixed_and_compound_units() {
+            let kmh = unit_parse("km/h").unwrap();
+            assert!(close(kmh.factor, 1000.0 / 3600.0));
+            assert_eq!(kmh.dim, Dim(SPEED));
+            assert_eq!(unit_parse("kg*m/s^2").unwrap().dim, Dim(FORCE));
+            assert_eq!(unit_parse("N m").unwrap().dim, Dim(ENERGY));
+            assert_eq!(unit_parse("J/(kg*K)").unwrap().dim, Dim([2, 0, -2, 0, -1, 0, 0]));
+            assert!(close(unit_parse("cm³").unwrap().factor, 1e-6));
+            assert_eq!(unit_parse("1/s").unwrap().dim, Dim(FREQUENCY));
+            assert_eq!(unit_parse("").unwrap(), Unit::NONE);
+        }
+
+        #[test]
+        fn table_symbols_win_over_prefixes() {
+            assert!(close(unit_lookup("min").unwrap().factor, 60.0));
+            assert!(close(unit_lookup("mm").unwrap().factor, 1e-3));
+            assert!(close(unit_lookup("dam").unwrap().factor, 10.0));
+            assert!(unit_lookup("kmin").is_none());  // Minutes don't take a prefix.
+        }
+
+        #[test]
+        fn rejects_unknown_units() {
+            assert!(unit_parse("furlong").is_err());
+            assert!(unit_parse("m/(s").is_err());
+            assert!(unit_parse("m^x").is_err());
+        }
+
+        #[test]
+        fn writes_dimensions_in_base_units() {
+            assert_eq!(unit_parse("N").unwrap().dim.to_string(), "m*kg/s^2");
+            assert_eq!(unit_parse("Hz").unwrap().dim.to_string(), "1/s");
+            assert_eq!(unit_parse("J/(mol K)").unwrap().dim.to_string(), "m^2*kg/(s^2*K*mol)");
+            assert_eq!(Dim::NONE.to_string(), "no unit");
+        }
+    }
+} // End   units   module
+
+/// Functions for parsing and evaluating answer formulas.
+///
+/// An answer formula is entered by the user in terms of the question's
+/// Variables, for example  `(§const2§ - §const1§) / §coeff1§`.  The formula
+/// is parsed into an expression tree, which is then evaluated using the
+/// current `content` of each Variable.
+pub mod formulas {
+    use crate::global::TypeWrapper::*;
+    use crate::math_functions::*;
+    use crate::units::{unit_parse, Dim, Unit};
+    use crate::variable::{vrbl_find_by_name, VarPrmtrs, Variable};
+
+    //region Struct Section
+
+    /// A node in the expression tree built from an answer formula or a
+    /// condition.  Comparisons & logical operators are `BinOp`s that give
+    /// 1 for true and 0 for false.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Expr {
+        Num(f64),
+        Var(String),
+        Neg(Box<Expr>),
+        Not(Box<Expr>),
+        BinOp(char, Box<Expr>, Box<Expr>),
+        Func(String, Vec<Expr>),
+    }
+
+    /// The pieces a formula is broken into before it is parsed.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Token {
+        Num(f64),
+        Var(String),
+        Ident(String),
+        Op(char),
+        LParen,
+        RParen,
+        Comma,
+    }
+
+    //endregion
+
+    /// The functions that may be used in an answer formula, along with
+    /// the number of arguments each one takes.  The `is_` functions and
+    /// `divides` give 1 for true and 0 for false, for use in conditions.
+    pub const FRML_FUNCTIONS: [(&str, usize); 28] = [
+        ("sqrt", 1), ("cbrt", 1), ("root", 2), ("abs", 1),
+        ("sin", 1), ("cos", 1), ("tan", 1),
+        ("asin", 1), ("acos", 1), ("atan", 1),
+        ("deg", 1), ("rad", 1),
+        ("ln", 1), ("log", 1), ("logb", 2), ("exp", 1),
+        ("round", 1), ("roundto", 2), ("floor", 1), ("ceil", 1), ("trunc", 1),
+        ("min", 2), ("max", 2),
+        ("gcd", 2), ("divides", 2), ("is_int", 1), ("is_prime", 1), ("is_square", 1),
+    ];
+
+    /// Words that join the parts of a condition.  They can't be used as
+    /// function names.
+    const FRML_KEYWORDS: [&str; 3] = ["and", "or", "not"];
+
+    /// Two numbers closer than this, relative to their size, are treated as
+    /// equal in a condition, so  0.1 + 0.2 == 0.3  holds.
+    const FRML_EQUAL_TOLERANCE: f64 = 1e-9;
+
+    /// Breaks the text of a formula into tokens.  Variables are written
+    /// between `§` flags, the same way they are flagged in the question text.
+    pub fn frml_tokenize(formula: &str) -> Result<Vec<Token>, String> {
+        let chars: Vec<char> = formula.chars().collect();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let ch = chars[i];
+
+            if ch.is_whitespace() {
+                i += 1;
+            } else if ch.is_ascii_digit() || ch == '.' {
+                // region Read a number, allowing for an exponent such as 6.02e23.
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let numtxt: String = chars[start..i].iter().collect();
+                match numtxt.parse::<f64>() {
+                    Ok(num) => tokens.push(Token::Num(num)),
+                    Err(_) => return Err(format!("'{}' is not a valid number.", numtxt)),
+                }
+                // endregion
+            } else if ch == '§' {
+                // region Read a flagged variable name.
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '§' {
+                    end += 1;
+                }
+                if end >= chars.len() {
+                    return Err("A variable name is missing its closing § flag.".to_string());
+                }
+                let varname: String = chars[start..end].iter().collect();
+                if varname.trim().is_empty() {
+                    return Err("A pair of § flags is empty.".to_string());
+                }
+                tokens.push(Token::Var(varname.trim().to_string()));
+                i = end + 1;
+                // endregion
+            } else if ch.is_alphabetic() || ch == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                tokens.push(Token::Ident(ident.to_lowercase()));
+            } else if let Some(op) = frml_comparison_op(ch, chars.get(i + 1).copied()) {
+                // region Read a comparison or logical operator of one or two characters.
+                let (op, len) = op;
+                tokens.push(Token::Op(op));
+                i += len;
+                // endregion
+            } else {
+                let token = match ch {
+                    '+' | '-' | '*' | '/' | '^' | '%' => Token::Op(ch),
+                    '×' => Token::Op('*'),
+                    '÷' => Token::Op('/'),
+                    '(' | '[' => Token::LParen,
+                    ')' | ']' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => return Err(format!("The character '{}' can't be used in a formula.", ch)),
+                };
+                tokens.push(token);
+                i += 1;
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// Recognizes the comparison & logical operators, given a character and
+    /// the one after it.  Returns the single character the operator is stored
+    /// as, along with how many characters it took up.
+    fn frml_comparison_op(ch: char, next: Option<char>) -> Option<(char, usize)> {
+        let op = match (ch, next) {
+            ('<', Some('=')) => ('≤', 2),
+            ('>', Some('=')) => ('≥', 2),
+            ('=', Some('=')) => ('=', 2),
+            ('!', Some('=')) => ('≠', 2),
+            ('&', Some('&')) => ('&', 2),
+            ('|', Some('|')) => ('|', 2),
+            ('<' | '>' | '=' | '≤' | '≥' | '≠' | '&' | '|' | '!', _) => (ch, 1),
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// Parses the text of a formula into an expression tree.
+    ///
+    /// Example:
+    ///
+    ///     use question_bank_creator::formulas::frml_parse;
+    ///
+    ///     let tree = frml_parse("(§const2§ - §const1§) / §coeff1§").unwrap();
+    ///     println!("\n The expression tree is:  {:?} \n", tree);
+    ///
+    pub fn frml_parse(formula: &str) -> Result<Expr, String> {
+        let tokens = frml_tokenize(formula)?;
+        if tokens.is_empty() {
+            return Err("The formula is empty.".to_string());
+        }
+
+        let mut pos = 0;
+        let expr = frml_parse_sum(&tokens, &mut pos, false)?;
+        if pos < tokens.len() {
+            return Err(format!("Unexpected {} in the formula.", frml_describe(&tokens[pos])));
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses a condition, such as  `§a§ > §b§ and is_square(§b§^2 - 4*§a§*§c§)`,
+    /// into an expression tree.  The condition holds when the tree
+    /// evaluates to anything but 0.
+    ///
+    /// Example:
+    ///
+    ///     use question_bank_creator::formulas::frml_parse_condition;
+    ///
+    ///     let tree = frml_parse_condition("§dividend§ % §divisor§ == 0").unwrap();
+    ///     println!("\n The expression tree is:  {:?} \n", tree);
+    ///
+    pub fn frml_parse_condition(condition: &str) -> Result<Expr, String> {
+        let tokens = frml_tokenize(condition)?;
+        if tokens.is_empty() {
+            return Err("The condition is empty.".to_string());
+        }
+
+        let mut pos = 0;
+        let expr = frml_parse_or(&tokens, &mut pos)?;
+        if pos < tokens.len() {
+            return Err(format!("Unexpected {} in the condition.", frml_describe(&tokens[pos])));
+        }
+
+        Ok(expr)
+    }
+
+    // region Recursive descent parser
+    //      or      = and { ("or" | "||") and }
+    //      and     = not { ("and" | "&&") not }
+    //      not     = ("not" | "!") not | compare
+    //      compare = sum [ ("<" | ">" | "<=" | ">=" | "==" | "!=") sum ]
+    //      sum     = product { ("+" | "-") product }
+    //      product = unary { ("*" | "/" | "%" | implied multiplication) unary }
+    //                  where implied multiplication is only allowed before "(", a variable, or a function
+    //      unary   = "-" unary | "+" unary | power
+    //      power   = primary [ "^" unary ]
+    //      primary = number | variable | constant | function "(" args ")" | "(" sum ")" | "(" or ")"
+    //
+    // Answer formulas start at `sum`;  conditions start at `or`.  `cond` is true
+    // within a condition, where parentheses may also hold comparisons & logic.
+
+    fn frml_parse_or(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+        let mut left = frml_parse_and(tokens, pos)?;
+
+        while frml_is_joiner(tokens.get(*pos), '|', "or") {
+            *pos += 1;
+            let right = frml_parse_and(tokens, pos)?;
+            left = Expr::BinOp('|', Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn frml_parse_and(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+        let mut left = frml_parse_not(tokens, pos)?;
+
+        while frml_is_joiner(tokens.get(*pos), '&', "and") {
+            *pos += 1;
+            let right = frml_parse_not(tokens, pos)?;
+            left = Expr::BinOp('&', Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn frml_parse_not(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+        if frml_is_joiner(tokens.get(*pos), '!', "not") {
+            *pos += 1;
+            return Ok(Expr::Not(Box::new(frml_parse_not(tokens, pos)?)));
+        }
+        frml_parse_compare(tokens, pos)
+    }
+
+    fn frml_parse_compare(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
+        let left = frml_parse_sum(tokens, pos, true)?;
+
+        if let Some(Token::Op(op)) = tokens.get(*pos)
+            && matches!(op, '<' | '>' | '≤' | '≥' | '=' | '≠')
+        {
+            let op = *op;
+            *pos += 1;
+            let right = frml_parse_sum(tokens, pos, true)?;
+            return Ok(Expr::BinOp(op, Box::new(left), Box::new(right)));
+        }
+
+        Ok(left)
+    }
+
+    /// Returns `true` if the token is the logical operator written either
+    /// as the symbol `op` or the word `word`.
+    fn frml_is_joiner(token: Option<&Token>, op: char, word: &str) -> bool {
+        match token {
+            Some(Token::Op(tokop)) => *tokop == op,
+            Some(Token::Ident(name)) => name == word,
+            _ => false,
+        }
+    }
+
+    fn frml_parse_sum(tokens: &[Token], pos: &mut usize, cond: bool) -> Result<Expr, String> {
+        let mut left = frml_parse_product(tokens, pos, cond)?;
+
+        while let Some(Token::Op(op)) = tokens.get(*pos) {
+            if *op != '+' && *op != '-' {
+                break;
+            }
+            let op = *op;
+            *pos += 1;
+            let right = frml_parse_product(tokens, pos, cond)?;
+            left = Expr::BinOp(op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn frml_parse_product(tokens: &[Token], pos: &mut usize, cond: bool) -> Result<Expr, String> {
+        let mut left = frml_parse_unary(tokens, pos, cond)?;
+
+        loop {
+            let op = match tokens.get(*pos) {
+                Some(Token::Op(op)) if *op == '*' || *op == '/' || *op == '%' => {
+                    *pos += 1;
+                    *op
+                }
+                // Allow  2§x§,  §a§(§b§ + 1),  or  2sqrt(§x§)  to mean multiplication.
+                Some(Token::Ident(name)) if FRML_KEYWORDS.contains(&name.as_str()) => break,
+                Some(Token::Var(_)) | Some(Token::LParen) => '*',
+                Some(Token::Ident(_)) if tokens.get(*pos + 1) == Some(&Token::LParen) => '*',
+                // A typo such as  2 3  or  2e  must not quietly become a product.
+                Some(token @ Token::Num(_)) | Some(token @ Token::Ident(_)) => {
+                    return Err(format!("An operator is missing before the {}.", frml_describe(token)));
+                }
+                _ => break,
+            };
+            let right = frml_parse_unary(tokens, pos, cond)?;
+            left = Expr::BinOp(op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn frml_parse_unary(tokens: &[Token], pos: &mut usize, cond: bool) -> Result<Expr, String> {
+        match tokens.get(*pos) {
+            Some(Token::Op('-')) => {
+                *pos += 1;
+                Ok(Expr::Neg(Box::new(frml_parse_unary(tokens, pos, cond)?)))
+            }
+            Some(Token::Op('+')) => {
+                *pos += 1;
+                frml_parse_unary(tokens, pos, cond)
+            }
+            _ => frml_parse_power(tokens, pos, cond),
+        }
+    }
+
+    fn frml_parse_power(tokens: &[Token], pos: &mut usize, cond: bool) -> Result<Expr, String> {
+        let base = frml_parse_primary(tokens, pos, cond)?;
+
+        if let Some(Token::Op('^')) = tokens.get(*pos) {
+            *pos += 1;
+            let exponent = frml_parse_unary(tokens, pos, cond)?; // Right associative:  2^3^2 = 2^9
+            return Ok(Expr::BinOp('^', Box::new(base), Box::new(exponent)));
+        }
+
+        Ok(base)
+    }
+
+    fn frml_parse_primary(tokens: &[Token], pos: &mut usize, cond: bool) -> Result<Expr, String> {
+        let token = match tokens.get(*pos) {
+            Some(token) => token.clone(),
+            None => return Err("The formula ends unexpectedly.".to_string()),
+        };
+        *pos += 1;
+
+        match token {
+            Token::Num(num) => Ok(Expr::Num(num)),
+            Token::Var(name) => Ok(Expr::Var(name)),
+            Token::LParen => {
+                let inner = if cond { frml_parse_or(tokens, pos)? } else { frml_parse_sum(tokens, pos, cond)? };
+                match tokens.get(*pos) {
+                    Some(Token::RParen) => {
+                        *pos += 1;
+                        Ok(inner)
+                    }
+                    _ => Err("A closing parenthesis is missing.".to_string()),
+                }
+            }
+            Token::Ident(name) => {
+                match name.as_str() {
+                    "pi" | "π" => return Ok(Expr::Num(std::f64::consts::PI)),
+                    "e" => return Ok(Expr::Num(std::f64::consts::E)),
+                    _ => {}
+                }
+
+                let numargs = match FRML_FUNCTIONS.iter().find(|(fname, _)| *fname == name) {
+                    Some((_, numargs)) => *numargs,
+                    None => return Err(format!("'{}' is not a known function.  Variables must be written between § flags.", name)),
+                };
+
+                if tokens.get(*pos) != Some(&Token::LParen) {
+                    return Err(format!("The function '{}' must be followed by parentheses.", name));
+                }
+                *pos += 1;
+
+                let mut args: Vec<Expr> = vec![frml_parse_sum(tokens, pos, cond)?];
+                while tokens.get(*pos) == Some(&Token::Comma) {
+                    *pos += 1;
+                    args.push(frml_parse_sum(tokens, pos, cond)?);
+                }
+                if tokens.get(*pos) != Some(&Token::RParen) {
+                    return Err("A closing parenthesis is missing.".to_string());
+                }
+                *pos += 1;
+
+                if args.len() != numargs {
+                    return Err(format!("The function '{}' takes {} value(s), but {} were given.", name, numargs, args.len()));
+                }
+
+                Ok(Expr::Func(name, args))
+            }
+            other => Err(format!("Unexpected {} in the formula.", frml_describe(&other))),
+        }
+    }
+
+    /// Describes a token for use in error messages.
+    fn frml_describe(token: &Token) -> String {
+        match token {
+            Token::Num(num) => format!("number {}", num),
+            Token::Var(name) => format!("variable §{}§", name),
+            Token::Ident(name) => format!("word '{}'", name),
+            Token::Op(op) => format!("operator '{}'", op),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+
+    // endregion
+
+    /// Evaluates an expression tree using the current content of the
+    /// Variables in `var_vec`.
+    pub fn frml_eval(expr: &Expr, var_vec: &[Variable]) -> Result<f64, String> {
+        let result = match expr {
+            Expr::Num(num) => *num,
+            Expr::Var(name) => frml_var_value(name, var_vec)?,
+            Expr::Neg(inner) => -frml_eval(inner, var_vec)?,
+            Expr::Not(inner) => frml_truth(frml_eval(inner, var_vec)? == 0.0),
+            Expr::BinOp(op, left, right) => {
+                let lft = frml_eval(left, var_vec)?;
+                let rgt = frml_eval(right, var_vec)?;
+                let equal = (lft - rgt).abs() <= FRML_EQUAL_TOLERANCE * lft.abs().max(rgt.abs()).max(1.0);
+                match op {
+                    '<' => frml_truth(lft < rgt && !equal),
+                    '>' => frml_truth(lft > rgt && !equal),
+                    '≤' => frml_truth(lft < rgt || equal),
+                    '≥' => frml_truth(lft > rgt || equal),
+                    '=' => frml_truth(equal),
+                    '≠' => frml_truth(!equal),
+                    '&' => frml_truth(lft != 0.0 && rgt != 0.0),
+                    '|' => frml_truth(lft != 0.0 || rgt != 0.0),
+                    '+' => lft + rgt,
+                    '-' => lft - rgt,
+                    '*' => lft * rgt,
+                    '/' | '%' if rgt == 0.0 => return Err("The formula divides by zero.".to_string()),
+                    '/' => lft / rgt,
+                    '%' => lft % rgt,
+                    '^' => lft.powf(rgt),
+                    _ => return Err(format!("Unknown operator '{}'.", op)),
+                }
+            }
+            Expr::Func(name, args) => {
+                let mut vals: Vec<f64> = Vec::new();
+                for arg in args.iter() {
+                    vals.push(frml_eval(arg, var_vec)?);
+                }
+                frml_apply_function(name, &vals)?
+            }
+        };
+
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err("The formula does not give a real number for these values.".to_string())
+        }
+    }
+
+    /// Converts true or false to the 1 or 0 that conditions evaluate to.
+    ///
+    fn frml_truth(holds: bool) -> f64 {
+        if holds { 1.0 } else { 0.0 }
+    }
+
+    /// Parses and evaluates a formula in one step.
+    ///
+    pub fn frml_evaluate(formula: &str, var_vec: &[Variable]) -> Result<f64, String> {
+        let expr = frml_parse(formula)?;
+        frml_eval(&expr, var_vec)
+    }
+
+    /// Returns the names of the variables used in an expression tree.
+    ///
+    pub fn frml_variables(expr: &Expr) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        frml_collect_variables(expr, &mut names);
+        names
+    }
+
+    fn frml_collect_variables(expr: &Expr, names: &mut Vec<String>) {
+        match expr {
+            Expr::Num(_) => {}
+            Expr::Var(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::Neg(inner) | Expr::Not(inner) => frml_collect_variables(inner, names),
+            Expr::BinOp(_, left, right) => {
+                frml_collect_variables(left, names);
+                frml_collect_variables(right, names);
+            }
+            Expr::Func(_, args) => {
+                for arg in args.iter() {
+                    frml_collect_variables(arg, names);
+                }
+            }
+        }
+    }
+
+    /// Checks that a formula parses and that every variable it uses is
+    /// one of the Variables in `var_vec`.
+    pub fn frml_check(formula: &str, var_vec: &[Variable]) -> Result<(), String> {
+        frml_check_variables(&frml_parse(formula)?, var_vec)
+    }
+
+    /// Checks that a condition parses and that every variable it uses is
+    /// one of the Variables in `var_vec`.
+    pub fn frml_check_condition(condition: &str, var_vec: &[Variable]) -> Result<(), String> {
+        frml_check_variables(&frml_parse_condition(condition)?, var_vec)
+    }
+
+    fn frml_check_variables(expr: &Expr, var_vec: &[Variable]) -> Result<(), String> {
+        for name in frml_variables(expr) {
+            if vrbl_find_by_name(var_vec, &name).is_none() {
+                return Err(format!("The variable §{}§ is not used in the question.", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up the numeric value of a Variable.  A value with a unit is
+    /// converted to SI base units, so  2 km  is 2000.
+    fn frml_var_value(name: &str, var_vec: &[Variable]) -> Result<f64, String> {
+        let usevar = vrbl_find_by_name(var_vec, name)
+            .ok_or_else(|| format!("The variable §{}§ is not used in the question.", name))?;
+        let num = match &usevar.content {
+            Integer(num) => *num as f64,
+            Floating(num) => *num,
+            Rational(ratio) => ratio.to_f64(),
+            Alphanum(_) | Letter(_) => return Err(format!("The variable §{}§ is not a number.", name)),
+        };
+        Ok(num * frml_var_unit(usevar)?.factor)
+    }
+
+    /// Reads the unit of a Variable.  Variables without one are plain numbers.
+    ///
+    fn frml_var_unit(usevar: &Variable) -> Result<Unit, String> {
+        unit_parse(&usevar.params.unit)
+            .map_err(|err| format!("The unit of §{}§ can't be used.  {}", usevar.fname.trim_end_matches(".vrbl"), err))
+    }
+
+    /// Applies one of the functions in `FRML_FUNCTIONS` to its evaluated arguments.
+    ///
+    fn frml_apply_function(name: &str, vals: &[f64]) -> Result<f64, String> {
+        let xxx = vals[0];
+        let result = match name {
+            "sqrt" if xxx < 0.0 => return Err("The formula takes the square root of a negative number.".to_string()),
+            "sqrt" => xxx.sqrt(),
+            "cbrt" => xxx.cbrt(),
+            "root" => {
+                let nnn = vals[1];
+                if nnn == 0.0 {
+                    return Err("The formula takes a zeroth root.".to_string());
+                }
+                // Odd roots of negative numbers are real:  root(-8, 3) = -2
+                if xxx < 0.0 && nnn.fract() == 0.0 && (nnn as i64) % 2 != 0 {
+                    -(-xxx).powf(1.0 / nnn)
+                } else {
+                    xxx.powf(1.0 / nnn)
+                }
+            }
+            "abs" => xxx.abs(),
+            "sin" => xxx.sin(),
+            "cos" => xxx.cos(),
+            "tan" => xxx.tan(),
+            "asin" => xxx.asin(),
+            "acos" => xxx.acos(),
+            "atan" => xxx.atan(),
+            "deg" => xxx.to_degrees(),
+            "rad" => xxx.to_radians(),
+            "ln" | "log" | "logb" if xxx <= 0.0 => return Err("The formula takes the logarithm of a number that is not positive.".to_string()),
+            "ln" => xxx.ln(),
+            "log" => xxx.log10(),
+            "logb" => xxx.log(vals[1]),
+            "exp" => xxx.exp(),
+            "round" => xxx.round(),
+            "roundto" => {
+                let factor = 10f64.powi(vals[1].round() as i32);
+                (xxx * factor).round() / factor
+            }
+            "floor" => xxx.floor(),
+            "ceil" => xxx.ceil(),
+            "trunc" => xxx.trunc(),
+            "min" => xxx.min(vals[1]),
+            "max" => xxx.max(vals[1]),
+            "gcd" if xxx.fract() != 0.0 || vals[1].fract() != 0.0 => {
+                return Err("The formula takes the gcd of numbers that are not whole.".to_string());
+            }
+            "gcd" => math_gcd(xxx as i64, vals[1] as i64) as f64,
+            "divides" => {  // divides(d, n) -- does d divide n evenly?
+                let (ddd, nnn) = (xxx, vals[1]);
+                frml_truth(ddd != 0.0 && ddd.fract() == 0.0 && nnn.fract() == 0.0 && nnn % ddd == 0.0)
+            }
+            "is_int" => frml_truth(xxx.fract() == 0.0),
+            "is_prime" => frml_truth(xxx.fract() == 0.0 && math_is_prime(xxx as i64)),
+            "is_square" => frml_truth(xxx.fract() == 0.0 && math_is_perfect_square(xxx as i64)),
+            _ => return Err(format!("'{}' is not a known function.", name)),
+        };
+
+        Ok(result)
+    }
+
+    // region Exact fraction arithmetic
+
+    /// Evaluates an expression tree exactly, as a fraction.  Returns `None`
+    /// if any part of it can't be done exactly -- a square root, a variable
+    /// that isn't a number or has a unit, a result too large for an i64, etc.  Decimal
+    /// numbers & variables are taken at exactly the value they are written as.
+    pub fn frml_eval_exact(expr: &Expr, var_vec: &[Variable]) -> Option<Ratio> {
+        match expr {
+            Expr::Num(num) => math_ratio_from_f64(*num),
+            Expr::Var(name) => match &vrbl_find_by_name(var_vec, name).filter(|usevar| usevar.params.unit.trim().is_empty())?.content {
+                Integer(num) => Ratio::new(*num, 1),
+                Floating(num) => math_ratio_from_f64(*num),
+                Rational(ratio) => Some(*ratio),
+                Alphanum(_) | Letter(_) => None,
+            },
+            Expr::Neg(inner) => {
+                let ratio = frml_eval_exact(inner, var_vec)?;
+                Ratio::new(ratio.num.checked_neg()?, ratio.den)
+            }
+            Expr::BinOp(op, left, right) => {
+                let lft = frml_eval_exact(left, var_vec)?;
+                let rgt = frml_eval_exact(right, var_vec)?;
+                match op {
+                    '+' => lft.checked_add(&rgt),
+                    '-' => lft.checked_sub(&rgt),
+                    '*' => lft.checked_mul(&rgt),
+                    '/' => lft.checked_div(&rgt),
+                    '^' if rgt.den == 1 && rgt.num.abs() <= 64 => lft.checked_pow(rgt.num),
+                    _ => None,
+                }
+            }
+            Expr::Func(name, args) => {
+                let vals: Vec<Ratio> = args.iter().map(|arg| frml_eval_exact(arg, var_vec)).collect::<Option<_>>()?;
+                let xxx = vals[0];
+                match name.as_str() {
+                    "abs" => Ratio::new(xxx.num.checked_abs()?, xxx.den),
+                    "floor" => Ratio::new(xxx.num.checked_div_euclid(xxx.den)?, 1),
+                    "ceil" => Ratio::new(xxx.num.checked_neg()?.checked_div_euclid(xxx.den)?.checked_neg()?, 1),
+                    "trunc" => Ratio::new(xxx.num.checked_div(xxx.den)?, 1),
+                    "min" => Some(if xxx.to_f64() <= vals[1].to_f64() { xxx } else { vals[1] }),
+                    "max" => Some(if xxx.to_f64() >= vals[1].to_f64() { xxx } else { vals[1] }),
+                    _ => None,
+                }
+            }
+            Expr::Not(_) => None,
+        }
+    }
+
+    /// Returns the format of the first fraction Variable a formula uses, or
+    /// `None` if it doesn't use any.
+    pub fn frml_fraction_format(expr: &Expr, var_vec: &[Variable]) -> Option<FracFormat> {
+        frml_variables(expr).iter()
+            .filter_map(|name| vrbl_find_by_name(var_vec, name))
+            .find(|usevar| matches!(usevar.content, Rational(_)))
+            .map(|usevar| usevar.params.frac_format)
+    }
+
+    /// Evaluates a formula and formats the result for display.  When the
+    /// formula uses a fraction Variable, the answer is worked out exactly and
+    /// shown as a reduced fraction in that Variable's format, if it can be.
+    /// When it uses Decimals with significant figures, the answer keeps the
+    /// fewest significant figures of any of them.  When it uses money, the
+    /// answer is money too.  Digits are grouped if any Variable used groups them.
+    /// An answer with a unit is shown in `unit`, or in one picked by
+    /// `frml_display_unit()` if `unit` is blank.  Without significant figures,
+    /// it is rounded to the most decimal places of the Decimals it uses.
+    pub fn frml_evaluate_text(formula: &str, var_vec: &[Variable], unit: &str, locale: &NumLocale) -> Result<String, String> {
+        let expr = frml_parse(formula)?;
+        let (label, factor) = frml_display_unit(&expr, var_vec, unit)?;
+        let value = frml_eval(&expr, var_vec)? / factor;
+        if let Some(style) = frml_fraction_format(&expr, var_vec)
+            && let Some(ratio) = frml_eval_exact(&expr, var_vec)
+        {
+            return Ok(math_format_ratio(&ratio.reduced(), &style));
+        }
+
+        let used: Vec<&Variable> = frml_variables(&expr).iter()
+            .filter_map(|name| vrbl_find_by_name(var_vec, name))
+            .collect();
+        let money = used.iter().find(|usevar| usevar.params.num_currency);
+        let numtxt = if let Some((sig_figs, notation)) = frml_sig_fig_format(&expr, var_vec) {
+            // Without a set number of figures, hide floating point noise as frml_format_answer() does.
+            let value = if sig_figs == 0 { math_round_to_sig_figs(value, 12) } else { value };
+            math_format_sig_figs(value, sig_figs, &notation)
+        } else if let Some(money) = money
+            && matches!(money.content, Floating(_))
+        {
+            format!("{:.*}", money.params.num_dcml_places, value)  // e.g.  12.50  rather than  12.5
+        } else if !label.is_empty() {
+            // Converting units gives long decimals, e.g. 13.3333333333 m/s, so keep the precision of the values used.
+            let places = used.iter()
+                .filter(|usevar| matches!(usevar.content, Floating(_)))
+                .map(|usevar| usevar.params.num_dcml_places)
+                .max()
+                .unwrap_or(0);
+            frml_format_answer(math_round_to_place_f64(&value, places))
+        } else {
+            frml_format_answer(value)
+        };
+
+        let numtxt = math_format_number(&numtxt, used.iter().any(|usevar| usevar.params.num_comma_frmttd), locale);
+        let numtxt = if money.is_some() { math_format_currency(&numtxt, locale) } else { numtxt };
+        Ok(if label.is_empty() { numtxt } else { format!("{} {}", numtxt, label) })
+    }
+
+    // region Units
+
+    /// Works out the dimension of an expression's result from the units of
+    /// its Variables, e.g.  §d§ / §t§  with d in km & t in min gives m/s.
+    /// Fails if the units don't agree, e.g. adding metres to seconds.
+    pub fn frml_dims(expr: &Expr, var_vec: &[Variable]) -> Result<Dim, String> {
+        match expr {
+            Expr::Num(_) => Ok(Dim::NONE),
+            Expr::Var(name) => match vrbl_find_by_name(var_vec, name) {
+                Some(usevar) => Ok(frml_var_unit(usevar)?.dim),
+                None => Err(format!("The variable §{}§ is not used in the question.", name)),
+            },
+            Expr::Neg(inner) => frml_dims(inner, var_vec),
+            Expr::Not(inner) => frml_dims(inner, var_vec).map(|_| Dim::NONE),
+            Expr::BinOp(op, left, right) => {
+                let lft = frml_dims(left, var_vec)?;
+                let rgt = frml_dims(right, var_vec)?;
+                match op {
+                    '+' | '-' | '%' if lft != rgt => Err(format!("{} and {} can't be added or subtracted.", lft, rgt)),
+                    '+' | '-' | '%' => Ok(lft),
+                    '<' | '>' | '≤' | '≥' | '=' | '≠' if lft != rgt => Err(format!("{} and {} can't be compared.", lft, rgt)),
+                    '*' => Ok(lft.times(&rgt)),
+                    '/' => Ok(lft.over(&rgt)),
+                    '^' if !rgt.is_none() => Err(format!("A power can't have a unit, but this one is in {}.", rgt)),
+                    '^' if lft.is_none() => Ok(Dim::NONE),
+                    '^' => match frml_whole_constant(right) {
+                        Some(power) => Ok(lft.pow(power)),
+                        None => Err(format!("A value in {} can only be raised to a whole number written in the formula.", lft)),
+                    },
+                    _ => Ok(Dim::NONE),  // Comparisons & logic give 1 or 0.
+                }
+            }
+            Expr::Func(name, args) => {
+                let dims: Vec<Dim> = args.iter().map(|arg| frml_dims(arg, var_vec)).collect::<Result<_, _>>()?;
+                let root = |nth: Option<i32>| match nth.and_then(|nth| dims[0].root(nth)) {
+                    Some(dim) => Ok(dim),
+                    None => Err(format!("{}() of a value in {} has no unit.", name, dims[0])),
+                };
+                match name.as_str() {
+                    "sqrt" => root(Some(2)),
+                    "cbrt" => root(Some(3)),
+                    "root" if dims[1].is_none() => root(frml_whole_constant(&args[1])),
+                    "abs" | "round" | "floor" | "ceil" | "trunc" => Ok(dims[0]),
+                    "roundto" if dims[1].is_none() => Ok(dims[0]),
+                    "min" | "max" if dims[0] == dims[1] => Ok(dims[0]),
+                    "min" | "max" => Err(format!("{}() can't compare {} and {}.", name, dims[0], dims[1])),
+                    _ => match dims.iter().find(|dim| !dim.is_none()) {
+                        Some(dim) => Err(format!("{}() only works on plain numbers, not a value in {}.", name, dim)),
+                        None => Ok(Dim::NONE),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Returns the value of an expression that is a whole number written
+    /// out in the formula, e.g. the  2  of  §t§^2.
+    fn frml_whole_constant(expr: &Expr) -> Option<i32> {
+        if !frml_variables(expr).is_empty() {
+            return None;
+        }
+        let value = frml_eval(expr, &[]).ok()?;
+        (value.fract() == 0.0 && value.abs() <= i32::MAX as f64).then_some(value as i32)
+    }
+
+    /// Picks the unit a formula's result is shown in & the factor that converts
+    /// SI base units to it.  A given `unit` must measure the same thing as the
+    /// result.  If `unit` is blank, the unit of the first Variable that does is
+    /// used, so  §d§ * 2  stays in km;  failing that, SI base units.  A result
+    /// without a unit gives a blank label.
+    pub fn frml_display_unit(expr: &Expr, var_vec: &[Variable], unit: &str) -> Result<(String, f64), String> {
+        let dim = frml_dims(expr, var_vec)?;
+        if !unit.trim().is_empty() {
+            let target = unit_parse(unit)?;
+            if target.dim != dim {
+                return Err(format!("The formula gives {}, which can't be written in {}.", dim, unit.trim()));
+            }
+            return Ok((unit.trim().to_string(), target.factor));
+        }
+        if dim.is_none() {
+            return Ok((String::new(), 1.0));
+        }
+        let matching = frml_variables(expr).iter()
+            .filter_map(|name| vrbl_find_by_name(var_vec, name))
+            .filter_map(|usevar| Some((usevar.params.unit.trim(), frml_var_unit(usevar).ok()?)))
+            .find(|(_, varunit)| varunit.dim == dim);
+        match matching {
+            Some((label, varunit)) => Ok((label.to_string(), varunit.factor)),
+            None => Ok((dim.to_string(), 1.0)),
+        }
+    }
+
+    /// Parses and evaluates a formula, giving the result in `unit` (see
+    /// `frml_display_unit()`) along with the unit it is in.
+    pub fn frml_evaluate_in_unit(formula: &str, var_vec: &[Variable], unit: &str) -> Result<(f64, String), String> {
+        let expr = frml_parse(formula)?;
+        let (label, factor) = frml_display_unit(&expr, var_vec, unit)?;
+        Ok((frml_eval(&expr, var_vec)? / factor, label))
+    }
+
+    /// Checks that the units in a formula agree with each other and, if
+    /// `unit` isn't blank, with it.
+    pub fn frml_check_units(formula: &str, var_vec: &[Variable], unit: &str) -> Result<(), String> {
+        frml_display_unit(&frml_parse(formula)?, var_vec, unit).map(|_| ())
+    }
+
+    /// Checks that the units in a condition agree, e.g. that  §d§ > §t§
+    /// doesn't compare a distance with a time.
+    pub fn frml_check_condition_units(condition: &str, var_vec: &[Variable]) -> Result<(), String> {
+        frml_dims(&frml_parse_condition(condition)?, var_vec).map(|_| ())
+    }
+
+    // endregion
+
+    // endregion
+
+    /// Returns the significant figures & notation an answer should use:  the
+    /// fewest significant figures of the Decimal Variables the formula uses,
+    /// and the notation of the first of them that sets one.  Returns `None`
+    /// if none of them use significant figures or a special notation.
+    pub fn frml_sig_fig_format(expr: &Expr, var_vec: &[Variable]) -> Option<(usize, Notation)> {
+        let decimals: Vec<&VarPrmtrs> = frml_variables(expr).iter()
+            .filter_map(|name| vrbl_find_by_name(var_vec, name))
+            .filter(|usevar| matches!(usevar.content, Floating(_)))
+            .map(|usevar| &usevar.params)
+            .filter(|prms| prms.num_sig_figs > 0 || prms.num_notation != Notation::Standard)
+            .collect();
+        if decimals.is_empty() {
+            return None;
+        }
+        let sig_figs = decimals.iter().map(|prms| prms.num_sig_figs).filter(|sig_figs| *sig_figs > 0).min().unwrap_or(0);
+        let notation = decimals.iter().map(|prms| prms.num_notation).find(|notation| *notation != Notation::Standard);
+        Some((sig_figs, notation.unwrap_or_default()))
+    }
+
+    /// Formats a calculated answer for display, dropping any trailing zeros
+    /// left over from floating point arithmetic.
+    pub fn frml_format_answer(value: f64) -> String {
+        // Keep 12 significant figures to hide floating point noise like 0.30000000000000004.
+        let rounded: f64 = format!("{:.11e}", value).parse().unwrap_or(value);
+        if rounded == 0.0 {
+            "0".to_string()
+        } else {
+            rounded.to_string()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn decimal_var(name: &str, num: f64, sig_figs: usize, notation: Notation) -> Variable {
+            let mut var1 = Variable::new();
+            var1.fname = name.to_string();
+            var1.var_type = "Decimals".to_string();
+            var1.content = Floating(num);
+            var1.params.is_int = false;
+            var1.params.is_float = true;
+            var1.params.num_sig_figs = sig_figs;
+            var1.params.num_notation = notation;
+            var1
+        }
+
+        #[test]
+        fn answer_keeps_fewest_sig_figs() {
+            let var_vec = vec![
+                decimal_var("mass", 2.50, 3, Notation::Standard),
+                decimal_var("accel", 1.2, 2, Notation::Standard),
+            ];
+            assert_eq!(frml_evaluate_text("§mass§ * §accel§", &var_vec, "", &NumLocale::new()), Ok("3.0".to_string()));
+            assert_eq!(frml_evaluate_text("§mass§ + 0", &var_vec, "", &NumLocale::new()), Ok("2.50".to_string()));
+        }
+
+        #[test]
+        fn answer_uses_notation() {
+            let var_vec = vec![
+                decimal_var("moles", 2.0, 3, Notation::Standard),
+                decimal_var("avogadro", 6.022e23, 4, Notation::Scientific),
+            ];
+            assert_eq!(frml_evaluate_text("§moles§ * §avogadro§", &var_vec, "", &NumLocale::new()), Ok("1.20 × 10^24".to_string()));
+        }
+
+        #[test]
+        fn answer_without_sig_figs_is_unchanged() {
+            let var_vec = vec![decimal_var("aaa", 0.1, 0, Notation::Standard)];
+            assert_eq!(frml_evaluate_text("§aaa§ + 0.2", &var_vec, "", &NumLocale::new()), Ok("0.3".to_string()));
+        }
+
+        #[test]
+        fn answer_uses_locale_and_currency() {
+            let mut price = decimal_var("price", 1234.5, 0, Notation::Standard);
+            price.params.num_dcml_places = 2;
+            price.params.num_comma_frmttd = true;
+            price.params.num_currency = true;
+            let locale = NumLocale { style: NumStyle::European, currency: "€".to_string(), currency_after: true };
+            assert_eq!(frml_evaluate_text("§price§ * 2", &[price], "", &locale), Ok("2.469,00 €".to_string()));
+        }
+
+        #[test]
+        fn answer_converts_units() {
+            let mut dist = decimal_var("dist", 12.0, 0, Notation::Standard);
+            dist.params.unit = "km".to_string();
+            let mut time = decimal_var("time", 10.0, 0, Notation::Standard);
+            time.params.unit = "min".to_string();
+            let var_vec = vec![dist, time];
+            let locale = NumLocale::new();
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "", &locale), Ok("20 m/s".to_string()));
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "km/h", &locale), Ok("72 km/h".to_string()));
+            assert_eq!(frml_evaluate_text("§dist§ * 2", &var_vec, "", &locale), Ok("24 km".to_string()));
+        }
+
+        #[test]
+        fn converted_answers_are_rounded() {
+            let mut dist = decimal_var("dist", 12.0, 0, Notation::Standard);
+            dist.params.unit = "km".to_string();
+            dist.params.num_dcml_places = 1;
+            let mut time = decimal_var("time", 15.0, 0, Notation::Standard);
+            time.params.unit = "min".to_string();
+            let var_vec = vec![dist, time];
+            let locale = NumLocale::new();
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "m/s", &locale), Ok("13.3 m/s".to_string()));
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "km/h", &locale), Ok("48 km/h".to_string()));
+
+            let mut sig_dist = var_vec[0].clone();
+            sig_dist.params.num_sig_figs = 3;
+            let var_vec = vec![sig_dist, var_vec[1].clone()];
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "m/s", &locale), Ok("13.3 m/s".to_string()));
+            assert_eq!(frml_evaluate_text("§dist§ / §time§", &var_vec, "km/h", &locale), Ok("48.0 km/h".to_string()));
+        }
+
+        #[test]
+        fn mismatched_units_are_errors() {
+            let mut dist = decimal_var("dist", 12.0, 0, Notation::Standard);
+            dist.params.unit = "km".to_string();
+            let mut time = decimal_var("time", 10.0, 0, Notation::Standard);
+            time.params.unit = "min".to_string();
+            let var_vec = vec![dist, time];
+            assert!(frml_check_units("§dist§ + §time§", &var_vec, "").is_err());
+            assert!(frml_check_units("§dist§ / §time§", &var_vec, "kg").is_err());
+            assert!(frml_check_condition_units("§dist§ > §time§", &var_vec).is_err());
+            assert!(frml_check_units("sqrt(§dist§ * §dist§)", &var_vec, "m").is_ok());
+        }
+
+        #[test]
+        fn operators_follow_precedence() {
+            assert_eq!(frml_evaluate("2 + 3 * 4", &[]), Ok(14.0));
+            assert_eq!(frml_evaluate("(2 + 3) * 4", &[]), Ok(20.0));
+            assert_eq!(frml_evaluate("10 - 4 - 3", &[]), Ok(3.0));
+            assert_eq!(frml_evaluate("2 * 3^2", &[]), Ok(18.0));
+            assert_eq!(frml_evaluate("-2^2", &[]), Ok(-4.0));
+            assert_eq!(frml_evaluate("(-2)^2", &[]), Ok(4.0));
+            assert_eq!(frml_evaluate("2^3^2", &[]), Ok(512.0));
+            assert_eq!(frml_evaluate("2^-1", &[]), Ok(0.5));
+        }
+
+        #[test]
+        fn implied_multiplication_needs_a_variable_or_parenthesis() {
+            let var_vec = vec![decimal_var("x", 3.0, 0, Notation::Standard)];
+            assert_eq!(frml_evaluate("2§x§", &var_vec), Ok(6.0));
+            assert_eq!(frml_evaluate("2(§x§ + 1)", &var_vec), Ok(8.0));
+            assert_eq!(frml_evaluate("2sqrt(9)", &var_vec), Ok(6.0));
+            assert!(frml_parse("2 3").is_err());
+            assert!(frml_parse("2e").is_err());
+            assert!(frml_parse("§x§ 4").is_err());
+        }
+
+        #[test]
+        fn bad_formulas_are_errors() {
+            assert!(frml_evaluate("5 / 0", &[]).is_err());
+            assert!(frml_evaluate("5 % (2 - 2)", &[]).is_err());
+            assert!(frml_parse("frob(2)").is_err());
+            assert!(frml_parse("sqrt(2, 3)").is_err());
+            assert!(frml_parse("§x + 1").is_err());
+            assert!(frml_parse("(2 + 3").is_err());
+            assert!(frml_parse("").is_err());
+        }
+
+        #[test]
+        fn exact_rounding_functions_catch_overflow() {
+            let exact = |formula: &str| frml_eval_exact(&frml_parse(formula).unwrap(), &[]);
+            assert_eq!(exact("ceil(-7/2)"), Ratio::new(-3, 1));
+            assert_eq!(exact("floor(-7/2)"), Ratio::new(-4, 1));
+            assert_eq!(exact("trunc(-7/2)"), Ratio::new(-3, 1));
+            let mut lowest = decimal_var("lowest", 0.0, 0, Notation::Standard);
+            lowest.content = Integer(i64::MIN);
+            let var_vec = vec![lowest];
+            assert_eq!(frml_eval_exact(&frml_parse("§lowest§").unwrap(), &var_vec), Ratio::new(i64::MIN, 1));
+            // Left to the decimal calculation instead of panicking.
+            assert_eq!(frml_eval_exact(&frml_parse("ceil(§lowest§)").unwrap(), &var_vec), None);
+        }
+
+        #[test]
+        fn conditions_give_one_or_zero() {
+            let holds = |condition: &str| frml_eval(&frml_parse_condition(condition).unwrap(), &[]).unwrap();
+            assert_eq!(holds("0.1 + 0.2 == 0.3"), 1.0);
+            assert_eq!(holds("3 != 3"), 0.0);
+            assert_eq!(holds("2 < 3 and 3 <= 3"), 1.0);
+            assert_eq!(holds("2 > 3 or 4 >= 5"), 0.0);
+            assert_eq!(holds("not 2 > 3 && !(1 == 2)"), 1.0);
+            assert_eq!(holds("divides(3, 12) || is_prime(9)"), 1.0);
+            assert!(frml_parse("2 < 3").is_err());  // Comparisons are only for conditions.
+            assert!(frml_parse("(2 < 3)").is_err());
+            assert!(frml_parse("(§a§ > §b§) * 5").is_err());
+            assert!(frml_parse("not (2 > 3)").is_err());
+            assert_eq!(holds("(2 < 3) and (1 + 1 == 2)"), 1.0);
+        }
+    }
+
+} // End   formulas   module
+
+/// A minimal PDF writer -- just enough for worksheets & answer keys.  Pages
+/// hold lines of text in the standard Helvetica fonts, which every PDF reader
+/// has, so no fonts need to be embedded.
+///
+pub mod pdf {
+
+    // region Struct section
+
+    /// The paper sizes a PDF can be laid out on.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Paper {
+        Letter,
+        A4,
+    }
+
+    impl Paper {
+        /// Every paper size, in the order they are offered to the user.
+        pub const ALL: [Paper; 2] = [Paper::Letter, Paper::A4];
+
+        /// Returns the name shown to the user for this paper size.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                Paper::Letter => "Letter",
+                Paper::A4 => "A4",
+            }
+        }
+
+        /// Returns the width & height of the paper in points (1/72 inch).
+        ///
+        pub fn size(&self) -> (f64, f64) {
+            match self {
+                Paper::Letter => (612.0, 792.0),
+                Paper::A4 => (595.0, 842.0),
+            }
+        }
+    }
+
+    /// The fonts a PDF can use.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PdfFont {
+        Regular,
+        Bold,
+        Italic,
+    }
+
+    impl PdfFont {
+        /// The PDF names of the fonts, in the order of the enum.
+        const BASE_FONTS: [&'static str; 3] = ["Helvetica", "Helvetica-Bold", "Helvetica-Oblique"];
+
+        /// Returns the name the page content uses for this font.
+        ///
+        fn resource(&self) -> &'static str {
+            match self {
+                PdfFont::Regular => "F1",
+                PdfFont::Bold => "F2",
+                PdfFont::Italic => "F3",
+            }
+        }
+    }
+
+    /// A PDF being built up page by page.  Coordinates are in points from
+    /// the bottom left corner of the page.
+    #[derive(Debug, Clone)]
+    pub struct PdfDoc {
+        pub paper: Paper,
+        pages: Vec<String>,  // The content stream of each page.
+    }
+
+    impl PdfDoc {
+        /// Starts an empty PDF on the given paper.
+        pub fn new(paper: Paper) -> PdfDoc {
+            Self { paper, pages: Vec::new() }
+        }
+    } // ----------  End PdfDoc impl ----------
+
+    // endregion
+
+    /// Widths of the printable ASCII characters in Helvetica, in thousandths
+    /// of the font size, starting with the space.
+    const HELVETICA_WIDTHS: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+        556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+        1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+        667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+        333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+        556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+    ];
+
+    /// Starts a new, blank page.  Text is always added to the last page.
+    ///
+    pub fn pdf_new_page(doc: &mut PdfDoc) {
+        doc.pages.push(String::new());
+    }
+
+    /// Returns the number of pages in the PDF so far.
+    ///
+    pub fn pdf_page_count(doc: &PdfDoc) -> usize {
+        doc.pages.len()
+    }
+
+    /// Writes a line of text on the current page with its baseline starting
+    /// at (x, y).  A page is started if there isn't one yet.
+    pub fn pdf_text(doc: &mut PdfDoc, xpos: f64, ypos: f64, size: f64, font: PdfFont, text: &str) {
+        if doc.pages.is_empty() {
+            pdf_new_page(doc);
+        }
+        let page = doc.pages.last_mut().unwrap();
+        page.push_str(&format!("BT /{} {:.1} Tf {:.2} {:.2} Td ({}) Tj ET\n", font.resource(), size, xpos, ypos, pdf_encode(text)));
+    }
+
+    /// Returns the width of a line of text in points.  Bold text is a
+    /// little wider, so it is measured generously.
+    pub fn pdf_text_width(text: &str, size: f64, font: PdfFont) -> f64 {
+        let units: u32 = text.chars()
+            .map(|ch| match ch as u32 {
+                32..=126 => HELVETICA_WIDTHS[ch as usize - 32] as u32,
+                _ => 556,
+            })
+            .sum();
+        let scale = if font == PdfFont::Bold { 1.1 } else { 1.0 };
+        units as f64 * size * scale / 1000.0
+    }
+
+    /// Breaks text into lines no wider than `width` points, breaking between
+    /// words where it can.  A word too long for a line is split.  Line breaks
+    /// in the text are kept.
+    pub fn pdf_wrap(text: &str, size: f64, font: PdfFont, width: f64) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let trial = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+                if pdf_text_width(&trial, size, font) <= width {
+                    line = trial;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                for ch in word.chars() {  // Only splits words that don't fit on a line by themselves.
+                    if !line.is_empty() && pdf_text_width(&format!("{}{}", line, ch), size, font) > width {
+                        lines.push(std::mem::take(&mut line));
+                    }
+                    line.push(ch);
+                }
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines
+    }
+
+    /// Writes text as a PDF string in the WinAnsi encoding, escaping the
+    /// characters PDF treats specially.  Characters the encoding lacks
+    /// become '?'.
+    fn pdf_encode(text: &str) -> String {
+        let mut out = String::new();
+        for ch in text.chars() {
+            let code: u8 = match ch {
+                '(' | ')' | '\\' => {
+                    out.push('\\');
+                    ch as u8
+                }
+                ' '..='~' => ch as u8,
+                '\u{A0}'..='\u{FF}' => ch as u32 as u8,  // Latin-1 matches WinAnsi here.
+                '\u{2009}' | '\u{202F}' => b' ',  // Thin spaces used for digit grouping.
+                '€' => 0x80,
+                '‘' => 0x91,
+                '’' => 0x92,
+                '“' => 0x93,
+                '”' => 0x94,
+                '•' => 0x95,
+                '–' => 0x96,
+                '—' => 0x97,
+                '…' => 0x85,
+                '−' => b'-',
+                _ => b'?',
+            };
+            if code.is_ascii() {
+                out.push(code as char);
+            } else {
+                out.push_str(&format!("\\{:03o}", code));  // Keeps the file itself ASCII.
+            }
         }
+        out
+    }
 
-        // endregion
-        
-        list_save_as_json(list, path.as_str());
+    /// Finishes the PDF and returns the bytes of the file.  A PDF with no
+    /// pages gets one blank page, since readers reject an empty one.
+    pub fn pdf_finish(doc: &PdfDoc) -> Vec<u8> {
+        let (width, height) = doc.paper.size();
+        let pages: Vec<String> = if doc.pages.is_empty() { vec![String::new()] } else { doc.pages.clone() };
+
+        // Objects 1 & 2 are the catalog & page tree, 3-5 the fonts, then a
+        // page object & its content for each page.
+        let first_page = 3 + PdfFont::BASE_FONTS.len();
+        let mut objects: Vec<String> = Vec::new();
+        objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+        let kids: Vec<String> = (0..pages.len()).map(|idx| format!("{} 0 R", first_page + idx * 2)).collect();
+        objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()));
+        for basefont in PdfFont::BASE_FONTS.iter() {
+            objects.push(format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", basefont));
+        }
+        for (idx, content) in pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                width, height, first_page + idx * 2 + 1
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content));
+        }
 
-        path
+        let mut out = String::from("%PDF-1.4\n");
+        let mut offsets: Vec<usize> = Vec::new();
+        for (idx, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.push_str(&format!("{} 0 obj\n{}\nendobj\n", idx + 1, object));
+        }
+        let xref_at = out.len();
+        out.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+        for offset in offsets.iter() {
+            out.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        out.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_at));
+        out.into_bytes()
     }
 
-    /// Save a list in json format.
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn wrap_breaks_between_words() {
+            let lines = pdf_wrap("The quick brown fox jumps over the lazy dog", 12.0, PdfFont::Regular, 100.0);
+            assert!(lines.len() > 1);
+            assert!(lines.iter().all(|line| pdf_text_width(line, 12.0, PdfFont::Regular) <= 100.0));
+            assert_eq!(lines.join(" "), "The quick brown fox jumps over the lazy dog");
+        }
+
+        #[test]
+        fn finish_writes_valid_cross_references() {
+            let mut doc = PdfDoc::new(Paper::Letter);
+            pdf_text(&mut doc, 72.0, 700.0, 12.0, PdfFont::Regular, "Cost (in €): 6 × 7");
+            pdf_new_page(&mut doc);
+            let bytes = pdf_finish(&doc);
+            let text = String::from_utf8(bytes).unwrap();
+            assert!(text.starts_with("%PDF-1.4"));
+            assert!(text.contains("/Count 2"));
+            assert!(text.contains("(Cost \\(in \\200\\): 6 \\327 7) Tj"));
+
+            // Every offset in the table must point at the start of its object.
+            let xref_at: usize = text.lines().rev().nth(1).unwrap().parse().unwrap();
+            for (idx, line) in text[xref_at..].lines().skip(3).take_while(|line| line.ends_with(" n ")).enumerate() {
+                let offset: usize = line[..10].parse().unwrap();
+                assert!(text[offset..].starts_with(&format!("{} 0 obj", idx + 1)));
+            }
+        }
+    }
+
+} // End   pdf   module
+
+/// Functions for turning a Bank into printable worksheets and answer keys.
+///
+pub mod export {
+    use crate::banks::{bnk_recalc_seeded, Bank};
+    use crate::errors::QbcError;
+    use crate::math_functions::NumLocale;
+    use crate::pdf::*;
+    use crate::questions::{qst_answer_text, qst_choice_label, qst_flag_value, qst_render_choices, qst_render_text, Question, QuestionKind};
+    use crate::variable::Variable;
+    #[cfg(feature = "gui")]
+    pub use gui::*;
+
+    // region Struct section
+
+    /// How a PDF worksheet is laid out.
     ///
-    pub fn list_save_as_json(list: &List, fname: &str) {
-        let list_as_json = serde_json::to_string(list).unwrap();
+    #[derive(Debug, Clone)]
+    pub struct PdfLayout {
+        pub paper: Paper,
+        pub font_size: f64,
+        pub work_lines: usize,  // Blank lines left after each question for the student's work.
+    }
+
+    impl Default for PdfLayout {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
 
-        let mut file = File::create(fname).expect("Could not create file!");
+    impl PdfLayout {
+        /// Initializes a layout for Letter paper with room for work.
+        pub fn new() -> PdfLayout {
+            Self {
+                paper: Paper::Letter,
+                font_size: 12.0,
+                work_lines: 4,
+            }
+        }
+    } // ----------  End PdfLayout impl ----------
 
-        file.write_all(list_as_json.as_bytes())
-            .expect("Cannot write to the file!");
+    /// One line of a PDF page:  pieces of text, each at its own distance
+    /// from the left margin, all in the same size.
+    struct PdfRow {
+        pieces: Vec<(f64, String, PdfFont)>,
+        size: f64,
     }
 
-    /// Check that a list contains the correct type of data.
+    /// Keeps track of where the next line goes while a version is written.
     ///
-    pub fn list_check_typematch(uselist: &List, typech: &str) -> bool {
-        if uselist.typechoice.as_str() != typech {
-            println!("\n The data type of that list does not match your typechoice. \n");
-            println!("Please choose a different list file. \n");
-            false
-        } else {
-            true
+    struct PdfCursor<'a> {
+        doc: &'a mut PdfDoc,
+        layout: &'a PdfLayout,
+        footer: String,  // Printed at the bottom of every page, followed by the page number.
+        page: usize,  // Page number within this version.
+        ypos: f64,
+    }
+
+    /// The document classes a LaTeX worksheet can use.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum LatexClass {
+        Exam,     // The exam class, with its own question & choice commands.
+        Article,  // A plain article, using enumerate.
+    }
+
+    impl LatexClass {
+        /// Every class, in the order they are offered to the user.
+        pub const ALL: [LatexClass; 2] = [LatexClass::Exam, LatexClass::Article];
+
+        /// Returns the LaTeX name of the class.
+        ///
+        pub fn name(&self) -> &'static str {
+            match self {
+                LatexClass::Exam => "exam",
+                LatexClass::Article => "article",
+            }
         }
     }
 
-} // End  lists module
+    /// How a LaTeX worksheet is written.
+    ///
+    #[derive(Debug, Clone)]
+    pub struct LatexOptions {
+        pub class: LatexClass,
+        pub answers: bool,  // Follow each question with an  \answer{}.
+        pub show_answers: bool,  // Print the answers, making the document an answer key.
+        pub work_lines: usize,  // Blank lines left after each question for the student's work.
+    }
 
-/// Functions for use in creating menus.
-///
-pub mod menus {
-    use crate::misc::check_for_bank_loaded;
-    use crate::{banks::*, lists::*, questions::*, variable::*};
-    use fltk::enums::{Color, Shortcut};
-    use fltk::prelude::{MenuExt, WidgetBase, WidgetExt};
-    use fltk::{app::quit, menu, window::Window};
+    impl Default for LatexOptions {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
 
-    /// Create a menubar for the primary window.
-    pub fn qbnk_menubar(primwin: &mut Window) -> menu::MenuBar {
-        // todo: `primwin` is in the global Widgets variable.  Access it
-        //          there rather than passing it to this function.
+    impl LatexOptions {
+        /// Initializes options for an exam-class worksheet with hidden answers.
+        pub fn new() -> LatexOptions {
+            Self {
+                class: LatexClass::Exam,
+                answers: true,
+                show_answers: false,
+                work_lines: 4,
+            }
+        }
+    } // ----------  End LatexOptions impl ----------
 
-        let mut menubar = menu::MenuBar::new(0, 0, primwin.width(), 40, "");
+    /// The space around the text on each page, in points.
+    const EXPORT_PDF_MARGIN: f64 = 54.0;
 
-        //region File section
+    // endregion
 
-        menubar.add(
-            "File/Print/Question Bank\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Printing a new Question Bank."),
-        );
-        menubar.add(
-            "File/Print/Question\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Printing a new Question."),
-        );
-        menubar.add(
-            "File/Print/Variable\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Printing a new Variable."),
-        );
-        menubar.add(
-            "File/Print/List\t", // Where does versioning come in?
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Printing a new List."),
-        );
-        menubar.add(
-            "File/Save\t", // Save always focuses on the Question Bank.
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Saving a Question Bank."),
-        );
-        menubar.add(
-            "File/Save-as\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Saving a Question Bank with a new name."),
-        );
+    /// Converts a zero-based version number into a version label:
+    /// A, B, C, ... Z, AA, AB, ...
+    pub fn export_version_label(version: usize) -> String {
+        let mut label = String::new();
+        let mut num = version + 1;
+        while num > 0 {
+            let rem = (num - 1) % 26;
+            label.insert(0, (b'A' + rem as u8) as char);
+            num = (num - 1) / 26;
+        }
+        label
+    }
 
-        let quit_idx = menubar.add(
-            "File/Quit\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| {
-                quit();
-            },
-        );
-        menubar.at(quit_idx).unwrap().set_label_color(Color::Red);
-        //endregion
+    /// Converts a version label back into a zero-based version number:
+    /// A is 0, Z is 25, AA is 26, ...  Returns `None` if it isn't a label.
+    pub fn export_version_index(label: &str) -> Option<usize> {
+        let label = label.trim().to_uppercase();
+        if label.is_empty() || !label.chars().all(|ch| ch.is_ascii_uppercase()) {
+            return None;
+        }
+        let num = label.bytes().fold(0usize, |num, ch| num * 26 + (ch - b'A') as usize + 1);
+        Some(num - 1)
+    }
 
-        //region Bank section
+    /// Makes one version of a worksheet from a Bank by recalculating its
+    /// variables from the seed & version number.  Returns the recalculated
+    /// Bank along with the errors for any variables that could not be recalculated.
+    pub fn export_make_version(usebank: &Bank, seed: u64, version: usize) -> (Bank, Vec<QbcError>) {
+        let mut verbank = usebank.clone();
+        let failed = bnk_recalc_seeded(&mut verbank, seed, version);
+        (verbank, failed)
+    }
 
-        menubar.add(
-            "Bank/New\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                bnk_create();
-                bnk_refresh_widgets();
-            },
-        );
+    /// The seed line printed under each heading, so the version can be
+    /// regenerated later.  Empty if the bank's values didn't come from a seed.
+    fn export_seed_note(usebank: &Bank, label: &str) -> String {
+        match usebank.seed {
+            Some(seed) => format!("Seed {}, version {}", seed, label),
+            None => String::new(),
+        }
+    }
 
-        menubar.add(
-            "Bank/Open\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                bnk_read();
-                bnk_refresh_widgets();
-            },
-        );
+    /// Renders a worksheet as text.  The `format` may be "text" or "markdown".
+    ///
+    pub fn export_worksheet(usebank: &Bank, label: &str, format: &str) -> String {
+        let mut out = String::new();
+
+        match format {
+            "markdown" => {
+                out.push_str(&format!("# {} -- Version {}\n\n", usebank.bank_title, label));
+                if !usebank.associated_textbook.is_empty() {
+                    out.push_str(&format!("*{}*\n\n", usebank.associated_textbook));
+                }
+                out.push_str("Name: ______________________    Date: ____________\n\n");
+                for (idx, quest) in usebank.question_vec.iter().enumerate() {
+                    out.push_str(&format!("{}. {}\n\n", idx + 1, qst_render_text(quest, &usebank.locale)));
+                    let choices = qst_render_choices(quest, &usebank.locale);
+                    if !choices.is_empty() {
+                        for line in choices.iter() {
+                            out.push_str(&format!("    {}  \n", line));  // Trailing spaces force a line break.
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+            _ => {
+                out.push_str(&format!("{} -- Version {}\n", usebank.bank_title, label));
+                if !usebank.associated_textbook.is_empty() {
+                    out.push_str(&format!("{}\n", usebank.associated_textbook));
+                }
+                out.push_str("\nName: ______________________    Date: ____________\n\n");
+                for (idx, quest) in usebank.question_vec.iter().enumerate() {
+                    out.push_str(&format!("{}.  {}\n\n", idx + 1, qst_render_text(quest, &usebank.locale)));
+                    let choices = qst_render_choices(quest, &usebank.locale);
+                    if !choices.is_empty() {
+                        for line in choices.iter() {
+                            out.push_str(&format!("      {}\n", line));
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+        }
 
-        // TODO: Add  ctrl-s  as option for saving.
-        // TODO: Right now  Save  and  Save-As  are the same thing.
-        //          Differentiate them.
+        let seednote = export_seed_note(usebank, label);  // At the bottom, out of the students' way.
+        if !seednote.is_empty() {
+            out.push_str(&format!("({})\n\n", seednote));
+        }
 
-        menubar.add(
-            "Bank/Save\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| {
-                bnk_save();
-                println!("/n The question bank has been saved. \n")
-            },
-        );
-        menubar.add(
-            "Bank/Save-as\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| {
-                bnk_save();
-                println!("/n The question bank has been saved. \n")
-            },
-        );
+        out
+    }
 
-        menubar.add(
-            "Bank/Recalculate\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("\n Not yet implemented. \n"),
-        );
+    /// Returns a copy of a Bank holding only the questions at the given
+    /// indexes, in that order.  Indexes past the end are ignored.
+    pub fn export_select_questions(usebank: &Bank, picks: &[usize]) -> Bank {
+        let mut selbank = usebank.clone();
+        selbank.question_vec = picks.iter().filter_map(|idx| usebank.question_vec.get(*idx).cloned()).collect();
+        selbank
+    }
+
+    /// Renders the answer key for a worksheet.  The `format` may be "text" or "markdown".
+    ///
+    pub fn export_answer_key(usebank: &Bank, label: &str, format: &str) -> String {
+        let mut out = String::new();
+
+        match format {
+            "markdown" => {
+                out.push_str(&format!("## Answer Key -- {} -- Version {}\n\n", usebank.bank_title, label));
+                let seednote = export_seed_note(usebank, label);
+                if !seednote.is_empty() {
+                    out.push_str(&format!("*{}*\n\n", seednote));
+                }
+                for (idx, quest) in usebank.question_vec.iter().enumerate() {
+                    out.push_str(&format!("{}. {}\n", idx + 1, qst_answer_text(quest, &usebank.locale)));
+                }
+            }
+            _ => {
+                out.push_str(&format!("Answer Key -- {} -- Version {}\n", usebank.bank_title, label));
+                out.push_str(&format!("{}\n\n", export_seed_note(usebank, label)));
+                for (idx, quest) in usebank.question_vec.iter().enumerate() {
+                    out.push_str(&format!("{}.  {}\n", idx + 1, qst_answer_text(quest, &usebank.locale)));
+                }
+            }
+        }
+        out.push('\n');
+
+        out
+    }
+
+    // region PDF
+
+    /// Adds a worksheet to a PDF, starting on a new page:  a heading with the
+    /// bank's title & textbook, name & date lines, then the numbered questions,
+    /// each followed by `work_lines` of space.  A question is never split
+    /// across pages if it fits on one.
+    pub fn export_worksheet_pdf(doc: &mut PdfDoc, usebank: &Bank, label: &str, layout: &PdfLayout) {
+        let size = layout.font_size;
+        let mut cur = export_pdf_start(doc, layout, usebank, label);
+
+        let mut heading = vec![PdfRow { pieces: vec![(0.0, format!("{} -- Version {}", usebank.bank_title, label), PdfFont::Bold)], size: size * 1.5 }];
+        if !usebank.associated_textbook.is_empty() {
+            heading.push(PdfRow { pieces: vec![(0.0, usebank.associated_textbook.clone(), PdfFont::Italic)], size });
+        }
+        heading.push(PdfRow { pieces: Vec::new(), size });
+        heading.push(PdfRow { pieces: vec![(0.0, "Name: ______________________________    Date: ______________".to_string(), PdfFont::Regular)], size });
+        heading.push(PdfRow { pieces: Vec::new(), size });
+        export_pdf_rows(&mut cur, &heading);
+
+        let indent = size * 2.5;  // Question text hangs past its number.
+        for (idx, quest) in usebank.question_vec.iter().enumerate() {
+            let mut rows = export_pdf_numbered(&cur, idx + 1, &qst_render_text(quest, &usebank.locale), indent, PdfFont::Regular);
+            for line in qst_render_choices(quest, &usebank.locale).iter() {
+                let width = export_pdf_width(&cur) - indent - size * 1.5;
+                for (lineidx, part) in pdf_wrap(line, size, PdfFont::Regular, width).into_iter().enumerate() {
+                    let xpos = if lineidx == 0 { indent } else { indent + size * 1.5 };  // Wrapped choices hang too.
+                    rows.push(PdfRow { pieces: vec![(xpos, part, PdfFont::Regular)], size });
+                }
+            }
+            rows.push(PdfRow { pieces: Vec::new(), size });
+            export_pdf_rows(&mut cur, &rows);
+            cur.ypos -= layout.work_lines as f64 * export_pdf_line_height(size);
+        }
+    }
+
+    /// Adds the answer key for a worksheet to a PDF, starting on a new page.
+    ///
+    pub fn export_answer_key_pdf(doc: &mut PdfDoc, usebank: &Bank, label: &str, layout: &PdfLayout) {
+        let size = layout.font_size;
+        let mut cur = export_pdf_start(doc, layout, usebank, label);
+
+        let mut heading = vec![PdfRow { pieces: vec![(0.0, format!("Answer Key -- {} -- Version {}", usebank.bank_title, label), PdfFont::Bold)], size: size * 1.5 }];
+        let seednote = export_seed_note(usebank, label);
+        if !seednote.is_empty() {
+            heading.push(PdfRow { pieces: vec![(0.0, seednote, PdfFont::Italic)], size });
+        }
+        heading.push(PdfRow { pieces: Vec::new(), size });
+        export_pdf_rows(&mut cur, &heading);
+
+        for (idx, quest) in usebank.question_vec.iter().enumerate() {
+            let rows = export_pdf_numbered(&cur, idx + 1, &qst_answer_text(quest, &usebank.locale), size * 2.5, PdfFont::Regular);
+            export_pdf_rows(&mut cur, &rows);
+        }
+    }
+
+    /// Starts a version on a new page.  Its pages are numbered from 1, with
+    /// the seed note in the footer so the version can be regenerated.
+    fn export_pdf_start<'a>(doc: &'a mut PdfDoc, layout: &'a PdfLayout, usebank: &Bank, label: &str) -> PdfCursor<'a> {
+        let seednote = export_seed_note(usebank, label);
+        let footer = if seednote.is_empty() { format!("Version {}", label) } else { seednote };
+        let mut cur = PdfCursor { doc, layout, footer, page: 0, ypos: 0.0 };
+        export_pdf_next_page(&mut cur);
+        cur
+    }
+
+    /// Moves on to a new page & prints its footer.
+    ///
+    fn export_pdf_next_page(cur: &mut PdfCursor) {
+        pdf_new_page(cur.doc);
+        cur.page += 1;
+        let (width, height) = cur.layout.paper.size();
+        let footer = format!("{}  --  Page {}", cur.footer, cur.page);
+        let xpos = (width - pdf_text_width(&footer, 9.0, PdfFont::Regular)) / 2.0;
+        pdf_text(cur.doc, xpos, EXPORT_PDF_MARGIN / 2.0, 9.0, PdfFont::Regular, &footer);
+        cur.ypos = height - EXPORT_PDF_MARGIN;
+    }
+
+    /// Returns the width of the text area between the margins.
+    ///
+    fn export_pdf_width(cur: &PdfCursor) -> f64 {
+        cur.layout.paper.size().0 - 2.0 * EXPORT_PDF_MARGIN
+    }
+
+    /// Returns the distance between the baselines of lines of text.
+    ///
+    fn export_pdf_line_height(size: f64) -> f64 {
+        size * 1.4
+    }
+
+    /// Breaks text into rows with its number at the margin and the text
+    /// lined up `indent` points in.
+    fn export_pdf_numbered(cur: &PdfCursor, num: usize, text: &str, indent: f64, font: PdfFont) -> Vec<PdfRow> {
+        let size = cur.layout.font_size;
+        pdf_wrap(text, size, font, export_pdf_width(cur) - indent).into_iter()
+            .enumerate()
+            .map(|(lineidx, line)| {
+                let mut pieces = vec![(indent, line, font)];
+                if lineidx == 0 {
+                    pieces.insert(0, (0.0, format!("{}.", num), font));
+                }
+                PdfRow { pieces, size }
+            })
+            .collect()
+    }
+
+    /// Writes rows of text, keeping them together on one page when they fit
+    /// on a page by themselves.
+    fn export_pdf_rows(cur: &mut PdfCursor, rows: &[PdfRow]) {
+        let (_, height) = cur.layout.paper.size();
+        let top = height - EXPORT_PDF_MARGIN;
+        let needed: f64 = rows.iter().map(|row| export_pdf_line_height(row.size)).sum();
+        if cur.ypos - needed < EXPORT_PDF_MARGIN && needed <= top - EXPORT_PDF_MARGIN && cur.ypos < top {
+            export_pdf_next_page(cur);
+        }
+
+        for row in rows.iter() {
+            let step = export_pdf_line_height(row.size);
+            if cur.ypos - step < EXPORT_PDF_MARGIN {
+                export_pdf_next_page(cur);
+            }
+            cur.ypos -= step;
+            for (xpos, text, font) in row.pieces.iter() {
+                pdf_text(cur.doc, EXPORT_PDF_MARGIN + xpos, cur.ypos, row.size, *font, text);
+            }
+        }
+    }
+
+    // endregion
+
+    // region LaTeX
+
+    /// Writes a document holding a worksheet for each Bank in `verbanks`,
+    /// paired with its version label.  Each version starts on a new page.
+    /// With `options.answers`, each question is followed by an `\answer{}`
+    /// that is only printed when the document says `\printanswers`, so
+    /// the same file makes the answer key.
+    pub fn export_latex_document(verbanks: &[(Bank, String)], options: &LatexOptions) -> String {
+        let mut out = format!("\\documentclass[11pt]{{{}}}\n", options.class.name());
+        out.push_str("\\usepackage{amsmath}\n\\usepackage{textcomp}\n");
+
+        // The exam class has its own answer switch;  an article needs one made.
+        let (setup, show) = match options.class {
+            LatexClass::Exam => ("", "\\printanswers"),
+            LatexClass::Article => ("\\newif\\ifprintanswers\n", "\\printanswerstrue"),
+        };
+        out.push_str(setup);
+        if options.show_answers {
+            out.push_str(&format!("{}\n", show));
+        } else if options.answers {
+            out.push_str(&format!("% {}  % Uncomment to print the answers.\n", show));
+        }
+        if options.answers {
+            out.push_str("\\newcommand{\\answer}[1]{\\ifprintanswers\\par\\noindent\\textbf{Answer:} #1\\fi}\n");
+        }
+
+        out.push_str("\n\\begin{document}\n");
+        for (idx, (verbank, label)) in verbanks.iter().enumerate() {
+            if idx > 0 {
+                out.push_str("\n\\clearpage\n");
+            }
+            out.push_str(&export_latex_version(verbank, label, options));
+        }
+        out.push_str("\\end{document}\n");
+
+        out
+    }
+
+    /// Writes the heading & questions of one version.
+    ///
+    fn export_latex_version(usebank: &Bank, label: &str, options: &LatexOptions) -> String {
+        let locale = &usebank.locale;
+        let mut out = String::new();
+
+        // region Heading
+        out.push_str("\\begin{center}\n");
+        out.push_str(&format!("{{\\Large\\bfseries {} -- Version {}}}", export_latex_escape(&usebank.bank_title), label));
+        if usebank.associated_textbook.is_empty() {
+            out.push('\n');
+        } else {
+            out.push_str(&format!("\\\\[0.5ex]\n\\textit{{{}}}\n", export_latex_escape(&usebank.associated_textbook)));
+        }
+        out.push_str("\\end{center}\n");
+        out.push_str("\\noindent Name: \\rule{0.45\\textwidth}{0.4pt}\\hfill Date: \\rule{0.2\\textwidth}{0.4pt}\n\\bigskip\n\n");
+        // endregion
+
+        let (list, item) = match options.class {
+            LatexClass::Exam => ("questions", "\\question"),
+            LatexClass::Article => ("enumerate", "\\item"),
+        };
+        out.push_str(&format!("\\begin{{{}}}\n", list));
+        for quest in usebank.question_vec.iter() {
+            out.push_str(&format!("{} {}\n", item, export_latex_text(&quest.qtext, &quest.var_vec, locale).trim_end()));
+            out.push_str(&export_latex_choices(quest, locale, options));
+            if options.answers {
+                out.push_str(&format!("\\answer{{{}}}\n", export_latex_value(&qst_answer_text(quest, locale), false)));
+            }
+            if options.work_lines > 0 {
+                out.push_str(&format!("\\vspace{{{}\\baselineskip}}\n", options.work_lines));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("\\end{{{}}}\n", list));
 
-        menubar.add(
-            "Bank/Export\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("\n Not yet implemented. \n"),
-        );
+        let seednote = export_seed_note(usebank, label);
+        if !seednote.is_empty() {
+            out.push_str(&format!("\\vfill\n\\begin{{center}}\\footnotesize {}\\end{{center}}\n", seednote));
+        }
 
-        //endregion
+        out
+    }
 
-        //region Question Section
+    /// Writes the options of a multiple choice, true/false, or matching
+    /// question.  The exam class marks the correct options, which it shows
+    /// when the answers are printed.
+    fn export_latex_choices(quest: &Question, locale: &NumLocale, options: &LatexOptions) -> String {
+        let is_exam = options.class == LatexClass::Exam;
+        let choice_cmd = |is_correct: bool| if is_correct && options.answers { "\\CorrectChoice" } else { "\\choice" };
+        let mut out = String::new();
+
+        match &quest.kind {
+            QuestionKind::MultipleChoice { .. } if is_exam => {
+                out.push_str("\\begin{choices}\n");
+                for (idx, option) in quest.choices.iter().enumerate() {
+                    out.push_str(&format!("  {} {}\n", choice_cmd(quest.choice_key.contains(&idx)), export_latex_value(option, false)));
+                }
+                out.push_str("\\end{choices}\n");
+            }
+            QuestionKind::MultipleChoice { .. } => {
+                out.push_str("\\begin{itemize}\n");
+                for (idx, option) in quest.choices.iter().enumerate() {
+                    out.push_str(&format!("  \\item[{}.] {}\n", qst_choice_label(idx), export_latex_value(option, false)));
+                }
+                out.push_str("\\end{itemize}\n");
+            }
+            QuestionKind::TrueFalse { correct } if is_exam => {
+                out.push_str(&format!("\\begin{{oneparchoices}}\n  {} True\n  {} False\n\\end{{oneparchoices}}\n",
+                                      choice_cmd(*correct), choice_cmd(!*correct)));
+            }
+            QuestionKind::TrueFalse { .. } => out.push_str("\n\\medskip\nTrue \\qquad False\n"),
+            QuestionKind::Matching { pairs } => {
+                out.push_str("\n\\medskip\n\\begin{tabular}{ll}\n");
+                for (idx, (prompt, _)) in pairs.iter().enumerate() {
+                    let rgt = quest.choices.get(idx).map(|item| export_latex_value(item, false)).unwrap_or_default();
+                    out.push_str(&format!("{}. {} & {}. {} \\\\\n", idx + 1, export_latex_text(prompt, &quest.var_vec, locale),
+                                          qst_choice_label(idx), rgt));
+                }
+                out.push_str("\\end{tabular}\n");
+            }
+            QuestionKind::FreeResponse | QuestionKind::Numeric { .. } => {}
+        }
 
-        menubar.add(
-            "Question/New\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                if check_for_bank_loaded() {
-                    qst_create();
-                    bnk_refresh_widgets();
+        out
+    }
+
+    /// Writes question text for LaTeX, putting in the values of its Variables.
+    /// Text between a pair of `$` signs is math and is kept as it was typed,
+    /// with the values written as math, e.g. fractions as `\frac{3}{4}`.  The
+    /// rest is escaped, so  50% & #1  print as typed.  A `\$`, or a `$`
+    /// without a partner, is a dollar sign.
+    pub fn export_latex_text(text: &str, var_vec: &[Variable], locale: &NumLocale) -> String {
+        let pieces: Vec<&str> = text.split('§').collect();
+        let is_flagged = |idx: usize| idx % 2 == 1 && idx < pieces.len() - 1;  // An unpaired flag is just text.
+
+        let count_dollars = |piece: &str| piece.match_indices('$').filter(|(pos, _)| !piece[..*pos].ends_with('\\')).count();
+        let total: usize = pieces.iter().enumerate().filter(|(idx, _)| !is_flagged(*idx)).map(|(_, piece)| count_dollars(piece)).sum();
+        let mut math_dollars = total - total % 2;  // The last one is a plain dollar sign if they don't pair up.
+        let mut in_math = false;
+
+        let mut out = String::new();
+        let mut prev: Option<char> = None;  // The last character written, including values.
+        for (idx, piece) in pieces.iter().enumerate() {
+            if is_flagged(idx) {
+                match qst_flag_value(piece, var_vec, locale) {
+                    Some(value) => {
+                        out.push_str(&export_latex_value(&value, in_math));
+                        prev = value.chars().last().or(prev);
+                    }
+                    None => {
+                        out.push_str(&export_latex_escape(&format!("§{}§", piece)));
+                        prev = Some('§');
+                    }
                 }
+                continue;
+            }
+            if idx % 2 == 1 {
+                out.push_str(&export_latex_escape("§"));  // The unpaired flag.
+                prev = Some('§');
+            }
 
-                // Keep the window display open after this function finishes.
-                //  while primwin1.shown() {
-                //      app::wait();
-                //   }
-            },
-        );
+            let mut chars = piece.chars().peekable();
+            while let Some(ch) = chars.next() {
+                if ch == '\\' && chars.peek() == Some(&'$') {
+                    chars.next();
+                    out.push_str("\\$");
+                } else if ch == '$' && math_dollars > 0 {
+                    math_dollars -= 1;
+                    in_math = !in_math;
+                    out.push('$');
+                } else if in_math {
+                    out.push(ch);
+                } else if matches!(ch, '-' | '−')
+                    && prev.is_none_or(|before| before.is_whitespace() || "([{/=:;".contains(before))
+                    && chars.peek().is_some_and(|next| next.is_ascii_digit())
+                {
+                    // A negative number, e.g.  -5x,  needs a minus sign rather than a hyphen.
+                    let mut number = String::new();
+                    while let Some(next) = chars.peek().copied()
+                        && (next.is_ascii_digit() || ".,\u{2009}\u{202F}".contains(next))
+                    {
+                        number.push(next);
+                        chars.next();
+                    }
+                    let kept = number.trim_end_matches(['.', ',', '\u{2009}', '\u{202F}']).len();
+                    let tail = number.split_off(kept);  // Punctuation that ends the sentence.
+                    out.push_str(&format!("$-{}$", export_latex_math_number(&number)));
+                    out.push_str(&export_latex_escape(&tail));
+                    prev = tail.chars().last().or(number.chars().last());
+                    continue;
+                } else {
+                    out.push_str(&export_latex_escape(&ch.to_string()));
+                }
+                prev = Some(ch);
+            }
+        }
 
-        menubar.add(
-            "Question/Recalculate\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("Not yet implemented.  Recalculating dynamic content in a Question."),
-        );
+        out
+    }
 
-        //endregion
+    /// Writes a value or answer for LaTeX.  Fractions, powers of ten, & the
+    /// × and ± signs become math;  units & other words stay text.  With
+    /// `in_math`, the whole value is written for use inside a math region.
+    fn export_latex_value(value: &str, in_math: bool) -> String {
+        let words: Vec<(bool, String)> = value.split(' ')
+            .filter(|word| !word.is_empty())
+            .map(export_latex_word)
+            .collect();
+
+        let mut out = String::new();
+        if in_math {
+            let numeric = |word: &str| word.chars().all(|ch| "0123456789.,-+\u{2009}\u{202F}".contains(ch));
+            let mut last_text = false;
+            for (idx, (is_math, word)) in words.iter().enumerate() {
+                let (latex, is_text) = if *is_math {
+                    (word.clone(), false)
+                } else if numeric(word) {
+                    (export_latex_math_number(word), false)
+                } else {
+                    (format!("\\text{{{}}}", export_latex_escape(word)), true)
+                };
+                if idx > 0 {
+                    out.push_str(if is_text || last_text { "\\ " } else { " " });
+                }
+                out.push_str(&latex);
+                last_text = is_text;
+            }
+        } else {
+            let mut in_dollars = false;
+            for (idx, (is_math, word)) in words.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(if in_dollars && !is_math { "$ " } else { " " });
+                    if in_dollars && !is_math {
+                        in_dollars = false;
+                    }
+                }
+                if *is_math && !in_dollars {
+                    out.push('$');
+                    in_dollars = true;
+                }
+                out.push_str(&if *is_math { word.clone() } else { export_latex_escape(word) });
+            }
+            if in_dollars {
+                out.push('$');
+            }
+        }
 
-        //region Variable Section
+        out
+    }
 
-        menubar.add(
-            "Variable/New\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                vrbl_create();
-            },
-        );
+    /// Sorts out one word of a value:  returns `true` & its LaTeX if it is
+    /// math, or `false` & the word itself if it is text.
+    fn export_latex_word(word: &str) -> (bool, String) {
+        if word.contains("\\frac{") {
+            return (true, word.to_string());  // Already LaTeX, from a fraction Variable.
+        }
+        let (sign, unsigned) = match word.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", word),
+        };
+        if let Some((num, den)) = unsigned.split_once('/')
+            && !num.is_empty() && !den.is_empty()
+            && num.chars().chain(den.chars()).all(|ch| ch.is_ascii_digit()) {
+            return (true, format!("{}\\frac{{{}}}{{{}}}", sign, num, den));
+        }
+        if !sign.is_empty()
+            && unsigned.starts_with(|ch: char| ch.is_ascii_digit())
+            && unsigned.chars().all(|ch| ch.is_ascii_digit() || ".,\u{2009}\u{202F}".contains(ch))
+        {
+            return (true, format!("-{}", export_latex_math_number(unsigned)));  // A minus sign, not a hyphen.
+        }
+        if let Some(power) = word.strip_prefix("10^") && power.parse::<i32>().is_ok() {
+            return (true, format!("10^{{{}}}", power));
+        }
+        match word {
+            "×" => (true, "\\times".to_string()),
+            "±" => (true, "\\pm".to_string()),
+            _ => (false, word.to_string()),
+        }
+    }
 
-        /*
-        menubar.add(
-            "Variable/New/String\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                vrbl_create("Strings");
-            },
-        );
+    /// Writes the digits of a number for use in math, where a comma would be
+    /// followed by a space and a thin space must be spelled out.
+    fn export_latex_math_number(number: &str) -> String {
+        number.replace(',', "{,}").replace(['\u{2009}', '\u{202F}'], "\\,")
+    }
 
-        menubar.add(
-            "Variable/New/Characters\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                vrbl_create("chars");
-            },
-        );
+    /// Escapes the characters LaTeX treats specially so text prints as typed.
+    ///
+    fn export_latex_escape(text: &str) -> String {
+        let mut out = String::new();
+        for ch in text.chars() {
+            match ch {
+                '\\' => out.push_str("\\textbackslash{}"),
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                    out.push('\\');
+                    out.push(ch);
+                }
+                '~' => out.push_str("\\textasciitilde{}"),
+                '^' => out.push_str("\\textasciicircum{}"),
+                '<' => out.push_str("\\textless{}"),
+                '>' => out.push_str("\\textgreater{}"),
+                '|' => out.push_str("\\textbar{}"),
+                '§' => out.push_str("\\S{}"),
+                '€' => out.push_str("\\texteuro{}"),
+                '×' => out.push_str("$\\times$"),
+                '±' => out.push_str("$\\pm$"),
+                '−' => out.push_str("$-$"),
+                '\u{2009}' | '\u{202F}' => out.push_str("\\,"),  // Thin spaces used for digit grouping.
+                _ => out.push(ch),
+            }
+        }
+        out
+    }
 
-        menubar.add(
-            "Variable/New/Integers\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                vrbl_create("ints");
-            },
-        );
+    // endregion
 
-        menubar.add(
-            "Variable/New/Floats\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                vrbl_create("floats");
-            },
-        );
+    /// FLTK dialogs for printing & exporting worksheets.
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::*;
+        use crate::errors::err_join_messages;
+        use crate::global::{glob_check_lastdirused, glob_update_lastdirused};
+        use crate::math_functions::math_new_seed;
+        use crate::CURRENT_BANK;
+        use fltk::button::{Button, CheckButton};
+        use fltk::enums::Color;
+        use fltk::input::{Input, IntInput};
+        use fltk::menu::Choice;
+        use fltk::prelude::{GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt, WindowExt};
+        use fltk::{app, window::Window};
+        use lib_file::file_fltk::file_browse_tosave;
+        use lib_myfltk::fltkutils::fltk_custom_message;
+        use std::cell::RefCell;
+        use std::path::Path;
+        use std::rc::Rc;
+
+        /// The choices made in the export dialog.
+        ///
+        struct ExportChoices {
+            format: String,  // "pdf", "text", "markdown", or "latex"
+            versions: usize,
+            seed: u64,
+            layout: PdfLayout,
+            latex: LatexOptions,
+            separate_key: bool,
+        }
 
-        menubar.add(
-            "Variable/Recalculate\t",  // Does this make sense as a user task?  Yes, definitely.
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| vrbl_recalc(),
-        );
+        /// Writes worksheets & answer keys for the current bank -- or for just
+        /// the questions at the indexes in `picks` -- after asking for the
+        /// format, number of versions, seed, & layout and where to save them.
+        pub fn export_dialog(picks: Option<Vec<usize>>) {
+            let usebank;
+            {
+                usebank = CURRENT_BANK.lock().unwrap().clone();
+            }
+            let usebank = match &picks {
+                Some(picks) => export_select_questions(&usebank, picks),
+                None => usebank,
+            };
 
-        //endregion
+            let Some(choices) = export_ask_choices() else {
+                return;
+            };
 
-         */
-        // Uneeded sub menu items.
-        // endregion
+            // region Choose where to save.
+            let ext = match choices.format.as_str() { "pdf" => "pdf", "markdown" => "md", "latex" => "tex", _ => "txt" };
+            let lastdir = glob_check_lastdirused("General");
+            let filters = vec!["PDF", "*.pdf", "Text", "*.txt", "Markdown", "*.md", "LaTeX", "*.tex", "All Files", "*.*"];
+            let usepath = file_browse_tosave(&lastdir, &format!("{}.{}", usebank.bank_title, ext), &filters);
+            if usepath.is_empty() {
+                return;
+            }
+            glob_update_lastdirused("General", &usepath);
+            let stem = Path::new(&usepath).with_extension("");
+            let keypath = format!("{} - Key.{}", stem.to_string_lossy(), ext);
+            // endregion
 
-        //region List Section
+            // region Build & write each version.
+            let mut sheettxt = String::new();
+            let mut keytxt = String::new();
+            let mut sheetpdf = PdfDoc::new(choices.layout.paper);
+            let mut keypdf = PdfDoc::new(choices.layout.paper);
+            let mut verbanks: Vec<(Bank, String)> = Vec::new();
+            for version in 0..choices.versions {
+                let label = export_version_label(version);
+                let (verbank, failed) = export_make_version(&usebank, choices.seed, version);
+                if !failed.is_empty() {
+                    let msg = format!("These variables could not be recalculated:\n{}", err_join_messages(&failed));
+                    fltk_custom_message(&msg, "Return to the main menu.");
+                    return;
+                }
+                match choices.format.as_str() {
+                    "pdf" => export_worksheet_pdf(&mut sheetpdf, &verbank, &label, &choices.layout),
+                    "latex" => {}  // The whole document is written at once below.
+                    _ => {
+                        sheettxt.push_str(&export_worksheet(&verbank, &label, &choices.format));
+                        keytxt.push_str(&export_answer_key(&verbank, &label, &choices.format));
+                    }
+                }
+                verbanks.push((verbank, label));
+            }
 
-        menubar.add(
-            "List/New/Strings\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                list_create("Strings");
-            },
-        );
+            let (sheetbytes, keybytes) = if choices.format == "pdf" {
+                let keydoc = if choices.separate_key { &mut keypdf } else { &mut sheetpdf };  // Else the keys follow the worksheets.
+                for (verbank, label) in verbanks.iter() {
+                    export_answer_key_pdf(keydoc, verbank, label, &choices.layout);
+                }
+                (pdf_finish(&sheetpdf), pdf_finish(&keypdf))
+            } else if choices.format == "latex" {
+                // The key is the same document with its answers printed.
+                let keyoptions = LatexOptions { answers: true, show_answers: true, ..choices.latex.clone() };
+                (export_latex_document(&verbanks, &choices.latex).into_bytes(),
+                 export_latex_document(&verbanks, &keyoptions).into_bytes())
+            } else if choices.separate_key {
+                (sheettxt.into_bytes(), keytxt.into_bytes())
+            } else {
+                (format!("{}{}", sheettxt, keytxt).into_bytes(), Vec::new())
+            };
 
-        menubar.add(
-            "List/New/Characters\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                list_create("chars");
-            },
-        );
+            let mut written = std::fs::write(&usepath, sheetbytes).map_err(|err| QbcError::io(&usepath, err));
+            if written.is_ok() && choices.separate_key {
+                written = std::fs::write(&keypath, keybytes).map_err(|err| QbcError::io(&keypath, err));
+            }
+            match written {
+                Ok(()) => {
+                    let mut msg = format!("{} version(s) were written to\n{}", choices.versions, usepath);
+                    if choices.separate_key {
+                        msg.push_str(&format!("\nwith the answer keys in\n{}", keypath));
+                    }
+                    fltk_custom_message(&format!("{}\n\nSeed:  {}", msg, choices.seed), "OK");
+                }
+                Err(err) => fltk_custom_message(&err.to_string(), "Return to the main menu."),
+            }
+            // endregion
+        }
 
-        menubar.add(
-            "List/New/Integers\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                list_create("ints");
-            },
-        );
+        /// Asks for the format, number of versions, seed, & layout of the
+        /// worksheets.  Returns `None` if the dialog was cancelled.
+        fn export_ask_choices() -> Option<ExportChoices> {
+            let mut win = Window::new(900, 100, 420, 375, "Print / Export");
+            win.set_color(Color::Cyan);
+            win.make_modal(true);
+
+            let mut format = Choice::new(200, 20, 160, 25, "Format:");
+            format.add_choice("PDF|Text|Markdown|LaTeX");
+            format.set_value(0);
+            let mut versions = IntInput::new(200, 60, 80, 25, "Versions:");
+            versions.set_value("1");
+            let mut seed = Input::new(200, 100, 160, 25, "Seed (blank for new):");
+            seed.set_tooltip("Use a seed from an earlier printing to make the same versions again.");
+            let mut worklines = IntInput::new(200, 140, 80, 25, "Lines for work:");
+            worklines.set_value(&PdfLayout::new().work_lines.to_string());
+            let mut paper = Choice::new(200, 180, 160, 25, "Paper (PDF):");
+            for item in Paper::ALL.iter() {
+                paper.add_choice(item.name());
+            }
+            paper.set_value(0);
+            let mut class = Choice::new(200, 220, 160, 25, "LaTeX class:");
+            for item in LatexClass::ALL.iter() {
+                class.add_choice(item.name());
+            }
+            class.set_value(0);
+            let separate = CheckButton::new(200, 255, 200, 25, "Answer key in its own file");
+            separate.set_checked(true);
+
+            let mut ok_btn = Button::new(110, 305, 90, 40, "OK");
+            let mut cancel_btn = Button::new(220, 305, 90, 40, "Cancel");
+            win.end();
+            win.show();
+
+            let chosen: Rc<RefCell<Option<ExportChoices>>> = Rc::new(RefCell::new(None));
+
+            let mut win_clone = win.clone();
+            cancel_btn.set_callback(move |_| win_clone.hide());
+
+            let mut win_clone = win.clone();
+            let chosen_clone = chosen.clone();
+            ok_btn.set_callback(move |_| {
+                let seedtxt = seed.value();
+                let parsed = (
+                    versions.value().trim().parse::<usize>().ok().filter(|num| *num > 0),
+                    if seedtxt.trim().is_empty() { Some(math_new_seed()) } else { seedtxt.trim().parse::<u64>().ok() },
+                    worklines.value().trim().parse::<usize>().ok(),
+                );
+                let (Some(numversions), Some(useseed), Some(numlines)) = parsed else {
+                    fltk_custom_message("The versions, seed, and lines for work must be whole numbers.", "Return to the dialog.");
+                    return;
+                };
+                let layout = PdfLayout {
+                    paper: Paper::ALL[paper.value().max(0) as usize],
+                    work_lines: numlines,
+                    ..PdfLayout::new()
+                };
+                let latex = LatexOptions {
+                    class: LatexClass::ALL[class.value().max(0) as usize],
+                    work_lines: numlines,
+                    ..LatexOptions::new()
+                };
+                let useformat = ["pdf", "text", "markdown", "latex"][format.value().max(0) as usize];
+                *chosen_clone.borrow_mut() = Some(ExportChoices {
+                    format: useformat.to_string(),
+                    versions: numversions,
+                    seed: useseed,
+                    layout,
+                    latex,
+                    separate_key: separate.is_checked(),
+                });
+                win_clone.hide();
+            });
 
-        menubar.add(
-            "List/New/Floats\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            move |_| {
-                list_create("floats");
-            },
-        );
+            while win.shown() {
+                app::wait();
+            }
 
-        menubar.add(
-            "List/Edit\t",
-            Shortcut::None,
-            menu::MenuFlag::Normal,
-            |_| println!("\n List editing will be added in a later iteration. \n"),
-        );
+            chosen.take()
+        }
 
-        // -------  End list section
-        //endregion
+    } // End   gui   submodule
 
-        menubar
-    }
-} //  End   menus  module
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::variable::test_int_var;
 
-/// Math-based functions.
-///
-pub mod math_functions {
-    use num_traits::pow;
-    use rand::distributions::uniform::SampleUniform;
-    use rand::{thread_rng, Rng};
+        #[test]
+        fn latex_text_escapes_outside_math() {
+            let var_vec = vec![test_int_var("rate", 15, 15)];
+            assert_eq!(export_latex_text("Save §rate§% & pay #1 in $x_1 + §rate§$ or \\$5", &var_vec, &NumLocale::new()),
+                       "Save 15\\% \\& pay \\#1 in $x_1 + 15$ or \\$5");
+            assert_eq!(export_latex_text("It costs $3.", &var_vec, &NumLocale::new()), "It costs \\$3.");
+        }
 
-    /// Round an f64 to a given decimal place.
-    ///
-    pub fn math_round_to_place_f64(num: &f64, place: usize) -> f64 {
-        let factor = pow(10, place);
-        
-        (num * factor as f64).round() / factor as f64
-    }
+        #[test]
+        fn latex_negative_numbers_use_minus_signs() {
+            let var_vec = vec![test_int_var("low", -4, -4)];
+            assert_eq!(export_latex_text("Solve -5x = 10 for x.", &var_vec, &NumLocale::new()), "Solve $-5$x = 10 for x.");
+            assert_eq!(export_latex_text("It fell to -2.5.  Pages 3-7 (-1)", &var_vec, &NumLocale::new()),
+                       "It fell to $-2.5$.  Pages 3-7 ($-1$)");
+            assert_eq!(export_latex_text("The low was §low§ degrees", &var_vec, &NumLocale::new()), "The low was $-4$ degrees");
+            assert_eq!(export_latex_text("$x = §low§$", &var_vec, &NumLocale::new()), "$x = -4$");
+            assert_eq!(export_latex_text("§low§-3 and §low§ -3", &var_vec, &NumLocale::new()), "$-4$-3 and $-4$ $-3$");
+        }
 
-    /// Generate and return a random number between the given min and max.
-    ///
-    /// Example:
-    ///
-    ///     fn main() {
-    ///         let choice: i64 = math_gen_random_num(-9, 9);
-    ///
-    ///         println!("\n Your random number is:   {} \n", choice);
-    ///     }
-    ///
-    pub fn math_gen_random_num<T: SampleUniform + PartialOrd>(min: T, max: T) -> T {
-        let mut rng = thread_rng();
-        rng.gen_range(min..=max)
+        #[test]
+        fn latex_values_become_math() {
+            assert_eq!(export_latex_value("-3/4 cup", false), "$-\\frac{3}{4}$ cup");
+            assert_eq!(export_latex_value("6.02 × 10^23 atoms", false), "6.02 $\\times 10^{23}$ atoms");
+            assert_eq!(export_latex_value("1 3/4 km/h", true), "1 \\frac{3}{4}\\ \\text{km/h}");
+        }
     }
-} // End   math_functions   module
 
-/// Miscellaneous functions used by other modules.
+} // End   export   module
+
+/// Miscellaneous functions used by the GUI.
 ///
+#[cfg(feature = "gui")]
 pub mod misc {
-    use crate::{banks::Bank, questions::qst_edit};
+    use crate::banks::{bnk_confirm_discard, bnk_refresh_widgets, Bank};
+    use crate::questions::{qst_delete, qst_duplicate, qst_edit, qst_move, qst_render_choices, qst_render_with_spans};
     use crate::{
-        Wdgts, CURRENT_BANK, DEVELOPMENT_VERSION, PROGRAM_TITLE, QDISP_HEIGHT, SCROLLBAR_WIDTH,
-        VERSION, WIDGETS,
+        Wdgts, CONFIG, CURRENT_BANK, DEVELOPMENT_VERSION, PROGRAM_TITLE, QDISP_HEIGHT,
+        SCROLLBAR_WIDTH, SHOW_VALUES, VERSION, WIDGETS,
     };
+    use fltk::enums::{Event, Font};
+    use fltk::misc::Tooltip;
     use fltk::prelude::{DisplayExt, GroupExt, WidgetBase, WidgetExt};
-    use fltk::text::{TextBuffer, TextDisplay, TextEditor};
-    use fltk::{button::Button, enums::Color, group::Scroll};
+    use fltk::text::{StyleTableEntry, TextBuffer, TextDisplay, TextEditor};
+    use fltk::{app, button::Button, enums::Color, group::Scroll};
     use fltk::{text, window::Window};
+    use std::collections::HashMap;
+    use std::ffi::{CStr, CString};
     use std::fs;
+    use std::sync::{LazyLock, Mutex};
+
+    /// The hover tips shown over variable values, one per variable name.  FLTK
+    /// needs a 'static tip, so each is made once and reused by every redraw.
+    static VALUE_TIPS: LazyLock<Mutex<HashMap<String, &'static CStr>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
 
     /// Gets and returns the text from a given FLTK TextEditor.
     ///
@@ -2092,6 +9405,12 @@ pub mod misc {
         );
         primwin.set_label(fulltitle.as_str());
         primwin.make_resizable(true);
+
+        primwin.set_callback(|_| {  // Closing the window asks about unsaved changes, like File/Quit.
+            if app::event() == Event::Close && bnk_confirm_discard() {
+                app::quit();
+            }
+        });
     }
 
 
@@ -2157,8 +9476,6 @@ pub mod misc {
     /// when displaying the questions in a question Bank.
     pub fn make_question_boxes() {
         // region TODO's
-        //todo: The question numbers are displaying weird.  First question's label doesn't
-        //          even show.  Work on that.  Next iteration.
         // TODO: Set up show/edit prereqs and objectives button
         // TODO: Create a subframe to display/edit the answer.
 
@@ -2166,23 +9483,37 @@ pub mod misc {
 
         let usebank: Bank;
         let mut wdgts: Wdgts;
+        let showvals: bool;
+        let textsize: i32;
         {
             usebank = CURRENT_BANK.lock().unwrap().clone();
             wdgts = WIDGETS.lock().unwrap().clone();
+            showvals = *SHOW_VALUES.lock().unwrap();
+            textsize = CONFIG.lock().unwrap().text_size;
         } // Access the global structs.
 
         // region Calculate size and position values.
         let mut box_y = wdgts.title_editbox.height() + 1; // Allow room for the Title Box
         box_y += 60; // Allow room for the label of the first question box.
-        let mut qnum = 1; // Question number -- starts at 1.
-                          // endregion
+        // endregion
 
-        // Set up a display box for each question in the bank.
-        for item in usebank.question_vec.iter() {
+        // Set up a display box for each question in the bank.  The questions are
+        //      numbered by their place in the bank, so they renumber themselves
+        //      whenever questions are moved, copied, or deleted.
+        for (qidx, item) in usebank.question_vec.iter().enumerate() {
+            let qnum = qidx + 1; // Question number -- starts at 1.
             // region Create the question label and set up text buffer.
             let qlabel = format!("Question {} :  ", qnum);
             let mut txtbuff = TextBuffer::default();
-            txtbuff.set_text(item.qtext.as_str());
+            let (mut qtext, spans) = if showvals {
+                qst_render_with_spans(item, &usebank.locale)
+            } else {
+                (item.qtext.clone(), Vec::new())
+            };
+            for line in qst_render_choices(item, &usebank.locale).iter() {  // Choices go below the question text.
+                qtext.push_str(&format!("\n      {}", line));
+            }
+            txtbuff.set_text(qtext.as_str());
             // endregion
 
             // region Setup the display box and it's attributes.
@@ -2193,11 +9524,14 @@ pub mod misc {
                 QDISP_HEIGHT,
                 qlabel.as_str(),
             );
-            quest_disp.set_buffer(txtbuff);
+            quest_disp.set_buffer(txtbuff.clone());
             quest_disp.wrap_mode(text::WrapMode::AtBounds, 0);
             quest_disp.set_color(fltk::enums::Color::White);
-            quest_disp.set_text_size(22);
+            quest_disp.set_text_size(textsize);
             quest_disp.set_text_color(fltk::enums::Color::Black);
+            if showvals {
+                qdisp_highlight_values(&mut quest_disp, &txtbuff, spans);
+            }
             // endregion
 
             // region Setup the edit button & callback. Buttons not added to widget struct.
@@ -2207,22 +9541,128 @@ pub mod misc {
 
             editbtn.set_callback(move |_| {
                 println!("\n Edit button for Question #{} has been pressed. \n", qnum);
-                qst_edit(qnum - 1);
+                qst_edit(qidx);
             });
             // endregion
 
+            // region Setup the Delete, Copy, Down, & Up buttons, right to left of the Edit button.
+            let mut btn_x = editbtn_x;
+            let mut qstbtns: Vec<Button> = Vec::new();
+            let actions = [
+                ("Delete", qst_delete as fn(usize)),
+                ("Copy", qst_duplicate),
+                ("Down", |idx| qst_move(idx, false)),
+                ("Up", |idx| qst_move(idx, true)),
+            ];
+            for (label, action) in actions.into_iter() {
+                btn_x -= 75;
+                let mut qstbtn = Button::new(btn_x, editbtn_y, 70, 30, label);
+                qstbtn.set_label_size(16);
+                qstbtn.set_callback(move |_| {
+                    action(qidx);
+                    qdisp_refresh_later();
+                });
+                qstbtns.push(qstbtn);
+            }
+            // endregion
+
             // region Increment values and push/add items to WIDGETS struct
-            box_y += QDISP_HEIGHT; // Increment the question display widget position.
-            qnum += 1; // Increment the question display number.
+            box_y += QDISP_HEIGHT + 30; // Leave room for the label of the next question box.
 
             wdgts.qstn_boxes.push(quest_disp.clone());
             wdgts.scroll.add(&quest_disp);
             wdgts.scroll.add(&editbtn);
+            for qstbtn in qstbtns.iter() {
+                wdgts.scroll.add(qstbtn);
+            }
             // endregion
         }
         *WIDGETS.lock().unwrap() = wdgts.clone(); // Update the WIDGET global variable.
     }
 
+    /// Rebuilds the bank display once the current callback has finished.  The
+    /// question buttons use this, since the rebuild deletes the buttons themselves.
+    pub fn qdisp_refresh_later() {
+        app::add_timeout3(0.0, |_| bnk_refresh_widgets());
+    }
+
+    /// Shows the bank's title in the primary window's title bar, with a `*`
+    /// when there are unsaved changes.
+    pub fn primwin_update_title() {
+        let mut primwin = WIDGETS.lock().unwrap().prim_win.clone();
+        let usebank = CURRENT_BANK.lock().unwrap().clone();
+
+        let mut fulltitle = format!("{} -- {} -- Version {}", DEVELOPMENT_VERSION, PROGRAM_TITLE, VERSION);
+        if !usebank.bank_title.is_empty() && usebank.bank_title != "No Bank Loaded" {
+            fulltitle = format!("{}{} -- {}", usebank.bank_title, if usebank.dirty { " *" } else { "" }, fulltitle);
+        }
+        primwin.set_label(fulltitle.as_str());
+    }
+
+    /// Highlights the substituted variable values in a question display box
+    /// and shows the name of the variable when the mouse hovers over a value.
+    pub fn qdisp_highlight_values(quest_disp: &mut TextDisplay, txtbuff: &TextBuffer, spans: Vec<(usize, usize, String)>) {
+        // region Build the style buffer.  'A' is plain text, 'B' is a variable value.
+        let textlen = txtbuff.text().len();
+        let mut styles = vec![b'A'; textlen];
+        for (start, end, _) in spans.iter() {
+            for style in styles[*start..*end].iter_mut() {
+                *style = b'B';
+            }
+        }
+        let mut stylebuff = TextBuffer::default();
+        stylebuff.set_text(&String::from_utf8(styles).unwrap_or_default());
+
+        let size = quest_disp.text_size();
+        let entries = vec![
+            StyleTableEntry { color: Color::Black, font: Font::Helvetica, size },
+            StyleTableEntry { color: Color::DarkBlue, font: Font::HelveticaBold, size },
+        ];
+        quest_disp.set_highlight_data(stylebuff, entries);
+        // endregion
+
+        // region Show the variable name when hovering over a value.
+        let tips: Vec<(usize, usize, &'static CStr)> = {
+            let mut cache = VALUE_TIPS.lock().unwrap();
+            spans
+                .into_iter()
+                .map(|(start, end, varname)| {
+                    let tip = *cache.entry(varname).or_insert_with_key(|varname| {
+                        let tip = CString::new(format!("§{}§", varname)).unwrap_or_default();
+                        Box::leak(tip.into_boxed_c_str())
+                    });
+                    (start, end, tip)
+                })
+                .collect()
+        };
+
+        quest_disp.handle(move |disp, evt| {
+            if evt != Event::Move {
+                return false;
+            }
+            let (mouse_x, mouse_y) = app::event_coords();
+            let line_h = disp.text_size() + 4;
+            for (start, end, tip) in tips.iter() {
+                let (start_x, start_y) = disp.position_to_xy(*start as i32);
+                let (end_x, end_y) = disp.position_to_xy(*end as i32);
+                let on_line = mouse_y >= start_y && mouse_y < end_y.max(start_y) + line_h;
+                let in_span = if start_y == end_y {
+                    mouse_x >= start_x && mouse_x < end_x
+                } else {  // The value wraps onto the next line.
+                    (mouse_y < start_y + line_h && mouse_x >= start_x)
+                        || (mouse_y >= end_y && mouse_x < end_x)
+                };
+                if on_line && in_span {
+                    Tooltip::enter_area(disp, start_x - disp.x(), start_y - disp.y(),
+                                        (end_x - start_x).max(1), line_h, tip);
+                    return false;
+                }
+            }
+            false
+        });
+        // endregion
+    }
+
     /// Check to see whether or not a bank has been loaded into memory.
     ///
     pub fn check_for_bank_loaded() -> bool {
@@ -2261,14 +9701,14 @@ pub mod misc {
     ///
     /// #Example
     ///
-    ///     fn main() {
-    ///         let usepath = "/home/jtreagan/programming/mine/cards";
+    ///     use question_bank_creator::misc::dir_is_empty;
     ///
-    ///         if dir_is_empty(usepath) {
-    ///             println!("\n The path  {}  is empty \n", usepath);
-    ///         } else {
-    ///             println!("\n The path  {}  is not empty \n", usepath);
-    ///         }
+    ///     let usepath = "/home/jtreagan/programming/mine/cards";
+    ///
+    ///     if dir_is_empty(usepath) {
+    ///         println!("\n The path  {}  is empty \n", usepath);
+    ///     } else {
+    ///         println!("\n The path  {}  is not empty \n", usepath);
     ///     }
     ///
     pub fn dir_is_empty(path: &str) -> bool {
@@ -39,23 +39,29 @@
     -- Do you want to attach p's to associated o's?
         -- Yes.
 
-    //TODO: Provide for re-ordering/moving questions in a bank.
-
 */
 // Thoughts & Ideas
 
 use fltk::{app::*, prelude::WidgetExt, prelude::*};
+use question_bank_creator::config::{cnfg_startup, cnfg_store_geometry};
 use question_bank_creator::Wdgts;
 use question_bank_creator::{menus::*, misc::*};
 use question_bank_creator::{APP_FLTK, WIDGETS};
 
 fn main() {
+    // The configuration file may be given with  --config <file>.  Otherwise the
+    //      QBC_CONFIG environment variable or the XDG config folder is used.
+    let args: Vec<String> = std::env::args().collect();
+    let cnfg_arg = args.iter()
+        .position(|arg| arg == "--config" || arg == "-c")
+        .and_then(|idx| args.get(idx + 1));
+
     let app = App::default();
     {
         *APP_FLTK.lock().unwrap() = app;
     } // Store the app in the global variable.
 
-    set_font_size(20);
+    cnfg_startup(cnfg_arg.map(|path| path.as_str()));  // Loads the settings & sets the fonts.
     let mut wdgts = Wdgts::new();
     let menubar = qbnk_menubar(&mut wdgts.prim_win);
     wdgts.prim_win.add(&menubar);
@@ -68,4 +74,6 @@ fn main() {
     wdgts.prim_win.show();
 
     app.run().unwrap();
+
+    cnfg_store_geometry();  // Reopen the window in the same place next time.
 }